@@ -1,7 +1,8 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
+use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, Criterion, BenchmarkId};
 use mdi::{
-    Tick, KLineBuilder, RingBuffer, TickStorage,
-    Distributor,
+    Tick, KLineBuilder, KLineWorkers, RingBuffer, TickStorage,
+    Distributor, CpuAffinity, ValueFormat,
 };
 use std::sync::Arc;
 use tempfile::TempDir;
@@ -10,15 +11,6 @@ use tempfile::TempDir;
 
 fn bench_ring_buffer_push(c: &mut Criterion) {
     let buffer = RingBuffer::new(100000);
-    let tick = Tick::new(
-        "BTCUSDT".to_string(),
-        1000000,
-        1000000,
-        100.0,
-        1.0,
-        true,
-        1,
-    );
 
     c.bench_function("ringbuffer_push", |b| {
         b.iter(|| {
@@ -30,7 +22,7 @@ fn bench_ring_buffer_push(c: &mut Criterion) {
                     100.0 + (i as f64 * 0.01),
                     1.0,
                     true,
-                    i as u64,
+                    i,
                 ));
             }
             // 清空缓冲区
@@ -59,7 +51,7 @@ fn bench_ring_buffer_pop(c: &mut Criterion) {
             
             // 测试 pop
             let mut count = 0;
-            while let Some(_) = buffer.pop() {
+            while buffer.pop().is_some() {
                 count += 1;
             }
             black_box(count);
@@ -150,6 +142,51 @@ fn bench_kline_get_latest(c: &mut Criterion) {
     }
 }
 
+fn bench_kline_sharded_scaling(c: &mut Criterion) {
+    // 32 个品种，各 1000 笔
+    let ticks: Vec<Tick> = (0..32_000u64)
+        .map(|i| {
+            Tick::new(
+                format!("SYM{}USDT", i % 32),
+                1000000 + (i / 32) * 100,
+                1000000 + (i / 32) * 100,
+                100.0 + (i as f64 % 10.0 - 5.0) * 0.1,
+                1.0,
+                i % 2 == 0,
+                i,
+            )
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("kline_sharded_32_symbols");
+    group.sample_size(10);
+    let max_workers = CpuAffinity::num_cpus().min(8);
+    let mut num_workers = 1;
+    while num_workers <= max_workers {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_workers),
+            &num_workers,
+            |b, &num_workers| {
+                b.iter(|| {
+                    let workers = KLineWorkers::spawn(
+                        KLineBuilder::standard(),
+                        num_workers,
+                        |_, klines| {
+                            black_box(klines);
+                        },
+                    );
+                    for tick in &ticks {
+                        workers.dispatch(tick.clone()).unwrap();
+                    }
+                    workers.shutdown().unwrap();
+                });
+            },
+        );
+        num_workers *= 2;
+    }
+    group.finish();
+}
+
 // ============ RocksDB Storage 基准测试 ============
 
 fn bench_storage_write_single(c: &mut Criterion) {
@@ -225,6 +262,7 @@ fn bench_storage_read(c: &mut Criterion) {
 
 // ============ Distributor 基准测试 ============
 
+#[allow(dead_code)]
 #[tokio::main]
 async fn bench_distributor_broadcast() {
     use std::time::Instant;
@@ -260,6 +298,47 @@ async fn bench_distributor_broadcast() {
     println!("Per-event latency: {:.2} µs", (elapsed.as_secs_f64() * 1_000_000.0) / num_events as f64);
 }
 
+fn bench_storage_value_format(c: &mut Criterion) {
+    let ticks: Vec<Tick> = (0..1000u64)
+        .map(|i| {
+            Tick::new(
+                "BTCUSDT".to_string(),
+                1000000 + i * 100,
+                1000000 + i * 100,
+                43000.0 + (i as f64 % 10.0 - 5.0) * 0.1,
+                0.001 * (i % 17 + 1) as f64,
+                i % 2 == 0,
+                i,
+            )
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("storage_write_batch_1000_format");
+    for (name, format) in [("json", ValueFormat::Json), ("binary", ValueFormat::Binary)] {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("bench.db");
+        let storage = TickStorage::open_with_format(&path, format).unwrap();
+
+        group.bench_function(name, |b| {
+            b.iter(|| storage.write_ticks(black_box(&ticks)).unwrap());
+        });
+
+        // 写入 10 万笔后比较磁盘占用
+        let mut batch = ticks.clone();
+        for round in 0..100u64 {
+            for (i, tick) in batch.iter_mut().enumerate() {
+                tick.trade_id = round * 1000 + i as u64;
+            }
+            storage.write_ticks(&batch).unwrap();
+        }
+        // 刷新并压实后按 SST 总大小比较，不受 WAL 和未压实的覆盖写影响
+        storage.compact_all().unwrap();
+        let sst_bytes = storage.get_stats().unwrap().sst_bytes();
+        println!("storage_value_format/{}: {} SST bytes for 100k ticks", name, sst_bytes);
+    }
+    group.finish();
+}
+
 // ============ 综合基准测试 ============
 
 fn bench_full_pipeline(c: &mut Criterion) {
@@ -276,7 +355,7 @@ fn bench_full_pipeline(c: &mut Criterion) {
                         1000000 + (i as u64 * 100),
                         1000000 + (i as u64 * 100),
                         100.0 + (i as f64 % 10.0 - 5.0) * 0.1,
-                        (1.0 + (i as f64 % 9.0) * 0.01),
+                        1.0 + (i as f64 % 9.0) * 0.01,
                         i % 2 == 0,
                         i as u64,
                     );
@@ -304,9 +383,11 @@ criterion_group!(
     bench_ring_buffer_pop_batch,
     bench_kline_process_tick,
     bench_kline_get_latest,
+    bench_kline_sharded_scaling,
     bench_storage_write_single,
     bench_storage_write_batch,
     bench_storage_read,
+    bench_storage_value_format,
     bench_full_pipeline,
 );
 
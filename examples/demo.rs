@@ -1,18 +1,18 @@
-/// 演示示例：本地数据流模拟
-/// 
-/// 这个示例演示系统的核心功能，无需连接真实的 Binance WebSocket
-/// 包括：
-/// - K线合并
-/// - Lock-free 分发
-/// - RocksDB 存储
-/// - CPU Affinity 线程绑定
+//! 演示示例：本地数据流模拟
+//! 
+//! 这个示例演示系统的核心功能，无需连接真实的 Binance WebSocket
+//! 包括：
+//! - K线合并
+//! - Lock-free 分发
+//! - RocksDB 存储
+//! - CPU Affinity 线程绑定
 
 use mdi::{
     Tick, KLineBuilder, Distributor, TickStorage, RingBuffer,
     CpuAffinity, ThreadBuilder,
 };
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::Instant;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("=== MDI Market Data System Demo ===\n");
@@ -27,7 +27,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Initializing components...");
     let kline_builder = Arc::new(KLineBuilder::standard());
     let distributor = Arc::new(Distributor::new(1000));
-    let storage = Arc::new(TickStorage::open("./data/demo.db")?);
+    // 数据写入临时目录，示例结束后自动删除
+    let data_dir = tempfile::TempDir::new()?;
+    let db_path = data_dir.path().join("demo.db");
+    let storage = Arc::new(TickStorage::open(&db_path)?);
     let ring_buffer = Arc::new(RingBuffer::new(10000));
 
     // 3. 生成模拟数据
@@ -63,11 +66,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         let _ = ring_buffer.push(tick.clone());
 
         // 构建 K 线
-        let klines = kline_builder.process_tick(tick);
+        let events = kline_builder.process_tick(tick);
 
         // 分发 K 线
-        for kline in klines {
-            let _ = distributor.broadcast_kline(kline, false);
+        for event in events {
+            let _ = distributor.broadcast_kline(event.kline, event.is_closed);
         }
 
         stored_ticks.push(tick.clone());
@@ -164,7 +167,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!();
 
     println!("=== Demo Completed Successfully ===");
-    println!("Data saved to: ./data/demo.db");
+    println!("Data saved to: {} (removed on exit)", db_path.display());
 
     Ok(())
 }
@@ -1,5 +1,5 @@
-/// 本地性能基准测试
-/// 运行方式：cargo run --example perf_test --release
+//! 本地性能基准测试
+//! 运行方式：cargo run --example perf_test --release
 
 use mdi::{Tick, KLineBuilder, RingBuffer, TickStorage};
 use std::time::Instant;
@@ -92,7 +92,7 @@ fn bench_ring_buffer_pop() {
 
     let start = Instant::now();
     let mut pop_count = 0u64;
-    while let Some(_) = buffer.pop() {
+    while buffer.pop().is_some() {
         pop_count += 1;
     }
     let elapsed = start.elapsed();
@@ -158,7 +158,7 @@ fn bench_kline_builder() {
             1000000 + (i * 100),
             1000000 + (i * 100),
             100.0 + (i as f64 % 10.0 - 5.0) * 0.1,
-            (1.0 + (i as f64 % 9.0) * 0.01),
+            1.0 + (i as f64 % 9.0) * 0.01,
             i % 2 == 0,
             i,
         );
@@ -222,12 +222,12 @@ fn bench_storage() -> Result<(), Box<dyn std::error::Error>> {
         for i in 0..batch_size {
             let tick = Tick::new(
                 "BTCUSDT".to_string(),
-                1000000 + (batch_id * batch_size as u64) as u64 + i as u64,
-                1000000 + (batch_id * batch_size as u64) as u64 + i as u64,
+                1000000 + batch_id * batch_size as u64 + i as u64,
+                1000000 + batch_id * batch_size as u64 + i as u64,
                 100.0,
                 1.0,
                 true,
-                (batch_id * batch_size as u64) as u64 + i as u64,
+                batch_id * batch_size as u64 + i as u64,
             );
             ticks.push(tick);
         }
@@ -261,7 +261,7 @@ fn bench_full_pipeline() -> Result<(), Box<dyn std::error::Error>> {
             1000000 + (i * 100),
             1000000 + (i * 100),
             100.0 + (i as f64 % 10.0 - 5.0) * 0.1,
-            (1.0 + (i as f64 % 9.0) * 0.01),
+            1.0 + (i as f64 % 9.0) * 0.01,
             i % 2 == 0,
             i,
         );
@@ -274,7 +274,7 @@ fn bench_full_pipeline() -> Result<(), Box<dyn std::error::Error>> {
 
         // 偶尔从 buffer 中弹出
         if i % 100 == 0 {
-            while let Some(_) = buffer.pop() {}
+            while buffer.pop().is_some() {}
         }
     }
 
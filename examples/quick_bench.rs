@@ -1,5 +1,5 @@
-/// 简洁性能测试 - 无需等待编译
-/// cargo run --example quick_bench --release
+//! 简洁性能测试 - 无需等待编译
+//! cargo run --example quick_bench --release
 
 use mdi::{Tick, KLineBuilder, RingBuffer};
 use std::time::Instant;
@@ -47,7 +47,7 @@ fn bench_push() {
             100.0 + (i as f64 % 10.0 - 5.0) * 0.01,
             1.0,
             true,
-            i as u64,
+            i,
         );
         let _ = buffer.push(tick);
     }
@@ -70,19 +70,19 @@ fn bench_pop() {
     for i in 0..num_items {
         let tick = Tick::new(
             "BTCUSDT".to_string(),
-            1000000 + i as u64,
-            1000000 + i as u64,
+            1000000 + i,
+            1000000 + i,
             100.0,
             1.0,
             true,
-            i as u64,
+            i,
         );
         let _ = buffer.push(tick);
     }
 
     let start = Instant::now();
     let mut count = 0;
-    while let Some(_) = buffer.pop() {
+    while buffer.pop().is_some() {
         count += 1;
     }
     let elapsed = start.elapsed();
@@ -105,12 +105,12 @@ fn bench_pop_batch() {
     for i in 0..num_items {
         let tick = Tick::new(
             "BTCUSDT".to_string(),
-            1000000 + i as u64,
-            1000000 + i as u64,
+            1000000 + i,
+            1000000 + i,
             100.0,
             1.0,
             true,
-            i as u64,
+            i,
         );
         let _ = buffer.push(tick);
     }
@@ -192,14 +192,14 @@ fn bench_pipeline() {
 
         // 每 1000 个 tick 弹出一次
         if i % 1000 == 0 {
-            while let Some(_) = buffer.pop() {
+            while buffer.pop().is_some() {
                 pop_count += 1;
             }
         }
     }
 
     // 清空缓冲区
-    while let Some(_) = buffer.pop() {
+    while buffer.pop().is_some() {
         pop_count += 1;
     }
 
@@ -75,6 +75,7 @@ impl CpuAffinity {
 }
 
 /// Thread Builder with CPU Affinity
+#[derive(Default)]
 pub struct ThreadBuilder {
     cpu_id: Option<usize>,
     name: Option<String>,
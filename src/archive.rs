@@ -0,0 +1,816 @@
+use crate::retention::{PurgeReport, RetentionPolicy, DAY_MS};
+use crate::storage::{BackupInfo, Page, RangeOptions, ScanDirection};
+use crate::store::{scan_map, TickStore};
+use crate::{Tick, KLine, VolumeProfile, TickStorage, MdiError, Result};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// 归档文件头
+const MAGIC: &[u8; 4] = b"MDIT";
+const VERSION: u8 = 1;
+const MANIFEST: &str = "manifest.json";
+/// 备份目录下存放归档快照的子目录，每份备份一个 `<backup_id>` 目录
+const BACKUP_ARCHIVE_DIR: &str = "archive";
+/// 默认缓存的解码天数
+const DEFAULT_CACHE_DAYS: usize = 2;
+/// 归档时每页从 RocksDB 读取的 Tick 数
+const COMPACT_PAGE: usize = 100_000;
+/// 每行至少占用的字节数：时间戳、event_time、trade_id 各 1 字节 varint，价格和数量各 1 字节头
+const MIN_ROW_BYTES: usize = 5;
+
+/// 归档清单中的一个文件（一个品种一天）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    pub symbol: String,
+    /// 自 1970-01-01 起的天数（UTC）
+    pub day: u64,
+    /// 相对归档目录的路径
+    pub file: String,
+    pub rows: u64,
+    /// 首条和末条 Tick 的时间戳（毫秒）
+    pub from_ms: u64,
+    pub to_ms: u64,
+    pub min_trade_id: u64,
+    pub max_trade_id: u64,
+    /// 文件大小
+    pub bytes: u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    files: Vec<ArchiveEntry>,
+}
+
+/// 解码后的一天数据
+struct DayTicks {
+    /// (timestamp, trade_id) -> Tick
+    ticks: BTreeMap<(u64, u64), Tick>,
+    /// trade_id -> timestamp
+    trade_index: HashMap<u64, u64>,
+}
+
+/// 最近读取的若干天，按访问顺序淘汰
+struct DayCache {
+    days: HashMap<(String, u64), Arc<DayTicks>>,
+    /// 最久未访问的在前
+    order: VecDeque<(String, u64)>,
+    capacity: usize,
+}
+
+impl DayCache {
+    fn get(&mut self, key: &(String, u64)) -> Option<Arc<DayTicks>> {
+        let day = self.days.get(key)?.clone();
+        self.touch(key);
+        Some(day)
+    }
+
+    fn insert(&mut self, key: (String, u64), day: Arc<DayTicks>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.days.insert(key.clone(), day).is_some() {
+            self.touch(&key);
+            return;
+        }
+        self.order.push_back(key);
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.days.remove(&oldest);
+            }
+        }
+    }
+
+    fn remove(&mut self, key: &(String, u64)) {
+        if self.days.remove(key).is_some() {
+            self.order.retain(|k| k != key);
+        }
+    }
+
+    fn touch(&mut self, key: &(String, u64)) {
+        if let Some(pos) = self.order.iter().position(|k| k == key) {
+            let key = self.order.remove(pos).expect("position in range");
+            self.order.push_back(key);
+        }
+    }
+}
+
+/// 单次归档的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompactionReport {
+    /// 写入的文件数
+    pub days: u64,
+    /// 从 RocksDB 移出的 Tick 数
+    pub ticks: u64,
+    /// 写入的文件字节数
+    pub bytes: u64,
+    /// symbol -> 移出的 Tick 数
+    pub symbols: HashMap<String, u64>,
+}
+
+impl CompactionReport {
+    pub fn is_empty(&self) -> bool {
+        self.days == 0
+    }
+}
+
+/// Tick 冷数据归档 - 每个品种每天一个不可变的列式文件，由 `manifest.json` 索引
+///
+/// 文件格式: `MDIT | version | snappy(列数据)`，列依次为时间戳增量、event_time 偏移、
+/// trade_id 增量（均为 varint）、主动方向位图、价格和数量（与前值异或后去掉前导零字节）
+///
+/// 最近读取的几天保留解码结果，分页读取和按 trade_id 查找不必每次解码整个文件
+pub struct TickArchive {
+    dir: PathBuf,
+    /// (symbol, day) -> 文件
+    entries: Arc<RwLock<BTreeMap<(String, u64), ArchiveEntry>>>,
+    cache: Arc<Mutex<DayCache>>,
+}
+
+impl Clone for TickArchive {
+    fn clone(&self) -> Self {
+        TickArchive {
+            dir: self.dir.clone(),
+            entries: Arc::clone(&self.entries),
+            cache: Arc::clone(&self.cache),
+        }
+    }
+}
+
+impl TickArchive {
+    /// 打开或创建归档目录
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir).map_err(|e| io_error(&dir, e))?;
+
+        let path = dir.join(MANIFEST);
+        let manifest: Manifest = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Manifest::default(),
+            Err(e) => return Err(io_error(&path, e)),
+        };
+        let entries = manifest
+            .files
+            .into_iter()
+            .map(|entry| ((entry.symbol.clone(), entry.day), entry))
+            .collect();
+
+        let cache = DayCache { days: HashMap::new(), order: VecDeque::new(), capacity: DEFAULT_CACHE_DAYS };
+        Ok(TickArchive { dir, entries: Arc::new(RwLock::new(entries)), cache: Arc::new(Mutex::new(cache)) })
+    }
+
+    /// 缓存解码结果的天数，0 表示不缓存
+    pub fn cache_days(self, days: usize) -> Self {
+        {
+            let mut cache = self.cache.lock();
+            cache.capacity = days;
+            while cache.order.len() > days {
+                if let Some(oldest) = cache.order.pop_front() {
+                    cache.days.remove(&oldest);
+                }
+            }
+        }
+        self
+    }
+
+    /// 指定品种的归档文件，按日期排序
+    pub fn entries(&self, symbol: &str) -> Vec<ArchiveEntry> {
+        self.entries
+            .read()
+            .range((symbol.to_string(), 0)..=(symbol.to_string(), u64::MAX))
+            .map(|(_, entry)| entry.clone())
+            .collect()
+    }
+
+    /// 有归档数据的品种，按名称排序
+    pub fn symbols(&self) -> Vec<String> {
+        let entries = self.entries.read();
+        entries.keys().map(|(symbol, _)| symbol.clone()).collect::<BTreeSet<_>>().into_iter().collect()
+    }
+
+    /// 读取一天的 Tick，按 (timestamp, trade_id) 排序
+    pub fn read_day(&self, symbol: &str, day: u64) -> Result<Vec<Tick>> {
+        Ok(self
+            .load_day(symbol, day)?
+            .map(|day| day.ticks.values().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    /// 读取并缓存一天的解码结果，没有该天的文件时返回 None
+    fn load_day(&self, symbol: &str, day: u64) -> Result<Option<Arc<DayTicks>>> {
+        let key = (symbol.to_string(), day);
+        if let Some(cached) = self.cache.lock().get(&key) {
+            return Ok(Some(cached));
+        }
+        let entry = match self.entries.read().get(&key) {
+            Some(entry) => entry.clone(),
+            None => return Ok(None),
+        };
+        let path = self.dir.join(&entry.file);
+        let bytes = std::fs::read(&path).map_err(|e| io_error(&path, e))?;
+        let ticks: BTreeMap<(u64, u64), Tick> = decode_day(&bytes)?
+            .into_iter()
+            .map(|tick| ((tick.timestamp, tick.trade_id), tick))
+            .collect();
+        let trade_index = ticks.keys().map(|&(timestamp, trade_id)| (trade_id, timestamp)).collect();
+        let decoded = Arc::new(DayTicks { ticks, trade_index });
+
+        // 解码期间文件可能已被 write_day 替换，只缓存仍在清单中的版本
+        let entries = self.entries.read();
+        if entries.get(&key) == Some(&entry) {
+            self.cache.lock().insert(key, Arc::clone(&decoded));
+        }
+        Ok(Some(decoded))
+    }
+
+    /// 写入一天的 Tick，与已有文件按 trade_id 合并后整体替换
+    pub fn write_day(&self, symbol: &str, day: u64, ticks: &[Tick]) -> Result<ArchiveEntry> {
+        let mut merged: BTreeMap<u64, Tick> = self
+            .read_day(symbol, day)?
+            .into_iter()
+            .map(|tick| (tick.trade_id, tick))
+            .collect();
+        for tick in ticks {
+            merged.insert(tick.trade_id, tick.clone());
+        }
+        let mut ticks: Vec<Tick> = merged.into_values().collect();
+        ticks.sort_by_key(|tick| (tick.timestamp, tick.trade_id));
+
+        let bytes = encode_day(symbol, &ticks);
+        let file = format!("{}/{}.ticks", symbol, day_name(day));
+        let path = self.dir.join(&file);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+        }
+        // 替换文件和更新清单在同一把锁内，`snapshot` 看到的文件与清单一致
+        let mut entries = self.entries.write();
+        write_atomic(&path, &bytes)?;
+
+        let entry = ArchiveEntry {
+            symbol: symbol.to_string(),
+            day,
+            file,
+            rows: ticks.len() as u64,
+            from_ms: ticks.first().map_or(0, |tick| tick.timestamp),
+            to_ms: ticks.last().map_or(0, |tick| tick.timestamp),
+            min_trade_id: ticks.iter().map(|tick| tick.trade_id).min().unwrap_or(0),
+            max_trade_id: ticks.iter().map(|tick| tick.trade_id).max().unwrap_or(0),
+            bytes: bytes.len() as u64,
+        };
+        entries.insert((symbol.to_string(), day), entry.clone());
+        self.cache.lock().remove(&(symbol.to_string(), day));
+        self.save_manifest(&entries)?;
+        Ok(entry)
+    }
+
+    /// 按 trade_id 查找
+    pub fn read_tick(&self, symbol: &str, trade_id: u64) -> Result<Option<Tick>> {
+        for entry in self.entries(symbol) {
+            if !(entry.min_trade_id..=entry.max_trade_id).contains(&trade_id) {
+                continue;
+            }
+            if let Some(day) = self.load_day(symbol, entry.day)? {
+                if let Some(&timestamp) = day.trade_index.get(&trade_id) {
+                    return Ok(day.ticks.get(&(timestamp, trade_id)).cloned());
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// 读取 `[from_ms, to_ms)` 内的归档 Tick，分页语义与 `TickStorage::read_ticks_range` 相同
+    pub fn read_ticks_range(&self, symbol: &str, from_ms: u64, to_ms: u64, options: &RangeOptions) -> Result<Page<Tick>> {
+        let mut entries: Vec<_> = self
+            .entries(symbol)
+            .into_iter()
+            .filter(|entry| entry.from_ms < to_ms && entry.to_ms >= from_ms)
+            .collect();
+        if options.direction == ScanDirection::Reverse {
+            entries.reverse();
+        }
+
+        let mut page = Page::default();
+        for entry in entries {
+            let day = match self.load_day(symbol, entry.day)? {
+                Some(day) => day,
+                None => continue,
+            };
+            let limit = options.limit.map(|limit| limit - page.items.len());
+            let day_page = scan_map(&day.ticks, from_ms, to_ms, &RangeOptions { limit, ..options.clone() });
+            page.items.extend(day_page.items);
+            if day_page.next_cursor.is_some() {
+                page.next_cursor = day_page.next_cursor;
+                break;
+            }
+        }
+        Ok(page)
+    }
+
+    /// 把当前清单及其引用的文件硬链接到 `target`（跨文件系统时复制），可直接用 `TickArchive::open` 打开
+    ///
+    /// 归档文件只会被整体替换或删除，不会原地修改，硬链接得到的快照不受之后的写入影响
+    pub fn snapshot<P: AsRef<Path>>(&self, target: P) -> Result<()> {
+        let target = target.as_ref();
+        let entries = self.entries.read();
+        for entry in entries.values() {
+            let from = self.dir.join(&entry.file);
+            let to = target.join(&entry.file);
+            if let Some(parent) = to.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+            }
+            if std::fs::hard_link(&from, &to).is_err() {
+                std::fs::copy(&from, &to).map_err(|e| io_error(&to, e))?;
+            }
+        }
+        write_manifest(target, &entries)
+    }
+
+    /// 删除整天都早于 `cutoff_ms` 的文件，返回 symbol -> 删除的 Tick 数
+    pub fn purge_before(&self, cutoff_ms: u64) -> Result<HashMap<String, u64>> {
+        let mut removed = HashMap::new();
+        let mut entries = self.entries.write();
+        let expired: Vec<_> = entries
+            .iter()
+            .filter(|(_, entry)| (entry.day + 1) * DAY_MS <= cutoff_ms)
+            .map(|(key, entry)| (key.clone(), entry.clone()))
+            .collect();
+        if expired.is_empty() {
+            return Ok(removed);
+        }
+
+        let mut cache = self.cache.lock();
+        for (key, entry) in expired {
+            cache.remove(&key);
+            entries.remove(&key);
+            *removed.entry(entry.symbol).or_default() += entry.rows;
+        }
+        drop(cache);
+        // 先更新清单再删文件，中途失败只会留下无人引用的文件
+        self.save_manifest(&entries)?;
+        for file in self.unreferenced(&entries)? {
+            let path = self.dir.join(&file);
+            std::fs::remove_file(&path).map_err(|e| io_error(&path, e))?;
+        }
+        Ok(removed)
+    }
+
+    /// 删除所有归档
+    pub fn clear(&self) -> Result<()> {
+        let mut entries = self.entries.write();
+        entries.clear();
+        let mut cache = self.cache.lock();
+        cache.days.clear();
+        cache.order.clear();
+        drop(cache);
+        self.save_manifest(&entries)?;
+        for file in self.unreferenced(&entries)? {
+            let path = self.dir.join(&file);
+            std::fs::remove_file(&path).map_err(|e| io_error(&path, e))?;
+        }
+        Ok(())
+    }
+
+    /// 目录中不在清单内的归档文件（相对路径）
+    fn unreferenced(&self, entries: &BTreeMap<(String, u64), ArchiveEntry>) -> Result<Vec<String>> {
+        let referenced: BTreeSet<&str> = entries.values().map(|entry| entry.file.as_str()).collect();
+        let mut files = Vec::new();
+        for symbol_dir in std::fs::read_dir(&self.dir).map_err(|e| io_error(&self.dir, e))? {
+            let symbol_dir = symbol_dir.map_err(|e| io_error(&self.dir, e))?.path();
+            if !symbol_dir.is_dir() {
+                continue;
+            }
+            let symbol = symbol_dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+            for file in std::fs::read_dir(&symbol_dir).map_err(|e| io_error(&symbol_dir, e))? {
+                let name = file.map_err(|e| io_error(&symbol_dir, e))?.file_name().to_string_lossy().to_string();
+                let file = format!("{}/{}", symbol, name);
+                if name.ends_with(".ticks") && !referenced.contains(file.as_str()) {
+                    files.push(file);
+                }
+            }
+        }
+        Ok(files)
+    }
+
+    fn save_manifest(&self, entries: &BTreeMap<(String, u64), ArchiveEntry>) -> Result<()> {
+        write_manifest(&self.dir, entries)
+    }
+}
+
+/// 冷热分层存储 - 新数据写入 RocksDB，`compact` 把已结束的自然日（UTC）移入 `TickArchive`，
+/// Tick 查询同时读取两层并按 (timestamp, trade_id) 合并
+pub struct TieredStorage {
+    hot: TickStorage,
+    archive: TickArchive,
+}
+
+impl Clone for TieredStorage {
+    fn clone(&self) -> Self {
+        TieredStorage {
+            hot: self.hot.clone(),
+            archive: self.archive.clone(),
+        }
+    }
+}
+
+impl TieredStorage {
+    pub fn new(hot: TickStorage, archive: TickArchive) -> Self {
+        TieredStorage { hot, archive }
+    }
+
+    pub fn hot(&self) -> &TickStorage {
+        &self.hot
+    }
+
+    pub fn archive(&self) -> &TickArchive {
+        &self.archive
+    }
+
+    /// 把 `now_ms` 所在日之前的 Tick 按品种、按天写入归档并从 RocksDB 删除
+    ///
+    /// 每天按 `COMPACT_PAGE` 笔分页读取，每页先写文件和清单，再只删除这一页读到的 Tick，
+    /// 期间写入的迟到 Tick 留到下次归档。中断后重跑会与已有文件合并，不丢不重
+    pub fn compact(&self, now_ms: u64) -> Result<CompactionReport> {
+        let today = now_ms / DAY_MS * DAY_MS;
+        let mut report = CompactionReport::default();
+
+        for symbol in self.hot.symbols() {
+            let mut from = 0;
+            loop {
+                // 跳过没有数据的日期
+                let first = self.hot.read_ticks_range(&symbol, from, today, &RangeOptions::new().limit(1))?;
+                let day = match first.items.first() {
+                    Some(tick) => tick.timestamp / DAY_MS,
+                    None => break,
+                };
+                let (day_start, day_end) = (day * DAY_MS, (day + 1) * DAY_MS);
+
+                let mut options = RangeOptions::new().limit(COMPACT_PAGE);
+                loop {
+                    let page = self.hot.read_ticks_range(&symbol, day_start, day_end, &options)?;
+                    if page.items.is_empty() {
+                        break;
+                    }
+                    let entry = self.archive.write_day(&symbol, day, &page.items)?;
+                    self.hot.remove_ticks(&page.items)?;
+
+                    let moved = page.items.len() as u64;
+                    report.ticks += moved;
+                    *report.symbols.entry(symbol.clone()).or_default() += moved;
+                    report.bytes += entry.bytes;
+                    match page.next_cursor {
+                        Some(cursor) => options = options.after(cursor),
+                        None => break,
+                    }
+                }
+
+                report.days += 1;
+                from = day_end;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// 恢复 `backup` 创建的备份：RocksDB 恢复到 `target`，归档快照恢复到 `archive_target`
+    ///
+    /// `backup_id` 为 None 时恢复最新备份。两个目标都必须不存在或为空；
+    /// 没有归档快照的备份只恢复 RocksDB
+    pub fn restore<P: AsRef<Path>, Q: AsRef<Path>, R: AsRef<Path>>(
+        backup_dir: P,
+        target: Q,
+        archive_target: R,
+        backup_id: Option<u32>,
+    ) -> Result<()> {
+        let (backup_dir, archive_target) = (backup_dir.as_ref(), archive_target.as_ref());
+        if archive_target.read_dir().is_ok_and(|mut entries| entries.next().is_some()) {
+            return Err(MdiError::StorageError(format!(
+                "Restore target {} is not empty",
+                archive_target.display()
+            )));
+        }
+        let backup_id = match backup_id {
+            Some(backup_id) => backup_id,
+            None => TickStorage::list_backups(backup_dir)?
+                .last()
+                .map(|info| info.backup_id)
+                .ok_or_else(|| MdiError::StorageError(format!("No backups in {}", backup_dir.display())))?,
+        };
+
+        TickStorage::restore(backup_dir, target, Some(backup_id))?;
+        let snapshot = backup_dir.join(BACKUP_ARCHIVE_DIR).join(backup_id.to_string());
+        if snapshot.is_dir() {
+            TickArchive::open(&snapshot)?.snapshot(archive_target)?;
+        }
+        Ok(())
+    }
+}
+
+impl TickStore for TieredStorage {
+    fn write_ticks(&self, ticks: &[Tick]) -> Result<()> {
+        self.hot.write_ticks(ticks)
+    }
+
+    fn read_tick(&self, symbol: &str, trade_id: u64) -> Result<Option<Tick>> {
+        match self.hot.read_tick(symbol, trade_id)? {
+            Some(tick) => Ok(Some(tick)),
+            None => self.archive.read_tick(symbol, trade_id),
+        }
+    }
+
+    fn read_ticks_range(&self, symbol: &str, from_ms: u64, to_ms: u64, options: &RangeOptions) -> Result<Page<Tick>> {
+        let archived = self.archive.read_ticks_range(symbol, from_ms, to_ms, options)?;
+        let hot = self.hot.read_ticks_range(symbol, from_ms, to_ms, options)?;
+
+        // 两层各自有序，合并后重新截断；同一笔成交以热数据为准
+        let merged: BTreeMap<(u64, u64), Tick> = archived
+            .items
+            .into_iter()
+            .chain(hot.items)
+            .map(|tick| ((tick.timestamp, tick.trade_id), tick))
+            .collect();
+        Ok(scan_map(&merged, from_ms, to_ms, options))
+    }
+
+    fn write_klines(&self, klines: &[KLine]) -> Result<()> {
+        self.hot.write_klines(klines)
+    }
+
+    fn read_kline(&self, symbol: &str, interval: u64, timestamp: u64) -> Result<Option<KLine>> {
+        self.hot.read_kline(symbol, interval, timestamp)
+    }
+
+    fn read_klines_range(
+        &self,
+        symbol: &str,
+        interval: u64,
+        from: u64,
+        to: u64,
+        options: &RangeOptions,
+    ) -> Result<Page<KLine>> {
+        self.hot.read_klines_range(symbol, interval, from, to, options)
+    }
+
+    fn write_profiles(&self, profiles: &[VolumeProfile]) -> Result<()> {
+        self.hot.write_profiles(profiles)
+    }
+
+    fn read_profile(&self, symbol: &str, interval: u64, timestamp: u64) -> Result<Option<VolumeProfile>> {
+        self.hot.read_profile(symbol, interval, timestamp)
+    }
+
+    fn read_meta(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        self.hot.read_meta(name)
+    }
+
+    fn write_meta(&self, name: &str, value: &[u8]) -> Result<()> {
+        self.hot.write_meta(name, value)
+    }
+
+    fn symbols(&self) -> Vec<String> {
+        let mut symbols: BTreeSet<String> = self.hot.symbols().into_iter().collect();
+        symbols.extend(self.archive.symbols());
+        symbols.into_iter().collect()
+    }
+
+    /// 归档文件整天删除，只删除全天都已过期的文件
+    fn purge(&self, policy: &RetentionPolicy, now_ms: u64) -> Result<PurgeReport> {
+        let mut report = self.hot.purge(policy, now_ms)?;
+        if let Some(keep_ms) = policy.ticks {
+            for (symbol, ticks) in self.archive.purge_before(RetentionPolicy::cutoff(keep_ms, now_ms))? {
+                report.ticks += ticks;
+                *report.symbols.entry(symbol).or_default() += ticks;
+            }
+        }
+        Ok(report)
+    }
+
+    /// 增量备份 RocksDB，并把归档快照硬链接到 `backup_dir/archive/<backup_id>`，用 `TieredStorage::restore` 恢复
+    ///
+    /// 先备份 RocksDB 再做归档快照：期间被 `compact` 移出的 Tick 在删除前已写入归档，不会两边都缺
+    fn backup(&self, backup_dir: &Path, keep: usize) -> Result<BackupInfo> {
+        let info = self.hot.backup(backup_dir, keep)?;
+
+        let snapshots = backup_dir.join(BACKUP_ARCHIVE_DIR);
+        let snapshot = snapshots.join(info.backup_id.to_string());
+        if snapshot.exists() {
+            std::fs::remove_dir_all(&snapshot).map_err(|e| io_error(&snapshot, e))?;
+        }
+        self.archive.snapshot(&snapshot)?;
+
+        // 删除已清理的备份对应的快照
+        let kept: BTreeSet<String> = TickStorage::list_backups(backup_dir)?
+            .iter()
+            .map(|info| info.backup_id.to_string())
+            .collect();
+        for dir in std::fs::read_dir(&snapshots).map_err(|e| io_error(&snapshots, e))? {
+            let dir = dir.map_err(|e| io_error(&snapshots, e))?;
+            if !kept.contains(dir.file_name().to_string_lossy().as_ref()) {
+                std::fs::remove_dir_all(dir.path()).map_err(|e| io_error(&dir.path(), e))?;
+            }
+        }
+        Ok(info)
+    }
+
+    fn clear(&self) -> Result<()> {
+        self.hot.clear()?;
+        self.archive.clear()
+    }
+}
+
+/// 编码一天的 Tick，`ticks` 需按 (timestamp, trade_id) 排序
+pub fn encode_day(symbol: &str, ticks: &[Tick]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(ticks.len() * 16);
+    put_varint(&mut body, ticks.len() as u64);
+    put_varint(&mut body, symbol.len() as u64);
+    body.extend_from_slice(symbol.as_bytes());
+
+    let mut prev = 0;
+    for tick in ticks {
+        put_varint(&mut body, tick.timestamp - prev);
+        prev = tick.timestamp;
+    }
+    for tick in ticks {
+        put_varint(&mut body, zigzag(tick.event_time.wrapping_sub(tick.timestamp) as i64));
+    }
+    let mut prev = 0u64;
+    for tick in ticks {
+        put_varint(&mut body, zigzag(tick.trade_id.wrapping_sub(prev) as i64));
+        prev = tick.trade_id;
+    }
+    let mut bits = vec![0u8; ticks.len().div_ceil(8)];
+    for (i, tick) in ticks.iter().enumerate() {
+        if tick.is_buyer_maker {
+            bits[i / 8] |= 1 << (i % 8);
+        }
+    }
+    body.extend_from_slice(&bits);
+    put_xor_column(&mut body, ticks.iter().map(|tick| tick.price));
+    put_xor_column(&mut body, ticks.iter().map(|tick| tick.quantity));
+
+    let mut out = Vec::with_capacity(body.len() / 2);
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    out.extend_from_slice(&snap::raw::Encoder::new().compress_vec(&body).expect("snappy compress"));
+    out
+}
+
+/// 解码 `encode_day` 的输出
+pub fn decode_day(bytes: &[u8]) -> Result<Vec<Tick>> {
+    if bytes.len() < 5 || &bytes[..4] != MAGIC {
+        return Err(corrupt("bad magic"));
+    }
+    if bytes[4] != VERSION {
+        return Err(corrupt(&format!("unsupported version {}", bytes[4])));
+    }
+    // snappy 每 3 字节最多展开为 64 字节，超出说明长度头已损坏
+    let body_len = snap::raw::decompress_len(&bytes[5..]).map_err(|e| corrupt(&e.to_string()))?;
+    if body_len / 22 > bytes.len() {
+        return Err(corrupt(&format!("uncompressed length {} too large", body_len)));
+    }
+    let body = snap::raw::Decoder::new()
+        .decompress_vec(&bytes[5..])
+        .map_err(|e| corrupt(&e.to_string()))?;
+
+    let mut reader = Reader { bytes: &body, pos: 0 };
+    let rows = reader.varint()?;
+    let symbol_len = reader.varint()? as usize;
+    let symbol = String::from_utf8(reader.take(symbol_len)?.to_vec()).map_err(|_| corrupt("bad symbol"))?;
+    // 行数来自文件内容，按剩余字节数检查后再分配
+    let remaining = (body.len() - reader.pos) as u64;
+    if rows > remaining / MIN_ROW_BYTES as u64 {
+        return Err(corrupt(&format!("{} rows in {} bytes", rows, remaining)));
+    }
+    let rows = rows as usize;
+
+    let mut timestamps = Vec::with_capacity(rows);
+    let mut prev = 0u64;
+    for _ in 0..rows {
+        prev = prev.checked_add(reader.varint()?).ok_or_else(|| corrupt("timestamp overflow"))?;
+        timestamps.push(prev);
+    }
+    let mut event_times = Vec::with_capacity(rows);
+    for timestamp in &timestamps {
+        event_times.push(timestamp.wrapping_add(unzigzag(reader.varint()?) as u64));
+    }
+    let mut trade_ids = Vec::with_capacity(rows);
+    let mut prev = 0u64;
+    for _ in 0..rows {
+        prev = prev.wrapping_add(unzigzag(reader.varint()?) as u64);
+        trade_ids.push(prev);
+    }
+    let bits = reader.take(rows.div_ceil(8))?.to_vec();
+    let prices = reader.xor_column(rows)?;
+    let quantities = reader.xor_column(rows)?;
+
+    Ok((0..rows)
+        .map(|i| Tick::new(
+            symbol.clone(),
+            timestamps[i],
+            event_times[i],
+            prices[i],
+            quantities[i],
+            bits[i / 8] & (1 << (i % 8)) != 0,
+            trade_ids[i],
+        ))
+        .collect())
+}
+
+fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+/// 与前值异或，写入前导零字节数和剩余字节；价格不变时只占 1 字节
+fn put_xor_column(buf: &mut Vec<u8>, values: impl Iterator<Item = f64>) {
+    let mut prev = 0u64;
+    for value in values {
+        let xor = value.to_bits() ^ prev;
+        let zeros = (xor.leading_zeros() / 8) as usize;
+        buf.push(zeros as u8);
+        buf.extend_from_slice(&xor.to_be_bytes()[zeros..]);
+        prev = value.to_bits();
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8]> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.bytes.len()).ok_or_else(|| corrupt("truncated"))?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(corrupt("varint too long"))
+    }
+
+    fn xor_column(&mut self, rows: usize) -> Result<Vec<f64>> {
+        let mut values = Vec::with_capacity(rows);
+        let mut prev = 0u64;
+        for _ in 0..rows {
+            let zeros = self.take(1)?[0] as usize;
+            if zeros > 8 {
+                return Err(corrupt("bad xor header"));
+            }
+            let mut bytes = [0u8; 8];
+            bytes[zeros..].copy_from_slice(self.take(8 - zeros)?);
+            prev ^= u64::from_be_bytes(bytes);
+            values.push(f64::from_bits(prev));
+        }
+        Ok(values)
+    }
+}
+
+/// `YYYY-MM-DD`
+fn day_name(day: u64) -> String {
+    chrono::DateTime::from_timestamp((day * DAY_MS / 1000) as i64, 0)
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| day.to_string())
+}
+
+/// 先写临时文件再重命名，读者不会看到写了一半的文件
+fn write_manifest(dir: &Path, entries: &BTreeMap<(String, u64), ArchiveEntry>) -> Result<()> {
+    std::fs::create_dir_all(dir).map_err(|e| io_error(dir, e))?;
+    let manifest = Manifest { files: entries.values().cloned().collect() };
+    write_atomic(&dir.join(MANIFEST), &serde_json::to_vec_pretty(&manifest)?)
+}
+
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, bytes).map_err(|e| io_error(&tmp, e))?;
+    std::fs::rename(&tmp, path).map_err(|e| io_error(path, e))
+}
+
+fn corrupt(reason: &str) -> MdiError {
+    MdiError::StorageError(format!("Corrupt archive file: {}", reason))
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> MdiError {
+    MdiError::StorageError(format!("Archive I/O error on {}: {}", path.display(), e))
+}
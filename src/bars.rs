@@ -0,0 +1,508 @@
+use crate::distributor::KLineEvent;
+use crate::{KLine, Tick, MdiError, Result};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 信息驱动 K 线的周期标识：高 8 位为类型标记，低 56 位为阈值
+///
+/// 与时间 K 线共用 `KLine::interval` / 存储键 / 订阅键，不会与秒数周期冲突；
+/// 标记 7 为日历周期，见 `interval::CALENDAR_TAG`
+pub const TICK_BAR_TAG: u64 = 1 << 56;
+pub const VOLUME_BAR_TAG: u64 = 2 << 56;
+pub const NOTIONAL_BAR_TAG: u64 = 3 << 56;
+pub const RANGE_BAR_TAG: u64 = 4 << 56;
+pub const RENKO_BAR_TAG: u64 = 5 << 56;
+/// Heikin-Ashi 的低 56 位为源 K 线周期（秒）
+pub const HEIKIN_ASHI_TAG: u64 = 6 << 56;
+const BAR_TAG_MASK: u64 = 0xff << 56;
+const BAR_VALUE_MASK: u64 = !BAR_TAG_MASK;
+/// Renko 标识中反转格数占 48..56 位，砖块大小占低 48 位
+const RENKO_REVERSAL_SHIFT: u32 = 48;
+const RENKO_BOX_MASK: u64 = (1 << RENKO_REVERSAL_SHIFT) - 1;
+const RENKO_REVERSAL_MAX: u32 = 0xff;
+/// 数量类阈值编码精度（1e-8）
+const THRESHOLD_SCALE: f64 = 1e8;
+/// 浮点累计误差容忍度（相对阈值）
+const EPSILON: f64 = 1e-9;
+/// 单笔成交最多收线的 K 线数，阈值相对成交过小时拒绝处理，避免一笔成交生成海量 K 线
+pub const MAX_BARS_PER_TICK: usize = 1000;
+
+/// 信息驱动 K 线类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BarSpec {
+    /// 每 N 笔成交收一根
+    Ticks(u64),
+    /// 每 N 个基础资产成交量收一根
+    Volume(f64),
+    /// 每 N 个计价资产成交额收一根
+    Notional(f64),
+    /// 最高价与最低价之差达到固定价差时收一根
+    Range(f64),
+    /// Renko 砖块：价格沿趋势每移动 `box_size` 生成一块，
+    /// 反向移动 `reversal` 格（至少 2 格，从最后一块的收盘价算起）才生成反向砖块
+    Renko { box_size: f64, reversal: u32 },
+}
+
+impl BarSpec {
+    /// 周期标识，用于 `KLine::interval`、存储和订阅；只有通过 `validate` 的类型能无损还原
+    pub fn id(&self) -> u64 {
+        match self {
+            BarSpec::Ticks(n) => TICK_BAR_TAG | (n & BAR_VALUE_MASK),
+            BarSpec::Volume(v) => VOLUME_BAR_TAG | encode_threshold(*v),
+            BarSpec::Notional(v) => NOTIONAL_BAR_TAG | encode_threshold(*v),
+            BarSpec::Range(v) => RANGE_BAR_TAG | encode_threshold(*v),
+            BarSpec::Renko { box_size, reversal } => {
+                RENKO_BAR_TAG
+                    | (((*reversal & RENKO_REVERSAL_MAX) as u64) << RENKO_REVERSAL_SHIFT)
+                    | (encode_threshold(*box_size) & RENKO_BOX_MASK)
+            }
+        }
+    }
+
+    /// 从周期标识还原，秒数周期返回 None
+    pub fn from_id(id: u64) -> Option<BarSpec> {
+        let value = id & BAR_VALUE_MASK;
+        match id & BAR_TAG_MASK {
+            TICK_BAR_TAG => Some(BarSpec::Ticks(value)),
+            VOLUME_BAR_TAG => Some(BarSpec::Volume(value as f64 / THRESHOLD_SCALE)),
+            NOTIONAL_BAR_TAG => Some(BarSpec::Notional(value as f64 / THRESHOLD_SCALE)),
+            RANGE_BAR_TAG => Some(BarSpec::Range(value as f64 / THRESHOLD_SCALE)),
+            RENKO_BAR_TAG => Some(BarSpec::Renko {
+                box_size: (value & RENKO_BOX_MASK) as f64 / THRESHOLD_SCALE,
+                reversal: (value >> RENKO_REVERSAL_SHIFT) as u32 & RENKO_REVERSAL_MAX,
+            }),
+            _ => None,
+        }
+    }
+
+    /// 检查阈值和标识范围：非正数或非有限值会让构建器无法推进，
+    /// 超出标识位宽的值会被截断成另一个类型
+    pub fn validate(&self) -> Result<()> {
+        let positive = |v: f64| v.is_finite() && v > 0.0;
+        // 按 1e-8 精度编码后不能为 0，也不能超出可用位宽
+        let encodable = |v: f64, mask: u64| positive(v) && (1.0..=mask as f64).contains(&(v * THRESHOLD_SCALE).round());
+        let valid = match *self {
+            BarSpec::Ticks(n) => n > 0 && n <= BAR_VALUE_MASK,
+            BarSpec::Volume(v) | BarSpec::Notional(v) | BarSpec::Range(v) => encodable(v, BAR_VALUE_MASK),
+            BarSpec::Renko { box_size, reversal } => {
+                encodable(box_size, RENKO_BOX_MASK) && (2..=RENKO_REVERSAL_MAX).contains(&reversal)
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(MdiError::Other(format!("Invalid bar spec: {:?}", self)))
+        }
+    }
+
+    fn threshold(&self) -> f64 {
+        match self {
+            BarSpec::Ticks(n) => *n as f64,
+            BarSpec::Volume(v) | BarSpec::Notional(v) | BarSpec::Range(v) => *v,
+            BarSpec::Renko { box_size, .. } => *box_size,
+        }
+    }
+
+    /// 当前 K 线已累计的进度
+    fn progress(&self, bar: &KLine) -> f64 {
+        match self {
+            BarSpec::Ticks(_) => bar.number_of_trades as f64,
+            BarSpec::Volume(_) => bar.volume,
+            BarSpec::Notional(_) => bar.quote_asset_volume,
+            BarSpec::Range(_) | BarSpec::Renko { .. } => bar.high - bar.low,
+        }
+    }
+
+    /// 剩余容量折算成的成交数量
+    fn remaining_quantity(&self, bar: &KLine, price: f64) -> f64 {
+        let remaining = self.threshold() - self.progress(bar);
+        match self {
+            BarSpec::Volume(_) => remaining,
+            BarSpec::Notional(_) => remaining / price,
+            _ => f64::INFINITY,
+        }
+    }
+
+    fn is_full(&self, bar: &KLine) -> bool {
+        self.progress(bar) >= self.threshold() * (1.0 - EPSILON)
+    }
+
+    /// 处理该成交最多会收线的 K 线数（估计值，偏大）
+    fn bars_needed(&self, state: Option<&BarState>, tick: &Tick) -> f64 {
+        match self {
+            BarSpec::Ticks(_) => 1.0,
+            BarSpec::Volume(v) => tick.quantity / v + 1.0,
+            BarSpec::Notional(v) => tick.quantity * tick.price / v + 1.0,
+            BarSpec::Range(_) => 2.0,
+            BarSpec::Renko { box_size, .. } => {
+                // 砖块数取决于价格离最后一块砖（或累计起点）的距离
+                let reference = state.and_then(|state| {
+                    state.last_brick.map(|(_, close)| close).or(state.current.as_ref().map(|bar| bar.open))
+                });
+                reference.map_or(0.0, |reference| (tick.price - reference).abs() / box_size) + 2.0
+            }
+        }
+    }
+}
+
+fn encode_threshold(value: f64) -> u64 {
+    ((value * THRESHOLD_SCALE).round() as u64) & BAR_VALUE_MASK
+}
+
+/// 单个品种、单个类型的构建状态
+#[derive(Debug, Default)]
+struct BarState {
+    /// 进行中的 K 线；Renko 下为自上一块砖以来累计的成交
+    current: Option<KLine>,
+    /// 上一根 K 线的时间键（毫秒），保证同一毫秒内的多根 K 线键唯一
+    last_key: u64,
+    /// Renko 最后一块砖 (open, close)
+    last_brick: Option<(f64, f64)>,
+}
+
+impl BarState {
+    fn next_key(&mut self, timestamp: u64) -> u64 {
+        let key = timestamp.max(self.last_key + 1);
+        self.last_key = key;
+        key
+    }
+
+    fn open_bar(&mut self, tick: &Tick, spec: &BarSpec) {
+        let key = self.next_key(tick.timestamp);
+        let mut bar = KLine::new(tick.symbol.clone(), key, spec.id(), tick.price);
+        bar.open_time = tick.timestamp;
+        bar.close_time = tick.timestamp;
+        self.current = Some(bar);
+    }
+
+    fn close_bar(&mut self, events: &mut Vec<KLineEvent>) {
+        if let Some(bar) = self.current.take() {
+            events.push(KLineEvent { kline: bar, is_closed: true });
+        }
+    }
+
+    /// 按剩余容量拆分成交（笔数、成交量、成交额）
+    fn fill_capacity(&mut self, spec: &BarSpec, tick: &Tick, events: &mut Vec<KLineEvent>) {
+        let mut remaining = tick.quantity;
+
+        loop {
+            if self.current.is_none() {
+                self.open_bar(tick, spec);
+            }
+            let bar = self.current.as_mut().expect("current bar exists");
+
+            let capacity = spec.remaining_quantity(bar, tick.price);
+            let split = remaining > capacity * (1.0 + EPSILON);
+            let fill = if split { capacity } else { remaining };
+
+            let mut part = tick.clone();
+            part.quantity = fill;
+            bar.update(&part);
+            bar.close_time = tick.timestamp;
+            remaining -= fill;
+
+            if spec.is_full(bar) {
+                self.close_bar(events);
+            }
+
+            if !split {
+                break;
+            }
+        }
+    }
+
+    /// Range：超出价差的成交开启新 K 线，价差恰好达到时收线
+    fn fill_range(&mut self, spec: &BarSpec, tick: &Tick, events: &mut Vec<KLineEvent>) {
+        let range = spec.threshold();
+        if let Some(bar) = &self.current {
+            let span = bar.high.max(tick.price) - bar.low.min(tick.price);
+            if span > range * (1.0 + EPSILON) {
+                self.close_bar(events);
+            }
+        }
+
+        if self.current.is_none() {
+            self.open_bar(tick, spec);
+        }
+        let bar = self.current.as_mut().expect("current bar exists");
+        bar.update(tick);
+        bar.close_time = tick.timestamp;
+
+        if spec.is_full(bar) {
+            self.close_bar(events);
+        }
+    }
+
+    /// Renko：成交先累计到 `current`，价格越过砖块边界时生成一块或多块砖，
+    /// 第一块砖承载累计的成交量和笔数
+    fn fill_renko(
+        &mut self,
+        spec: &BarSpec,
+        box_size: f64,
+        reversal: u32,
+        tick: &Tick,
+        events: &mut Vec<KLineEvent>,
+    ) {
+        if self.current.is_none() {
+            self.open_bar(tick, spec);
+            let reference = self.last_brick.map(|(_, close)| close);
+            let bar = self.current.as_mut().expect("current bar exists");
+            if let Some(close) = reference {
+                bar.open = close;
+                bar.high = close;
+                bar.low = close;
+                bar.close = close;
+            }
+        }
+        let bar = self.current.as_mut().expect("current bar exists");
+        bar.update(tick);
+        bar.close_time = tick.timestamp;
+
+        let anchor = bar.open;
+        let (mut last_open, mut last_close) = self.last_brick.unwrap_or((anchor, anchor));
+        let reversal = reversal as f64;
+        let tolerance = box_size * EPSILON;
+        let price = tick.price;
+        let mut bricks = Vec::new();
+
+        loop {
+            let up = last_close > last_open;
+            let down = last_close < last_open;
+            let brick = if !down && price >= last_close + box_size - tolerance {
+                (last_close, last_close + box_size)
+            } else if !up && price <= last_close - box_size + tolerance {
+                (last_close, last_close - box_size)
+            } else if up && price <= last_close - reversal * box_size + tolerance {
+                (last_open, last_open - box_size)
+            } else if down && price >= last_close + reversal * box_size - tolerance {
+                (last_open, last_open + box_size)
+            } else {
+                break;
+            };
+            bricks.push(brick);
+            (last_open, last_close) = brick;
+        }
+
+        if bricks.is_empty() {
+            return;
+        }
+
+        let pending = self.current.take().expect("current bar exists");
+        for (i, (open, close)) in bricks.into_iter().enumerate() {
+            let mut brick = pending.clone();
+            if i > 0 {
+                brick.timestamp = self.next_key(tick.timestamp);
+                brick.open_time = tick.timestamp;
+                brick.volume = 0.0;
+                brick.quote_asset_volume = 0.0;
+                brick.number_of_trades = 0;
+                brick.taker_buy_volume = 0.0;
+                brick.taker_buy_quote_volume = 0.0;
+                brick.taker_buy_trades = 0;
+                brick.taker_sell_volume = 0.0;
+                brick.taker_sell_quote_volume = 0.0;
+                brick.taker_sell_trades = 0;
+            }
+            brick.open = open;
+            brick.close = close;
+            brick.high = open.max(close);
+            brick.low = open.min(close);
+            events.push(KLineEvent { kline: brick, is_closed: true });
+            self.last_brick = Some((open, close));
+        }
+    }
+}
+
+/// 信息驱动 K 线构建器 - 按成交笔数、成交量、成交额、价差或 Renko 砖块收线
+///
+/// 与时间 K 线不同，这里的 `timestamp`/`open_time`/`close_time` 单位为毫秒：
+/// `open_time`/`close_time` 为首笔与末笔成交时间，`timestamp` 为严格递增的唯一键。
+/// 跨越边界的成交会按剩余容量精确拆分到相邻 K 线（拆分部分各计一笔）
+pub struct BarBuilder {
+    specs: Vec<BarSpec>,
+    /// symbol -> bar id -> 构建状态
+    bars: Arc<RwLock<HashMap<String, HashMap<u64, BarState>>>>,
+}
+
+impl BarBuilder {
+    /// 创建构建器，阈值无效时返回错误（见 `BarSpec::validate`）
+    pub fn new(specs: Vec<BarSpec>) -> Result<Self> {
+        for spec in &specs {
+            spec.validate()?;
+        }
+        Ok(BarBuilder {
+            specs,
+            bars: Arc::new(RwLock::new(HashMap::new())),
+        })
+    }
+
+    /// 处理 Tick，返回本次收线的 K 线（is_closed = true）和仍在进行中的 K 线
+    ///
+    /// 一笔成交会收线超过 `MAX_BARS_PER_TICK` 根时返回错误，构建状态保持不变
+    pub fn process_tick(&self, tick: &Tick) -> Result<Vec<KLineEvent>> {
+        let mut bars = self.bars.write();
+        for spec in &self.specs {
+            let state = bars.get(&tick.symbol).and_then(|symbol_bars| symbol_bars.get(&spec.id()));
+            let needed = spec.bars_needed(state, tick);
+            if !(0.0..=MAX_BARS_PER_TICK as f64).contains(&needed) {
+                return Err(MdiError::Other(format!(
+                    "Trade {} of {} would close {:.0} bars of {:?} (max {})",
+                    tick.trade_id, tick.symbol, needed, spec, MAX_BARS_PER_TICK
+                )));
+            }
+        }
+
+        let mut events = Vec::new();
+        let symbol_bars = bars.entry(tick.symbol.clone()).or_default();
+
+        for spec in &self.specs {
+            let state = symbol_bars.entry(spec.id()).or_default();
+
+            match *spec {
+                BarSpec::Ticks(_) | BarSpec::Volume(_) | BarSpec::Notional(_) => {
+                    state.fill_capacity(spec, tick, &mut events)
+                }
+                BarSpec::Range(_) => state.fill_range(spec, tick, &mut events),
+                BarSpec::Renko { box_size, reversal } => {
+                    state.fill_renko(spec, box_size, reversal, tick, &mut events)
+                }
+            }
+
+            if let Some(bar) = &state.current {
+                events.push(KLineEvent { kline: bar.clone(), is_closed: false });
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// 获取指定品种、类型正在构建中的 K 线
+    pub fn current_bar(&self, symbol: &str, spec: &BarSpec) -> Option<KLine> {
+        let bars = self.bars.read();
+        bars.get(symbol)
+            .and_then(|symbol_bars| symbol_bars.get(&spec.id()))
+            .and_then(|state| state.current.clone())
+    }
+
+    /// 获取配置的 K 线类型
+    pub fn specs(&self) -> &[BarSpec] {
+        &self.specs
+    }
+
+    /// 清空所有构建状态
+    pub fn clear(&self) {
+        self.bars.write().clear();
+    }
+}
+
+impl Clone for BarBuilder {
+    fn clone(&self) -> Self {
+        BarBuilder {
+            specs: self.specs.clone(),
+            bars: Arc::clone(&self.bars),
+        }
+    }
+}
+
+/// Heikin-Ashi 周期标识，`interval` 为源 K 线周期（秒）
+pub fn heikin_ashi_interval(interval: u64) -> u64 {
+    HEIKIN_ASHI_TAG | (interval & BAR_VALUE_MASK)
+}
+
+/// 单个品种、单个周期的 Heikin-Ashi 状态
+#[derive(Debug, Default)]
+struct HeikinAshiState {
+    /// 上一根已完成 HA K 线的 (open, close)
+    prev: Option<(f64, f64)>,
+    /// 当前源 K 线对应的 HA K 线（未完成）
+    current: Option<KLine>,
+}
+
+/// Heikin-Ashi 转换器 - 由时间 K 线（`KLineBuilder` 输出）派生
+///
+/// 源 K 线每次更新都会重新计算当前 HA K 线（未完成）；
+/// 出现更新的时间戳或显式标记完成时，上一根 HA K 线随之完成
+pub struct HeikinAshiBuilder {
+    /// (symbol, 源周期) -> 状态
+    states: Arc<RwLock<HashMap<(String, u64), HeikinAshiState>>>,
+}
+
+impl HeikinAshiBuilder {
+    pub fn new() -> Self {
+        HeikinAshiBuilder {
+            states: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// 处理源 K 线更新，返回已完成的 HA K 线和当前 HA K 线
+    pub fn process_kline(&self, kline: &KLine, is_closed: bool) -> Vec<KLineEvent> {
+        let mut events = Vec::new();
+        let mut states = self.states.write();
+        let state = states
+            .entry((kline.symbol.clone(), kline.interval))
+            .or_default();
+
+        if let Some(current) = &state.current {
+            if current.timestamp < kline.timestamp {
+                let finished = state.current.take().expect("current bar exists");
+                state.prev = Some((finished.open, finished.close));
+                events.push(KLineEvent { kline: finished, is_closed: true });
+            } else if current.timestamp > kline.timestamp {
+                // 过期的源 K 线更新，忽略
+                return events;
+            }
+        }
+
+        let ha_close = (kline.open + kline.high + kline.low + kline.close) / 4.0;
+        let ha_open = match state.prev {
+            Some((open, close)) => (open + close) / 2.0,
+            None => (kline.open + kline.close) / 2.0,
+        };
+
+        let mut ha = kline.clone();
+        ha.interval = heikin_ashi_interval(kline.interval);
+        ha.open = ha_open;
+        ha.close = ha_close;
+        ha.high = kline.high.max(ha_open).max(ha_close);
+        ha.low = kline.low.min(ha_open).min(ha_close);
+
+        if is_closed {
+            state.prev = Some((ha.open, ha.close));
+            state.current = None;
+            events.push(KLineEvent { kline: ha, is_closed: true });
+        } else {
+            state.current = Some(ha.clone());
+            events.push(KLineEvent { kline: ha, is_closed: false });
+        }
+
+        events
+    }
+
+    /// 获取指定品种、源周期当前的 HA K 线
+    pub fn current(&self, symbol: &str, interval: u64) -> Option<KLine> {
+        let states = self.states.read();
+        states
+            .get(&(symbol.to_string(), interval))
+            .and_then(|state| state.current.clone())
+    }
+
+    /// 清空所有状态
+    pub fn clear(&self) {
+        self.states.write().clear();
+    }
+}
+
+impl Default for HeikinAshiBuilder {
+    fn default() -> Self {
+        HeikinAshiBuilder::new()
+    }
+}
+
+impl Clone for HeikinAshiBuilder {
+    fn clone(&self) -> Self {
+        HeikinAshiBuilder {
+            states: Arc::clone(&self.states),
+        }
+    }
+}
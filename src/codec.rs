@@ -0,0 +1,209 @@
+use crate::{KLine, MdiError, Result, Tick};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Tick 二进制格式 v1 的标签
+pub const TAG_TICK_V1: u8 = 0x01;
+/// KLine 二进制格式 v1 的标签
+pub const TAG_KLINE_V1: u8 = 0x02;
+/// JSON 记录总是以 '{' 开头，可与二进制标签区分
+const JSON_START: u8 = b'{';
+
+/// 存储值的编码格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ValueFormat {
+    /// serde_json，早期版本写入的格式
+    Json,
+    /// 定长二进制：标签字节 + 小端数值字段 + 品种名
+    #[default]
+    Binary,
+}
+
+impl ValueFormat {
+    /// 识别已编码记录的格式
+    pub fn detect(bytes: &[u8]) -> Option<ValueFormat> {
+        match bytes.first()? {
+            &JSON_START => Some(ValueFormat::Json),
+            &TAG_TICK_V1 | &TAG_KLINE_V1 => Some(ValueFormat::Binary),
+            _ => None,
+        }
+    }
+}
+
+/// 支持二进制编码的存储值
+pub trait BinaryCodec: Sized + Serialize + DeserializeOwned {
+    fn encode_binary(&self) -> Result<Vec<u8>>;
+    fn decode_binary(bytes: &[u8]) -> Result<Self>;
+}
+
+/// 按指定格式编码
+pub fn encode<T: BinaryCodec>(value: &T, format: ValueFormat) -> Result<Vec<u8>> {
+    match format {
+        ValueFormat::Json => serde_json::to_vec(value).map_err(|e| {
+            MdiError::StorageError(format!("Serialization error: {}", e))
+        }),
+        ValueFormat::Binary => value.encode_binary(),
+    }
+}
+
+/// 按首字节识别格式并解码，兼容旧的 JSON 记录
+pub fn decode<T: BinaryCodec>(bytes: &[u8]) -> Result<T> {
+    match ValueFormat::detect(bytes) {
+        Some(ValueFormat::Json) => serde_json::from_slice(bytes).map_err(|e| {
+            MdiError::StorageError(format!("Deserialization error: {}", e))
+        }),
+        Some(ValueFormat::Binary) => T::decode_binary(bytes),
+        None => Err(MdiError::StorageError(format!(
+            "Unknown value format tag: {:?}",
+            bytes.first()
+        ))),
+    }
+}
+
+impl BinaryCodec for Tick {
+    /// 布局: tag | timestamp | event_time | price | quantity | trade_id | is_buyer_maker(u8) | symbol_len(u8) | symbol
+    fn encode_binary(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::with_capacity(43 + self.symbol.len());
+        buf.push(TAG_TICK_V1);
+        put_u64(&mut buf, self.timestamp);
+        put_u64(&mut buf, self.event_time);
+        put_f64(&mut buf, self.price);
+        put_f64(&mut buf, self.quantity);
+        put_u64(&mut buf, self.trade_id);
+        buf.push(self.is_buyer_maker as u8);
+        put_symbol(&mut buf, &self.symbol)?;
+        Ok(buf)
+    }
+
+    fn decode_binary(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(bytes, TAG_TICK_V1)?;
+        let timestamp = reader.u64()?;
+        let event_time = reader.u64()?;
+        let price = reader.f64()?;
+        let quantity = reader.f64()?;
+        let trade_id = reader.u64()?;
+        let is_buyer_maker = reader.u8()? != 0;
+        let symbol = reader.symbol()?;
+        Ok(Tick::new(symbol, timestamp, event_time, price, quantity, is_buyer_maker, trade_id))
+    }
+}
+
+impl BinaryCodec for KLine {
+    /// 布局: tag | 按结构体字段顺序的数值字段 | symbol_len(u8) | symbol
+    fn encode_binary(&self) -> Result<Vec<u8>> {
+        let mut buf = Vec::with_capacity(138 + self.symbol.len());
+        buf.push(TAG_KLINE_V1);
+        put_u64(&mut buf, self.timestamp);
+        put_u64(&mut buf, self.interval);
+        put_f64(&mut buf, self.open);
+        put_f64(&mut buf, self.high);
+        put_f64(&mut buf, self.low);
+        put_f64(&mut buf, self.close);
+        put_f64(&mut buf, self.volume);
+        put_f64(&mut buf, self.quote_asset_volume);
+        put_u64(&mut buf, self.number_of_trades);
+        put_f64(&mut buf, self.taker_buy_volume);
+        put_f64(&mut buf, self.taker_buy_quote_volume);
+        put_u64(&mut buf, self.taker_buy_trades);
+        put_f64(&mut buf, self.taker_sell_volume);
+        put_f64(&mut buf, self.taker_sell_quote_volume);
+        put_u64(&mut buf, self.taker_sell_trades);
+        put_u64(&mut buf, self.open_time);
+        put_u64(&mut buf, self.close_time);
+        put_symbol(&mut buf, &self.symbol)?;
+        Ok(buf)
+    }
+
+    fn decode_binary(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader::new(bytes, TAG_KLINE_V1)?;
+        let timestamp = reader.u64()?;
+        let interval = reader.u64()?;
+        let mut kline = KLine::new(String::new(), timestamp, interval, 0.0);
+        kline.open = reader.f64()?;
+        kline.high = reader.f64()?;
+        kline.low = reader.f64()?;
+        kline.close = reader.f64()?;
+        kline.volume = reader.f64()?;
+        kline.quote_asset_volume = reader.f64()?;
+        kline.number_of_trades = reader.u64()?;
+        kline.taker_buy_volume = reader.f64()?;
+        kline.taker_buy_quote_volume = reader.f64()?;
+        kline.taker_buy_trades = reader.u64()?;
+        kline.taker_sell_volume = reader.f64()?;
+        kline.taker_sell_quote_volume = reader.f64()?;
+        kline.taker_sell_trades = reader.u64()?;
+        kline.open_time = reader.u64()?;
+        kline.close_time = reader.u64()?;
+        kline.symbol = reader.symbol()?;
+        Ok(kline)
+    }
+}
+
+fn put_u64(buf: &mut Vec<u8>, value: u64) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_f64(buf: &mut Vec<u8>, value: f64) {
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+/// 品种名最长 255 字节，超长时返回错误而不是截断
+fn put_symbol(buf: &mut Vec<u8>, symbol: &str) -> Result<()> {
+    let len = u8::try_from(symbol.len()).map_err(|_| {
+        MdiError::StorageError(format!(
+            "Symbol too long for binary format: {} bytes (max {})",
+            symbol.len(),
+            u8::MAX
+        ))
+    })?;
+    buf.push(len);
+    buf.extend_from_slice(symbol.as_bytes());
+    Ok(())
+}
+
+/// 顺序读取二进制记录
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8], tag: u8) -> Result<Self> {
+        if bytes.first() != Some(&tag) {
+            return Err(MdiError::StorageError(format!(
+                "Unexpected value format tag: {:?}, expected {}",
+                bytes.first(),
+                tag
+            )));
+        }
+        Ok(Reader { bytes, pos: 1 })
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let slice = self
+            .bytes
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| MdiError::StorageError("Truncated binary record".to_string()))?;
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().expect("8 bytes")))
+    }
+
+    fn f64(&mut self) -> Result<f64> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().expect("8 bytes")))
+    }
+
+    fn symbol(&mut self) -> Result<String> {
+        let len = self.u8()? as usize;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|e| MdiError::StorageError(format!("Invalid symbol: {}", e)))
+    }
+}
@@ -0,0 +1,123 @@
+use crate::Tick;
+use parking_lot::RwLock;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+
+/// 单个品种的去重状态
+#[derive(Debug, Default)]
+struct DedupState {
+    /// 已见过的最大 trade_id
+    high_water: Option<u64>,
+    /// 高水位之下窗口内已见过的 trade_id
+    recent: BTreeSet<u64>,
+    /// 重复次数
+    duplicates: u64,
+    /// 早于窗口、无法确认是否见过而丢弃的次数
+    stale: u64,
+}
+
+/// 成交去重器 - 放在接收器和 `KLineBuilder` 之间，丢弃重复的成交
+///
+/// 每个品种维护 trade_id 高水位和其下 `window` 个 id 的已见集合，
+/// 允许窗口内的乱序成交；早于窗口的 id 无法确认，同样丢弃，但单独计为过期而不是重复
+pub struct TradeDedup {
+    window: u64,
+    /// symbol -> 去重状态
+    states: Arc<RwLock<HashMap<String, DedupState>>>,
+}
+
+impl TradeDedup {
+    /// 创建去重器
+    /// # Arguments
+    /// * `window` - 允许乱序的 trade_id 范围，例如: 1024
+    pub fn new(window: u64) -> Self {
+        TradeDedup {
+            window: window.max(1),
+            states: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// 检查 Tick 是否为新成交，新成交返回 true 并记录，重复返回 false
+    pub fn check(&self, tick: &Tick) -> bool {
+        let mut states = self.states.write();
+        let state = states.entry(tick.symbol.clone()).or_default();
+        let id = tick.trade_id;
+
+        match state.high_water {
+            None => {}
+            Some(high_water) if id > high_water => {}
+            Some(high_water) if high_water - id >= self.window => {
+                state.stale += 1;
+                return false;
+            }
+            Some(_) if state.recent.contains(&id) => {
+                state.duplicates += 1;
+                return false;
+            }
+            Some(_) => {}
+        }
+
+        state.recent.insert(id);
+        if state.high_water.is_none_or(|high_water| id > high_water) {
+            state.high_water = Some(id);
+            // 移出窗口的 id
+            if let Some(floor) = id.saturating_add(1).checked_sub(self.window) {
+                state.recent = state.recent.split_off(&floor);
+            }
+        }
+        true
+    }
+
+    /// 指定品种的重复次数
+    pub fn duplicates(&self, symbol: &str) -> u64 {
+        self.states.read().get(symbol).map(|state| state.duplicates).unwrap_or(0)
+    }
+
+    /// 指定品种早于窗口而丢弃的次数
+    pub fn stale(&self, symbol: &str) -> u64 {
+        self.states.read().get(symbol).map(|state| state.stale).unwrap_or(0)
+    }
+
+    /// 获取去重统计
+    pub fn get_stats(&self) -> DedupStats {
+        let states = self.states.read();
+        DedupStats {
+            duplicates: states
+                .iter()
+                .map(|(symbol, state)| (symbol.clone(), state.duplicates))
+                .collect(),
+            total_duplicates: states.values().map(|state| state.duplicates).sum(),
+            total_stale: states.values().map(|state| state.stale).sum(),
+        }
+    }
+
+    /// 清空所有状态
+    pub fn clear(&self) {
+        self.states.write().clear();
+    }
+}
+
+impl Default for TradeDedup {
+    fn default() -> Self {
+        TradeDedup::new(1024)
+    }
+}
+
+impl Clone for TradeDedup {
+    fn clone(&self) -> Self {
+        TradeDedup {
+            window: self.window,
+            states: Arc::clone(&self.states),
+        }
+    }
+}
+
+/// 去重统计信息
+#[derive(Debug, Clone)]
+pub struct DedupStats {
+    /// symbol -> 重复次数
+    pub duplicates: HashMap<String, u64>,
+    pub total_duplicates: u64,
+    /// 早于窗口而丢弃的总次数
+    pub total_stale: u64,
+}
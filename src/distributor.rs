@@ -1,5 +1,10 @@
-use crate::KLine;
+use crate::{KLine, SymbolStats, VolumeProfile, MdiError, Result};
+use crate::indicators::IndicatorValue;
+use crate::interval::Interval;
 use tokio::sync::broadcast;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
 use std::sync::Arc;
 
 /// K 线广播事件
@@ -9,10 +14,145 @@ pub struct KLineEvent {
     pub is_closed: bool, // K 线是否已完成
 }
 
+/// 成交量分布广播事件
+#[derive(Debug, Clone)]
+pub struct ProfileEvent {
+    pub profile: VolumeProfile,
+    pub is_closed: bool,
+}
+
+/// 品种匹配规则
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SymbolPattern {
+    /// 所有品种
+    Any,
+    Exact(String),
+    /// `*` 匹配任意长度，`?` 匹配单个字符，例如 "*USDT"
+    Glob(String),
+}
+
+impl SymbolPattern {
+    /// "*" 为所有品种，含 `*` 或 `?` 时按通配符匹配，否则精确匹配
+    pub fn new(pattern: &str) -> Self {
+        if pattern == "*" {
+            SymbolPattern::Any
+        } else if pattern.contains(['*', '?']) {
+            SymbolPattern::Glob(pattern.to_string())
+        } else {
+            SymbolPattern::Exact(pattern.to_string())
+        }
+    }
+
+    pub fn matches(&self, symbol: &str) -> bool {
+        match self {
+            SymbolPattern::Any => true,
+            SymbolPattern::Exact(exact) => exact == symbol,
+            SymbolPattern::Glob(glob) => glob_match(glob.as_bytes(), symbol.as_bytes()),
+        }
+    }
+}
+
+impl fmt::Display for SymbolPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SymbolPattern::Any => write!(f, "*"),
+            SymbolPattern::Exact(pattern) | SymbolPattern::Glob(pattern) => write!(f, "{}", pattern),
+        }
+    }
+}
+
+/// K 线订阅条件，`interval` 为 None 表示所有周期
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SubscriptionPattern {
+    pub symbol: SymbolPattern,
+    pub interval: Option<u64>,
+}
+
+impl SubscriptionPattern {
+    /// 所有品种的所有周期
+    pub fn all() -> Self {
+        SubscriptionPattern { symbol: SymbolPattern::Any, interval: None }
+    }
+
+    /// 限定品种，规则见 `SymbolPattern::new`
+    pub fn symbol(mut self, pattern: &str) -> Self {
+        self.symbol = SymbolPattern::new(pattern);
+        self
+    }
+
+    /// 限定周期 id
+    pub fn interval(mut self, interval: u64) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    pub fn matches(&self, symbol: &str, interval: u64) -> bool {
+        self.interval.is_none_or(|expected| expected == interval) && self.symbol.matches(symbol)
+    }
+}
+
+impl fmt::Display for SubscriptionPattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.interval {
+            Some(interval) => write!(f, "{}:{}", self.symbol, interval),
+            None => write!(f, "{}:*", self.symbol),
+        }
+    }
+}
+
+impl FromStr for SubscriptionPattern {
+    type Err = MdiError;
+
+    /// 解析 "品种:周期"，例如 "BTCUSDT:*"、"*:1m"、"*USDT:60"；周期为 `*`、周期 id 或周期字符串
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || MdiError::Other(format!("Invalid subscription pattern: {}", s));
+        let (symbol, interval) = s.split_once(':').ok_or_else(invalid)?;
+        if symbol.is_empty() {
+            return Err(invalid());
+        }
+        let interval = match interval {
+            "*" => None,
+            interval => match interval.parse::<u64>() {
+                Ok(id) => Some(id),
+                Err(_) => Some(interval.parse::<Interval>().map_err(|_| invalid())?.id()),
+            },
+        };
+        Ok(SubscriptionPattern { symbol: SymbolPattern::new(symbol), interval })
+    }
+}
+
+type KLineSender = Arc<broadcast::Sender<KLineEvent>>;
+
+/// 通配订阅及其预先计算的分发表
+#[derive(Default)]
+struct PatternRoutes {
+    channels: HashMap<SubscriptionPattern, KLineSender>,
+    /// symbol -> interval -> 匹配的通配通道，首次发布该品种周期时计算，新增订阅时增量更新
+    routes: HashMap<String, HashMap<u64, Arc<[KLineSender]>>>,
+}
+
+impl PatternRoutes {
+    fn matching(&self, symbol: &str, interval: u64) -> Arc<[KLineSender]> {
+        self.channels
+            .iter()
+            .filter(|(pattern, _)| pattern.matches(symbol, interval))
+            .map(|(_, sender)| Arc::clone(sender))
+            .collect()
+    }
+}
+
 /// 分发器 - 管理多个订阅通道
 pub struct Distributor {
     /// symbol -> （interval -> broadcast channel）
     channels: Arc<parking_lot::RwLock<std::collections::HashMap<String, Arc<broadcast::Sender<KLineEvent>>>>>,
+    /// 通配 K 线订阅
+    patterns: Arc<parking_lot::RwLock<PatternRoutes>>,
+    /// symbol:interval -> 指标通道
+    indicator_channels: Arc<parking_lot::RwLock<std::collections::HashMap<String, Arc<broadcast::Sender<IndicatorValue>>>>>,
+    /// symbol:interval -> 成交量分布通道
+    profile_channels: Arc<parking_lot::RwLock<std::collections::HashMap<String, Arc<broadcast::Sender<ProfileEvent>>>>>,
+    /// symbol -> 滚动行情通道
+    ticker_channels: Arc<parking_lot::RwLock<std::collections::HashMap<String, Arc<broadcast::Sender<SymbolStats>>>>>,
     channel_capacity: usize,
 }
 
@@ -21,23 +161,46 @@ impl Distributor {
     pub fn new(channel_capacity: usize) -> Self {
         Distributor {
             channels: Arc::new(parking_lot::RwLock::new(std::collections::HashMap::new())),
+            patterns: Arc::new(parking_lot::RwLock::new(PatternRoutes::default())),
+            indicator_channels: Arc::new(parking_lot::RwLock::new(std::collections::HashMap::new())),
+            profile_channels: Arc::new(parking_lot::RwLock::new(std::collections::HashMap::new())),
+            ticker_channels: Arc::new(parking_lot::RwLock::new(std::collections::HashMap::new())),
             channel_capacity,
         }
     }
 
-    /// 发送 K 线事件
+    /// 发送 K 线事件，返回精确订阅和匹配的通配订阅的接收者总数
     pub fn broadcast_kline(&self, kline: KLine, is_closed: bool) -> usize {
+        let routes = self.pattern_routes(&kline.symbol, kline.interval);
         let key = format!("{}:{}", kline.symbol, kline.interval);
-        let channels = self.channels.read();
+        let sender = self.channels.read().get(&key).cloned();
 
-        if let Some(sender) = channels.get(&key) {
-            let event = KLineEvent { kline, is_closed };
+        let event = KLineEvent { kline, is_closed };
+        let mut receivers = 0;
+        for sender in routes.iter().chain(sender.as_ref()) {
             // 记录失败的订阅者数量，但不中断广播
-            let _ = sender.send(event);
-            sender.receiver_count()
-        } else {
-            0
+            let _ = sender.send(event.clone());
+            receivers += sender.receiver_count();
+        }
+        receivers
+    }
+
+    /// 品种和周期对应的通配通道，未缓存时计算一次
+    fn pattern_routes(&self, symbol: &str, interval: u64) -> Arc<[KLineSender]> {
+        {
+            let patterns = self.patterns.read();
+            if patterns.channels.is_empty() {
+                return Arc::new([]);
+            }
+            if let Some(routes) = patterns.routes.get(symbol).and_then(|routes| routes.get(&interval)) {
+                return Arc::clone(routes);
+            }
         }
+
+        let mut patterns = self.patterns.write();
+        let routes = patterns.matching(symbol, interval);
+        patterns.routes.entry(symbol.to_string()).or_default().insert(interval, Arc::clone(&routes));
+        routes
     }
 
     /// 订阅指定品种和周期的 K 线
@@ -56,29 +219,156 @@ impl Distributor {
         sender.subscribe()
     }
 
-    /// 获取订阅者数量
+    /// 按条件订阅 K 线，例如某品种的所有周期、所有品种的 1 分钟、"*USDT" 的所有周期
+    ///
+    /// 同一条件共用一个通道；匹配在订阅时预先算入分发表，发布时只查表
+    pub fn subscribe_pattern(&self, pattern: &SubscriptionPattern) -> broadcast::Receiver<KLineEvent> {
+        if let (SymbolPattern::Exact(symbol), Some(interval)) = (&pattern.symbol, pattern.interval) {
+            return self.subscribe(symbol, interval);
+        }
+
+        let mut patterns = self.patterns.write();
+        if let Some(sender) = patterns.channels.get(pattern) {
+            return sender.subscribe();
+        }
+
+        let (tx, rx) = broadcast::channel(self.channel_capacity);
+        let sender = Arc::new(tx);
+        for (symbol, routes) in patterns.routes.iter_mut() {
+            for (&interval, senders) in routes.iter_mut() {
+                if pattern.matches(symbol, interval) {
+                    *senders = senders.iter().cloned().chain([Arc::clone(&sender)]).collect();
+                }
+            }
+        }
+        patterns.channels.insert(pattern.clone(), sender);
+        rx
+    }
+
+    /// 发送指标值
+    pub fn broadcast_indicator(&self, value: IndicatorValue) -> usize {
+        let key = format!("{}:{}", value.symbol, value.interval);
+        let channels = self.indicator_channels.read();
+
+        if let Some(sender) = channels.get(&key) {
+            let _ = sender.send(value);
+            sender.receiver_count()
+        } else {
+            0
+        }
+    }
+
+    /// 订阅指定品种和周期的全部指标
+    pub fn subscribe_indicators(&self, symbol: &str, interval: u64) -> broadcast::Receiver<IndicatorValue> {
+        let key = format!("{}:{}", symbol, interval);
+        let mut channels = self.indicator_channels.write();
+
+        let sender = channels
+            .entry(key)
+            .or_insert_with(|| {
+                let (tx, _) = broadcast::channel(self.channel_capacity);
+                Arc::new(tx)
+            })
+            .clone();
+
+        sender.subscribe()
+    }
+
+    /// 发送成交量分布
+    pub fn broadcast_profile(&self, profile: VolumeProfile, is_closed: bool) -> usize {
+        let key = format!("{}:{}", profile.symbol, profile.interval);
+        let channels = self.profile_channels.read();
+
+        if let Some(sender) = channels.get(&key) {
+            let _ = sender.send(ProfileEvent { profile, is_closed });
+            sender.receiver_count()
+        } else {
+            0
+        }
+    }
+
+    /// 订阅指定品种和周期的成交量分布
+    pub fn subscribe_profiles(&self, symbol: &str, interval: u64) -> broadcast::Receiver<ProfileEvent> {
+        let key = format!("{}:{}", symbol, interval);
+        let mut channels = self.profile_channels.write();
+
+        let sender = channels
+            .entry(key)
+            .or_insert_with(|| {
+                let (tx, _) = broadcast::channel(self.channel_capacity);
+                Arc::new(tx)
+            })
+            .clone();
+
+        sender.subscribe()
+    }
+
+    /// 发送滚动行情
+    pub fn broadcast_ticker(&self, ticker: SymbolStats) -> usize {
+        let channels = self.ticker_channels.read();
+
+        if let Some(sender) = channels.get(&ticker.symbol) {
+            let _ = sender.send(ticker);
+            sender.receiver_count()
+        } else {
+            0
+        }
+    }
+
+    /// 订阅指定品种的滚动行情
+    pub fn subscribe_ticker(&self, symbol: &str) -> broadcast::Receiver<SymbolStats> {
+        let mut channels = self.ticker_channels.write();
+
+        let sender = channels
+            .entry(symbol.to_string())
+            .or_insert_with(|| {
+                let (tx, _) = broadcast::channel(self.channel_capacity);
+                Arc::new(tx)
+            })
+            .clone();
+
+        sender.subscribe()
+    }
+
+    /// 获取订阅者数量，包括匹配的通配订阅
     pub fn subscriber_count(&self, symbol: &str, interval: u64) -> usize {
         let key = format!("{}:{}", symbol, interval);
-        let channels = self.channels.read();
-        channels
-            .get(&key)
-            .map(|sender| sender.receiver_count())
-            .unwrap_or(0)
+        let exact = self.channels.read().get(&key).map(|sender| sender.receiver_count()).unwrap_or(0);
+        let patterns = self.patterns.read();
+        let matched: usize = patterns
+            .channels
+            .iter()
+            .filter(|(pattern, _)| pattern.matches(symbol, interval))
+            .map(|(_, sender)| sender.receiver_count())
+            .sum();
+        exact + matched
     }
 
-    /// 获取所有活跃频道
+    /// 获取所有活跃频道，通配订阅以条件表示，例如 "*USDT:60"
     pub fn active_channels(&self) -> Vec<String> {
         let channels = self.channels.read();
+        let patterns = self.patterns.read();
         channels
             .iter()
             .filter(|(_, sender)| sender.receiver_count() > 0)
             .map(|(key, _)| key.clone())
+            .chain(
+                patterns
+                    .channels
+                    .iter()
+                    .filter(|(_, sender)| sender.receiver_count() > 0)
+                    .map(|(pattern, _)| pattern.to_string()),
+            )
             .collect()
     }
 
     /// 清空所有频道
     pub fn clear(&self) {
         self.channels.write().clear();
+        *self.patterns.write() = PatternRoutes::default();
+        self.indicator_channels.write().clear();
+        self.profile_channels.write().clear();
+        self.ticker_channels.write().clear();
     }
 }
 
@@ -86,7 +376,39 @@ impl Clone for Distributor {
     fn clone(&self) -> Self {
         Distributor {
             channels: Arc::clone(&self.channels),
+            patterns: Arc::clone(&self.patterns),
+            indicator_channels: Arc::clone(&self.indicator_channels),
+            profile_channels: Arc::clone(&self.profile_channels),
+            ticker_channels: Arc::clone(&self.ticker_channels),
             channel_capacity: self.channel_capacity,
         }
     }
 }
+
+/// 通配符匹配，`*` 匹配任意长度（含空），`?` 匹配单个字节
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // 最近一个 `*` 的位置及其匹配到的文本位置，失配时回溯
+    let mut star = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
@@ -0,0 +1,306 @@
+use crate::storage::{Cursor, Page, RangeOptions};
+use crate::{Tick, KLine, TickStore, MdiError, Result};
+use arrow::array::{ArrayRef, BooleanArray, Float64Array, StringArray, UInt64Array};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde::Serialize;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// 导出文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Parquet,
+}
+
+impl FromStr for ExportFormat {
+    type Err = MdiError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "parquet" => Ok(ExportFormat::Parquet),
+            _ => Err(MdiError::ExportError(format!("Unknown export format: {}", s))),
+        }
+    }
+}
+
+/// 导出选项
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Parquet 每个 row group 的行数
+    pub row_group_size: usize,
+    /// 每次从存储读取的记录数
+    pub page_size: usize,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            format: ExportFormat::Csv,
+            row_group_size: 128 * 1024,
+            page_size: 10_000,
+        }
+    }
+}
+
+impl ExportOptions {
+    pub fn new(format: ExportFormat) -> Self {
+        ExportOptions { format, ..ExportOptions::default() }
+    }
+
+    pub fn row_group_size(mut self, row_group_size: usize) -> Self {
+        self.row_group_size = row_group_size.max(1);
+        self
+    }
+
+    pub fn page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+}
+
+/// 导出统计
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportStats {
+    pub rows: u64,
+    /// Parquet row group 数，CSV 为 0
+    pub row_groups: u64,
+}
+
+/// 导出指定品种 `[from_ms, to_ms)` 的 Tick，按品种顺序、时间排序写入 `out`
+pub fn export_ticks<W: Write + Send>(
+    storage: &dyn TickStore,
+    symbols: &[String],
+    from_ms: u64,
+    to_ms: u64,
+    options: &ExportOptions,
+    out: W,
+) -> Result<ExportStats> {
+    export(options, out, symbols, |symbol, range| {
+        storage.read_ticks_range(symbol, from_ms, to_ms, range)
+    })
+}
+
+/// 导出指定品种、周期 `[from, to)`（秒）的 K 线
+pub fn export_klines<W: Write + Send>(
+    storage: &dyn TickStore,
+    symbols: &[String],
+    interval: u64,
+    from: u64,
+    to: u64,
+    options: &ExportOptions,
+    out: W,
+) -> Result<ExportStats> {
+    export(options, out, symbols, |symbol, range| {
+        storage.read_klines_range(symbol, interval, from, to, range)
+    })
+}
+
+/// 逐页读取并写出，内存占用与 `page_size`、`row_group_size` 成正比
+fn export<R, W, F>(options: &ExportOptions, out: W, symbols: &[String], mut read_page: F) -> Result<ExportStats>
+where
+    R: ExportRecord,
+    W: Write + Send,
+    F: FnMut(&str, &RangeOptions) -> Result<Page<R>>,
+{
+    let mut sink = Sink::new(options, out)?;
+    for symbol in symbols {
+        let mut cursor: Option<Cursor> = None;
+        loop {
+            let mut range = RangeOptions::new().limit(options.page_size);
+            if let Some(cursor) = cursor {
+                range = range.after(cursor);
+            }
+            let page = read_page(symbol, &range)?;
+            sink.write(page.items)?;
+            cursor = match page.next_cursor {
+                Some(next) => Some(next),
+                None => break,
+            };
+        }
+    }
+    sink.finish()
+}
+
+/// 可导出的记录
+trait ExportRecord: Serialize + Sized {
+    fn schema() -> SchemaRef;
+    fn to_batch(rows: &[Self]) -> Result<RecordBatch>;
+}
+
+impl ExportRecord for Tick {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("symbol", DataType::Utf8, false),
+            Field::new("timestamp", DataType::UInt64, false),
+            Field::new("event_time", DataType::UInt64, false),
+            Field::new("price", DataType::Float64, false),
+            Field::new("quantity", DataType::Float64, false),
+            Field::new("is_buyer_maker", DataType::Boolean, false),
+            Field::new("trade_id", DataType::UInt64, false),
+        ]))
+    }
+
+    fn to_batch(rows: &[Self]) -> Result<RecordBatch> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from_iter_values(rows.iter().map(|t| t.symbol.as_str()))),
+            Arc::new(UInt64Array::from_iter_values(rows.iter().map(|t| t.timestamp))),
+            Arc::new(UInt64Array::from_iter_values(rows.iter().map(|t| t.event_time))),
+            Arc::new(Float64Array::from_iter_values(rows.iter().map(|t| t.price))),
+            Arc::new(Float64Array::from_iter_values(rows.iter().map(|t| t.quantity))),
+            Arc::new(BooleanArray::from_iter(rows.iter().map(|t| Some(t.is_buyer_maker)))),
+            Arc::new(UInt64Array::from_iter_values(rows.iter().map(|t| t.trade_id))),
+        ];
+        record_batch(Tick::schema(), columns)
+    }
+}
+
+impl ExportRecord for KLine {
+    fn schema() -> SchemaRef {
+        let u64_field = |name: &str| Field::new(name, DataType::UInt64, false);
+        let f64_field = |name: &str| Field::new(name, DataType::Float64, false);
+        Arc::new(Schema::new(vec![
+            Field::new("symbol", DataType::Utf8, false),
+            u64_field("timestamp"),
+            u64_field("interval"),
+            f64_field("open"),
+            f64_field("high"),
+            f64_field("low"),
+            f64_field("close"),
+            f64_field("volume"),
+            f64_field("quote_asset_volume"),
+            u64_field("number_of_trades"),
+            f64_field("taker_buy_volume"),
+            f64_field("taker_buy_quote_volume"),
+            u64_field("taker_buy_trades"),
+            f64_field("taker_sell_volume"),
+            f64_field("taker_sell_quote_volume"),
+            u64_field("taker_sell_trades"),
+            u64_field("open_time"),
+            u64_field("close_time"),
+        ]))
+    }
+
+    fn to_batch(rows: &[Self]) -> Result<RecordBatch> {
+        let u64_column = |f: fn(&KLine) -> u64| -> ArrayRef {
+            Arc::new(UInt64Array::from_iter_values(rows.iter().map(f)))
+        };
+        let f64_column = |f: fn(&KLine) -> f64| -> ArrayRef {
+            Arc::new(Float64Array::from_iter_values(rows.iter().map(f)))
+        };
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from_iter_values(rows.iter().map(|k| k.symbol.as_str()))),
+            u64_column(|k| k.timestamp),
+            u64_column(|k| k.interval),
+            f64_column(|k| k.open),
+            f64_column(|k| k.high),
+            f64_column(|k| k.low),
+            f64_column(|k| k.close),
+            f64_column(|k| k.volume),
+            f64_column(|k| k.quote_asset_volume),
+            u64_column(|k| k.number_of_trades),
+            f64_column(|k| k.taker_buy_volume),
+            f64_column(|k| k.taker_buy_quote_volume),
+            u64_column(|k| k.taker_buy_trades),
+            f64_column(|k| k.taker_sell_volume),
+            f64_column(|k| k.taker_sell_quote_volume),
+            u64_column(|k| k.taker_sell_trades),
+            u64_column(|k| k.open_time),
+            u64_column(|k| k.close_time),
+        ];
+        record_batch(KLine::schema(), columns)
+    }
+}
+
+fn record_batch(schema: SchemaRef, columns: Vec<ArrayRef>) -> Result<RecordBatch> {
+    RecordBatch::try_new(schema, columns)
+        .map_err(|e| MdiError::ExportError(format!("Failed to build record batch: {}", e)))
+}
+
+/// 输出端，Parquet 按 row group 攒批
+enum Sink<R, W: Write + Send> {
+    Csv { writer: csv::Writer<W>, rows: u64 },
+    Parquet { writer: ArrowWriter<W>, buffer: Vec<R>, row_group_size: usize, rows: u64 },
+}
+
+impl<R: ExportRecord, W: Write + Send> Sink<R, W> {
+    fn new(options: &ExportOptions, out: W) -> Result<Self> {
+        match options.format {
+            ExportFormat::Csv => Ok(Sink::Csv { writer: csv::Writer::from_writer(out), rows: 0 }),
+            ExportFormat::Parquet => {
+                let props = WriterProperties::builder()
+                    .set_max_row_group_size(options.row_group_size)
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+                let writer = ArrowWriter::try_new(out, R::schema(), Some(props)).map_err(|e| {
+                    MdiError::ExportError(format!("Failed to create parquet writer: {}", e))
+                })?;
+                Ok(Sink::Parquet {
+                    writer,
+                    buffer: Vec::with_capacity(options.row_group_size),
+                    row_group_size: options.row_group_size,
+                    rows: 0,
+                })
+            }
+        }
+    }
+
+    fn write(&mut self, records: Vec<R>) -> Result<()> {
+        match self {
+            Sink::Csv { writer, rows } => {
+                for record in &records {
+                    writer.serialize(record).map_err(|e| {
+                        MdiError::ExportError(format!("CSV write error: {}", e))
+                    })?;
+                }
+                *rows += records.len() as u64;
+            }
+            Sink::Parquet { writer, buffer, row_group_size, rows } => {
+                for record in records {
+                    buffer.push(record);
+                    if buffer.len() >= *row_group_size {
+                        *rows += flush_parquet(writer, buffer)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<ExportStats> {
+        match self {
+            Sink::Csv { mut writer, rows } => {
+                writer.flush().map_err(|e| MdiError::ExportError(format!("CSV flush error: {}", e)))?;
+                Ok(ExportStats { rows, row_groups: 0 })
+            }
+            Sink::Parquet { mut writer, mut buffer, mut rows, .. } => {
+                rows += flush_parquet(&mut writer, &mut buffer)?;
+                let metadata = writer.close().map_err(|e| {
+                    MdiError::ExportError(format!("Failed to close parquet writer: {}", e))
+                })?;
+                Ok(ExportStats { rows, row_groups: metadata.row_groups.len() as u64 })
+            }
+        }
+    }
+}
+
+fn flush_parquet<R: ExportRecord, W: Write + Send>(writer: &mut ArrowWriter<W>, buffer: &mut Vec<R>) -> Result<u64> {
+    if buffer.is_empty() {
+        return Ok(0);
+    }
+    let batch = R::to_batch(buffer)?;
+    writer
+        .write(&batch)
+        .map_err(|e| MdiError::ExportError(format!("Parquet write error: {}", e)))?;
+    let rows = buffer.len() as u64;
+    buffer.clear();
+    Ok(rows)
+}
@@ -0,0 +1,331 @@
+use crate::storage::RangeOptions;
+use crate::{Tick, KLine, Interval, KLineBuilder, TickStore, MdiError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// 早于该值的时间戳为毫秒，否则为微秒（Binance 现货数据自 2025 年起使用微秒）
+const MICROS_THRESHOLD: u64 = 100_000_000_000_000;
+
+/// 归档文件中的数据类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    /// `SYMBOL-trades-YYYY-MM[-DD]`
+    Trades,
+    /// `SYMBOL-1m-YYYY-MM[-DD]`
+    KLines(Interval),
+}
+
+/// Binance 公开数据归档文件（data.binance.vision）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveFile {
+    pub path: PathBuf,
+    pub symbol: String,
+    pub kind: ArchiveKind,
+}
+
+impl ArchiveFile {
+    /// 按文件名识别品种和数据类型，支持 `.zip` 和解压后的 `.csv`
+    pub fn parse<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let invalid = || MdiError::ImportError(format!("Unrecognized archive name: {}", path.display()));
+
+        let stem = path.file_stem().and_then(|stem| stem.to_str()).ok_or_else(invalid)?;
+        let mut parts = stem.splitn(3, '-');
+        let (symbol, kind) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(symbol), Some(kind), Some(_date)) if !symbol.is_empty() => (symbol, kind),
+            _ => return Err(invalid()),
+        };
+        let kind = match kind {
+            "trades" => ArchiveKind::Trades,
+            interval => ArchiveKind::KLines(interval.parse().map_err(|_| invalid())?),
+        };
+
+        Ok(ArchiveFile { path: path.to_path_buf(), symbol: symbol.to_string(), kind })
+    }
+
+    /// 进度记录的元数据键
+    fn progress_key(&self) -> String {
+        let name = self.path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        format!("import:{}", name)
+    }
+}
+
+/// 单个文件的导入进度，保存在存储的元数据中
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct ImportProgress {
+    /// 已写入的数据行数
+    rows: u64,
+    complete: bool,
+}
+
+/// 导入统计
+#[derive(Debug, Clone, Default)]
+pub struct ImportStats {
+    pub files: u64,
+    /// 已完整导入而跳过的文件
+    pub skipped_files: u64,
+    pub ticks: u64,
+    pub klines: u64,
+    /// 断点续传时跳过的已导入行数
+    pub resumed_rows: u64,
+    /// symbol -> 导入 Tick 的时间范围 [from_ms, to_ms)
+    pub tick_ranges: HashMap<String, (u64, u64)>,
+}
+
+impl ImportStats {
+    fn merge(&mut self, other: ImportStats) {
+        self.files += other.files;
+        self.skipped_files += other.skipped_files;
+        self.ticks += other.ticks;
+        self.klines += other.klines;
+        self.resumed_rows += other.resumed_rows;
+        for (symbol, (from, to)) in other.tick_ranges {
+            let range = self.tick_ranges.entry(symbol).or_insert((from, to));
+            *range = (range.0.min(from), range.1.max(to));
+        }
+    }
+}
+
+/// Binance 历史数据导入器
+///
+/// 读取 `trades` / `klines` 的 zip 或 CSV 文件，批量写入存储。记录按键覆盖写入，
+/// 重复导入不会产生重复数据；每批写入后记录进度，中断后从上次的行继续
+pub struct Importer {
+    storage: Arc<dyn TickStore>,
+    batch_size: usize,
+}
+
+impl Importer {
+    pub fn new(storage: Arc<dyn TickStore>) -> Self {
+        Importer { storage, batch_size: 10_000 }
+    }
+
+    /// 每批写入的记录数
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// 导入文件或目录（按文件名顺序导入目录下可识别的文件）
+    pub fn import_path<P: AsRef<Path>>(&self, path: P) -> Result<ImportStats> {
+        let path = path.as_ref();
+        if !path.is_dir() {
+            return self.import_file(&ArchiveFile::parse(path)?);
+        }
+
+        let mut files: Vec<_> = std::fs::read_dir(path)
+            .map_err(|e| MdiError::ImportError(format!("Failed to read {}: {}", path.display(), e)))?
+            .filter_map(|entry| ArchiveFile::parse(entry.ok()?.path()).ok())
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut stats = ImportStats::default();
+        for file in &files {
+            stats.merge(self.import_file(file)?);
+        }
+        Ok(stats)
+    }
+
+    /// 导入单个文件，已完整导入的文件直接跳过
+    pub fn import_file(&self, file: &ArchiveFile) -> Result<ImportStats> {
+        let mut stats = ImportStats::default();
+        let mut progress: ImportProgress = match self.storage.read_meta(&file.progress_key())? {
+            Some(value) => serde_json::from_slice(&value)?,
+            None => ImportProgress::default(),
+        };
+        if progress.complete {
+            stats.skipped_files = 1;
+            return Ok(stats);
+        }
+        stats.files = 1;
+        stats.resumed_rows = progress.rows;
+
+        let mut ticks = Vec::with_capacity(self.batch_size);
+        let mut klines = Vec::with_capacity(self.batch_size);
+        let mut row = 0;
+        for_each_record(&file.path, |record| {
+            row += 1;
+            if row <= progress.rows {
+                return Ok(());
+            }
+            match file.kind {
+                ArchiveKind::Trades => {
+                    let tick = parse_trade(&file.symbol, record)?;
+                    let range = stats.tick_ranges.entry(file.symbol.clone()).or_insert((tick.timestamp, tick.timestamp));
+                    *range = (range.0.min(tick.timestamp), range.1.max(tick.timestamp + 1));
+                    ticks.push(tick);
+                }
+                ArchiveKind::KLines(interval) => klines.push(parse_kline(&file.symbol, &interval, record)?),
+            }
+            if ticks.len() + klines.len() >= self.batch_size {
+                self.flush(file, &mut ticks, &mut klines, &mut progress, &mut stats)?;
+            }
+            Ok(())
+        })?;
+        self.flush(file, &mut ticks, &mut klines, &mut progress, &mut stats)?;
+
+        progress.complete = true;
+        self.storage.write_meta(&file.progress_key(), &serde_json::to_vec(&progress)?)?;
+        Ok(stats)
+    }
+
+    /// 写入一批记录并保存进度
+    fn flush(
+        &self,
+        file: &ArchiveFile,
+        ticks: &mut Vec<Tick>,
+        klines: &mut Vec<KLine>,
+        progress: &mut ImportProgress,
+        stats: &mut ImportStats,
+    ) -> Result<()> {
+        if ticks.is_empty() && klines.is_empty() {
+            return Ok(());
+        }
+        self.storage.write_ticks(ticks)?;
+        self.storage.write_klines(klines)?;
+
+        let rows = (ticks.len() + klines.len()) as u64;
+        progress.rows += rows;
+        stats.ticks += ticks.len() as u64;
+        stats.klines += klines.len() as u64;
+        ticks.clear();
+        klines.clear();
+        self.storage.write_meta(&file.progress_key(), &serde_json::to_vec(progress)?)
+    }
+
+    /// 用已存储的 Tick 重新生成覆盖 `[from_ms, to_ms)` 的 K 线并覆盖写入，返回写入的 K 线数
+    ///
+    /// 范围扩展到各周期的 K 线边界，边界上的 K 线也按完整的成交重新计算
+    pub fn rebuild_klines(&self, symbol: &str, intervals: &[Interval], from_ms: u64, to_ms: u64) -> Result<u64> {
+        if from_ms >= to_ms {
+            return Ok(0);
+        }
+        let (from_ms, to_ms) = intervals.iter().fold((from_ms, to_ms), |(from, to), interval| {
+            let start = interval.bucket_start(from_ms / 1000) * 1000;
+            let end = interval.bucket_end(interval.bucket_start((to_ms - 1) / 1000)) * 1000;
+            (from.min(start), to.max(end))
+        });
+
+        let builder = KLineBuilder::with_intervals(intervals.to_vec());
+        let mut options = RangeOptions::new().limit(self.batch_size);
+        loop {
+            let page = self.storage.read_ticks_range(symbol, from_ms, to_ms, &options)?;
+            for tick in &page.items {
+                builder.process_tick(tick);
+            }
+            match page.next_cursor {
+                Some(cursor) => options = options.after(cursor),
+                None => break,
+            }
+        }
+
+        let mut written = 0;
+        for interval in intervals {
+            let klines = builder.get_klines(symbol, interval.id());
+            for chunk in klines.chunks(self.batch_size) {
+                self.storage.write_klines(chunk)?;
+            }
+            written += klines.len() as u64;
+        }
+        Ok(written)
+    }
+}
+
+/// 按行读取 zip 内所有 CSV 或单个 CSV 文件，跳过表头
+fn for_each_record<F>(path: &Path, mut f: F) -> Result<()>
+where
+    F: FnMut(&csv::StringRecord) -> Result<()>,
+{
+    let open_error = |e: &dyn std::fmt::Display| {
+        MdiError::ImportError(format!("Failed to read {}: {}", path.display(), e))
+    };
+    let file = File::open(path).map_err(|e| open_error(&e))?;
+
+    if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("zip")) {
+        let mut archive = zip::ZipArchive::new(file).map_err(|e| open_error(&e))?;
+        for index in 0..archive.len() {
+            let entry = archive.by_index(index).map_err(|e| open_error(&e))?;
+            if entry.name().ends_with(".csv") {
+                read_csv(entry, &mut f)?;
+            }
+        }
+        Ok(())
+    } else {
+        read_csv(file, &mut f)
+    }
+}
+
+fn read_csv<R: Read, F>(reader: R, f: &mut F) -> Result<()>
+where
+    F: FnMut(&csv::StringRecord) -> Result<()>,
+{
+    let mut reader = csv::ReaderBuilder::new().has_headers(false).flexible(true).from_reader(reader);
+    let mut record = csv::StringRecord::new();
+    let mut first = true;
+    while reader
+        .read_record(&mut record)
+        .map_err(|e| MdiError::ImportError(format!("CSV read error: {}", e)))?
+    {
+        // 较新的文件带表头，首列不是数字
+        if std::mem::take(&mut first) && record.get(0).is_some_and(|id| id.parse::<u64>().is_err()) {
+            continue;
+        }
+        f(&record)?;
+    }
+    Ok(())
+}
+
+fn field<T: std::str::FromStr>(record: &csv::StringRecord, index: usize) -> Result<T> {
+    record
+        .get(index)
+        .and_then(|value| value.trim().parse().ok())
+        .ok_or_else(|| MdiError::ImportError(format!("Invalid field {} in record {:?}", index, record)))
+}
+
+/// 统一为毫秒
+fn millis(timestamp: u64) -> u64 {
+    if timestamp >= MICROS_THRESHOLD {
+        timestamp / 1000
+    } else {
+        timestamp
+    }
+}
+
+/// `id,price,qty,quote_qty,time,is_buyer_maker[,is_best_match]`
+fn parse_trade(symbol: &str, record: &csv::StringRecord) -> Result<Tick> {
+    let timestamp = millis(field(record, 4)?);
+    let is_buyer_maker = field::<String>(record, 5)?.eq_ignore_ascii_case("true");
+    Ok(Tick::new(
+        symbol.to_string(),
+        timestamp,
+        timestamp,
+        field(record, 1)?,
+        field(record, 2)?,
+        is_buyer_maker,
+        field(record, 0)?,
+    ))
+}
+
+/// `open_time,open,high,low,close,volume,close_time,quote_volume,count,taker_buy_volume,taker_buy_quote_volume,ignore`
+///
+/// 归档中没有主动买卖的成交笔数，对应字段为 0
+fn parse_kline(symbol: &str, interval: &Interval, record: &csv::StringRecord) -> Result<KLine> {
+    let timestamp = millis(field(record, 0)?) / 1000;
+    let mut kline = KLine::new(symbol.to_string(), timestamp, interval.id(), field(record, 1)?);
+    kline.high = field(record, 2)?;
+    kline.low = field(record, 3)?;
+    kline.close = field(record, 4)?;
+    kline.volume = field(record, 5)?;
+    kline.quote_asset_volume = field(record, 7)?;
+    kline.number_of_trades = field(record, 8)?;
+    kline.taker_buy_volume = field(record, 9)?;
+    kline.taker_buy_quote_volume = field(record, 10)?;
+    kline.taker_sell_volume = kline.volume - kline.taker_buy_volume;
+    kline.taker_sell_quote_volume = kline.quote_asset_volume - kline.taker_buy_quote_volume;
+    kline.close_time = interval.bucket_end(timestamp);
+    Ok(kline)
+}
@@ -0,0 +1,424 @@
+use crate::KLine;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+
+/// 指标值广播事件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndicatorValue {
+    pub symbol: String,
+    /// 源 K 线周期
+    pub interval: u64,
+    /// 源 K 线开始时间
+    pub timestamp: u64,
+    /// 指标名称，例如 "EMA(20)"、"MACD(12,26,9)"
+    pub name: String,
+    /// 指标输出，顺序见 `IndicatorSpec`
+    pub values: Vec<f64>,
+    /// 源 K 线是否已完成；未完成时为暂定值，随 K 线更新而修正
+    pub is_closed: bool,
+}
+
+/// 指标类型
+///
+/// 输出：
+/// - `Sma`/`Ema`/`Rsi`/`Atr`: `[value]`
+/// - `Macd`: `[macd, signal, histogram]`
+/// - `Bollinger`: `[middle, upper, lower]`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IndicatorSpec {
+    Sma(usize),
+    Ema(usize),
+    /// Wilder RSI
+    Rsi(usize),
+    Macd { fast: usize, slow: usize, signal: usize },
+    /// `k` 为标准差倍数
+    Bollinger { period: usize, k: f64 },
+    /// Wilder ATR
+    Atr(usize),
+}
+
+impl IndicatorSpec {
+    /// 创建指标实例
+    pub fn build(&self) -> Box<dyn Indicator> {
+        match *self {
+            IndicatorSpec::Sma(period) => Box::new(Sma { window: Window::new(period) }),
+            IndicatorSpec::Ema(period) => Box::new(Ema { ema: Smoother::ema(period) }),
+            IndicatorSpec::Rsi(period) => Box::new(Rsi {
+                prev_close: None,
+                gain: Smoother::wilder(period),
+                loss: Smoother::wilder(period),
+            }),
+            IndicatorSpec::Macd { fast, slow, signal } => Box::new(Macd {
+                fast: Smoother::ema(fast),
+                slow: Smoother::ema(slow),
+                signal: Smoother::ema(signal),
+            }),
+            IndicatorSpec::Bollinger { period, k } => Box::new(Bollinger { window: Window::new(period), k }),
+            IndicatorSpec::Atr(period) => Box::new(Atr {
+                prev_close: None,
+                tr: Smoother::wilder(period),
+            }),
+        }
+    }
+}
+
+impl fmt::Display for IndicatorSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndicatorSpec::Sma(period) => write!(f, "SMA({})", period),
+            IndicatorSpec::Ema(period) => write!(f, "EMA({})", period),
+            IndicatorSpec::Rsi(period) => write!(f, "RSI({})", period),
+            IndicatorSpec::Macd { fast, slow, signal } => write!(f, "MACD({},{},{})", fast, slow, signal),
+            IndicatorSpec::Bollinger { period, k } => write!(f, "BB({},{})", period, k),
+            IndicatorSpec::Atr(period) => write!(f, "ATR({})", period),
+        }
+    }
+}
+
+/// 增量指标 - 每根 K 线 O(1) 更新
+pub trait Indicator: Send + Sync {
+    /// 用 K 线计算指标值，数据不足时返回 None
+    ///
+    /// `commit` 为 false 时只计算暂定值，不改变内部状态，同一根 K 线可反复修正；
+    /// 为 true 时 K 线已完成，状态前进一根
+    fn update(&mut self, kline: &KLine, commit: bool) -> Option<Vec<f64>>;
+}
+
+/// 指数平滑，前 `period` 个值的简单平均作为初值
+#[derive(Debug, Clone, Copy)]
+struct Smoother {
+    period: usize,
+    alpha: f64,
+    count: usize,
+    sum: f64,
+    value: Option<f64>,
+}
+
+impl Smoother {
+    fn new(period: usize, alpha: f64) -> Self {
+        Smoother { period: period.max(1), alpha, count: 0, sum: 0.0, value: None }
+    }
+
+    /// EMA: alpha = 2 / (period + 1)
+    fn ema(period: usize) -> Self {
+        Smoother::new(period, 2.0 / (period.max(1) as f64 + 1.0))
+    }
+
+    /// Wilder 平滑: alpha = 1 / period
+    fn wilder(period: usize) -> Self {
+        Smoother::new(period, 1.0 / period.max(1) as f64)
+    }
+
+    fn next(&self, x: f64) -> Self {
+        let mut next = *self;
+        match next.value {
+            Some(value) => next.value = Some(value + next.alpha * (x - value)),
+            None => {
+                next.count += 1;
+                next.sum += x;
+                if next.count >= next.period {
+                    next.value = Some(next.sum / next.period as f64);
+                }
+            }
+        }
+        next
+    }
+}
+
+/// 固定长度滑动窗口，维护和与平方和
+#[derive(Debug, Clone)]
+struct Window {
+    period: usize,
+    values: VecDeque<f64>,
+    sum: f64,
+    sum_sq: f64,
+}
+
+impl Window {
+    fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Window { period, values: VecDeque::with_capacity(period), sum: 0.0, sum_sq: 0.0 }
+    }
+
+    /// 加入 `x` 后窗口的 (均值, 总体方差)，窗口未满时返回 None
+    fn stats_with(&self, x: f64) -> Option<(f64, f64)> {
+        let (mut sum, mut sum_sq) = (self.sum + x, self.sum_sq + x * x);
+        if self.values.len() == self.period {
+            let oldest = self.values[0];
+            sum -= oldest;
+            sum_sq -= oldest * oldest;
+        } else if self.values.len() + 1 < self.period {
+            return None;
+        }
+        let n = self.period as f64;
+        let mean = sum / n;
+        Some((mean, (sum_sq / n - mean * mean).max(0.0)))
+    }
+
+    fn push(&mut self, x: f64) {
+        if self.values.len() == self.period {
+            let oldest = self.values.pop_front().expect("window is full");
+            self.sum -= oldest;
+            self.sum_sq -= oldest * oldest;
+        }
+        self.values.push_back(x);
+        self.sum += x;
+        self.sum_sq += x * x;
+    }
+}
+
+struct Sma {
+    window: Window,
+}
+
+impl Indicator for Sma {
+    fn update(&mut self, kline: &KLine, commit: bool) -> Option<Vec<f64>> {
+        let stats = self.window.stats_with(kline.close);
+        if commit {
+            self.window.push(kline.close);
+        }
+        stats.map(|(mean, _)| vec![mean])
+    }
+}
+
+struct Ema {
+    ema: Smoother,
+}
+
+impl Indicator for Ema {
+    fn update(&mut self, kline: &KLine, commit: bool) -> Option<Vec<f64>> {
+        let next = self.ema.next(kline.close);
+        if commit {
+            self.ema = next;
+        }
+        next.value.map(|value| vec![value])
+    }
+}
+
+struct Rsi {
+    prev_close: Option<f64>,
+    gain: Smoother,
+    loss: Smoother,
+}
+
+impl Indicator for Rsi {
+    fn update(&mut self, kline: &KLine, commit: bool) -> Option<Vec<f64>> {
+        let prev_close = match self.prev_close {
+            Some(prev_close) => prev_close,
+            None => {
+                if commit {
+                    self.prev_close = Some(kline.close);
+                }
+                return None;
+            }
+        };
+
+        let change = kline.close - prev_close;
+        let gain = self.gain.next(change.max(0.0));
+        let loss = self.loss.next((-change).max(0.0));
+        if commit {
+            self.prev_close = Some(kline.close);
+            self.gain = gain;
+            self.loss = loss;
+        }
+
+        let (gain, loss) = (gain.value?, loss.value?);
+        let rsi = if loss == 0.0 {
+            if gain == 0.0 { 50.0 } else { 100.0 }
+        } else {
+            100.0 - 100.0 / (1.0 + gain / loss)
+        };
+        Some(vec![rsi])
+    }
+}
+
+struct Macd {
+    fast: Smoother,
+    slow: Smoother,
+    signal: Smoother,
+}
+
+impl Indicator for Macd {
+    fn update(&mut self, kline: &KLine, commit: bool) -> Option<Vec<f64>> {
+        let fast = self.fast.next(kline.close);
+        let slow = self.slow.next(kline.close);
+        let macd = fast.value.zip(slow.value).map(|(fast, slow)| fast - slow);
+        // 信号线只在 MACD 可用后开始累积
+        let signal = match macd {
+            Some(macd) => self.signal.next(macd),
+            None => self.signal,
+        };
+        if commit {
+            self.fast = fast;
+            self.slow = slow;
+            self.signal = signal;
+        }
+
+        let (macd, signal) = macd.zip(signal.value)?;
+        Some(vec![macd, signal, macd - signal])
+    }
+}
+
+struct Bollinger {
+    window: Window,
+    k: f64,
+}
+
+impl Indicator for Bollinger {
+    fn update(&mut self, kline: &KLine, commit: bool) -> Option<Vec<f64>> {
+        let stats = self.window.stats_with(kline.close);
+        if commit {
+            self.window.push(kline.close);
+        }
+        stats.map(|(mean, variance)| {
+            let band = self.k * variance.sqrt();
+            vec![mean, mean + band, mean - band]
+        })
+    }
+}
+
+struct Atr {
+    prev_close: Option<f64>,
+    tr: Smoother,
+}
+
+impl Indicator for Atr {
+    fn update(&mut self, kline: &KLine, commit: bool) -> Option<Vec<f64>> {
+        let range = kline.high - kline.low;
+        let true_range = match self.prev_close {
+            Some(prev_close) => range
+                .max((kline.high - prev_close).abs())
+                .max((kline.low - prev_close).abs()),
+            None => range,
+        };
+        let tr = self.tr.next(true_range);
+        if commit {
+            self.prev_close = Some(kline.close);
+            self.tr = tr;
+        }
+        tr.value.map(|value| vec![value])
+    }
+}
+
+/// 单个品种、单个周期的指标状态
+struct IndicatorSet {
+    indicators: Vec<Box<dyn Indicator>>,
+    /// 当前未完成的源 K 线（最近一次更新）
+    current: Option<KLine>,
+    /// 最近一根已完成源 K 线的时间戳
+    last_closed: Option<u64>,
+    /// 每个指标的最新值
+    latest: Vec<Option<IndicatorValue>>,
+}
+
+/// 指标引擎 - 订阅 `KLineBuilder` 输出，按品种和周期增量计算指标
+///
+/// 源 K 线每次更新都会重新计算暂定值；出现更新的时间戳或显式标记完成时，
+/// 上一根 K 线以最后一次更新的数据提交。过期的更新被忽略
+pub struct IndicatorEngine {
+    specs: Vec<IndicatorSpec>,
+    names: Vec<String>,
+    /// (symbol, 周期) -> 指标状态
+    sets: Arc<RwLock<HashMap<(String, u64), IndicatorSet>>>,
+}
+
+impl IndicatorEngine {
+    /// 创建指标引擎
+    /// # Arguments
+    /// * `specs` - 每个品种、周期计算的指标，例如: vec![IndicatorSpec::Ema(20), IndicatorSpec::Rsi(14)]
+    pub fn new(specs: Vec<IndicatorSpec>) -> Self {
+        let names = specs.iter().map(ToString::to_string).collect();
+        IndicatorEngine {
+            specs,
+            names,
+            sets: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// 处理源 K 线更新，返回上一根 K 线提交后的指标值和当前暂定值
+    pub fn process_kline(&self, kline: &KLine, is_closed: bool) -> Vec<IndicatorValue> {
+        let mut values = Vec::new();
+        let mut sets = self.sets.write();
+        let set = sets
+            .entry((kline.symbol.clone(), kline.interval))
+            .or_insert_with(|| IndicatorSet {
+                indicators: self.specs.iter().map(IndicatorSpec::build).collect(),
+                current: None,
+                last_closed: None,
+                latest: vec![None; self.specs.len()],
+            });
+
+        if set.last_closed.is_some_and(|ts| ts >= kline.timestamp) {
+            return values;
+        }
+
+        if let Some(current) = &set.current {
+            if current.timestamp < kline.timestamp {
+                let finished = set.current.take().expect("current bar exists");
+                set.last_closed = Some(finished.timestamp);
+                self.evaluate(set, &finished, true, &mut values);
+            } else if current.timestamp > kline.timestamp {
+                // 过期的源 K 线更新，忽略
+                return values;
+            }
+        }
+
+        if is_closed {
+            set.current = None;
+            set.last_closed = Some(kline.timestamp);
+        } else {
+            set.current = Some(kline.clone());
+        }
+        self.evaluate(set, kline, is_closed, &mut values);
+
+        values
+    }
+
+    fn evaluate(&self, set: &mut IndicatorSet, kline: &KLine, is_closed: bool, out: &mut Vec<IndicatorValue>) {
+        for (i, indicator) in set.indicators.iter_mut().enumerate() {
+            if let Some(result) = indicator.update(kline, is_closed) {
+                let value = IndicatorValue {
+                    symbol: kline.symbol.clone(),
+                    interval: kline.interval,
+                    timestamp: kline.timestamp,
+                    name: self.names[i].clone(),
+                    values: result,
+                    is_closed,
+                };
+                set.latest[i] = Some(value.clone());
+                out.push(value);
+            }
+        }
+    }
+
+    /// 获取指定品种、周期、指标的最新值
+    pub fn latest(&self, symbol: &str, interval: u64, name: &str) -> Option<IndicatorValue> {
+        let index = self.names.iter().position(|n| n == name)?;
+        let sets = self.sets.read();
+        sets.get(&(symbol.to_string(), interval))
+            .and_then(|set| set.latest[index].clone())
+    }
+
+    /// 获取配置的指标
+    pub fn specs(&self) -> &[IndicatorSpec] {
+        &self.specs
+    }
+
+    /// 清空所有状态
+    pub fn clear(&self) {
+        self.sets.write().clear();
+    }
+}
+
+impl Clone for IndicatorEngine {
+    fn clone(&self) -> Self {
+        IndicatorEngine {
+            specs: self.specs.clone(),
+            names: self.names.clone(),
+            sets: Arc::clone(&self.sets),
+        }
+    }
+}
@@ -0,0 +1,333 @@
+use crate::{MdiError, Result};
+use chrono::{DateTime, Datelike, NaiveDate};
+use std::fmt;
+use std::str::FromStr;
+
+const SECONDS_PER_DAY: i64 = 86400;
+
+/// 日历周期标识的类型标记，与 `bars` 中信息驱动 K 线的 1..=6 号标记共用高 8 位
+///
+/// 低 56 位：48..56 位为种类（1 日 / 2 周 / 3 月），24..48 位为时区偏移加一天（秒），
+/// 低 24 位为时段开始（秒）
+pub const CALENDAR_TAG: u64 = 7 << 56;
+const TAG_MASK: u64 = 0xff << 56;
+const CALENDAR_KIND_SHIFT: u32 = 48;
+const CALENDAR_OFFSET_SHIFT: u32 = 24;
+const CALENDAR_FIELD_MASK: u64 = (1 << 24) - 1;
+const CALENDAR_DAILY: u64 = 1;
+const CALENDAR_WEEKLY: u64 = 2;
+const CALENDAR_MONTHLY: u64 = 3;
+
+/// 交易时段定义 - 决定日/周/月线的切分点
+///
+/// 切分点 = 本地时区（`utc_offset`）零点之后 `start` 秒。
+/// 例如 UTC+8 零点: `Session::new(8 * 3600, 0)`；
+/// 以 UTC+8 的 08:00 为界（即 UTC 零点）: `Session::new(8 * 3600, 8 * 3600)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Session {
+    /// 时区相对 UTC 的偏移（秒），东八区为 28800
+    utc_offset: i32,
+    /// 时段开始时间，本地零点之后的秒数
+    start: u32,
+}
+
+impl Session {
+    /// 偏移需在 ±24 小时以内，时段开始需小于一天，保证能编码进周期标识
+    pub fn new(utc_offset: i32, start: u32) -> Result<Self> {
+        if utc_offset.unsigned_abs() >= SECONDS_PER_DAY as u32 || start >= SECONDS_PER_DAY as u32 {
+            return Err(MdiError::Other(format!(
+                "Invalid session: utc_offset {}s, start {}s",
+                utc_offset, start
+            )));
+        }
+        Ok(Session { utc_offset, start })
+    }
+
+    /// 时区相对 UTC 的偏移（秒）
+    pub fn utc_offset(&self) -> i32 {
+        self.utc_offset
+    }
+
+    /// 时段开始时间，本地零点之后的秒数
+    pub fn start(&self) -> u32 {
+        self.start
+    }
+
+    /// UTC 零点切分
+    pub fn utc() -> Self {
+        Session::default()
+    }
+
+    /// 将 UTC 时间平移到"时段时间"，使切分点落在零点
+    fn shift(&self) -> i64 {
+        self.utc_offset as i64 - self.start as i64
+    }
+}
+
+/// K 线周期
+///
+/// `Seconds` 为固定长度周期，按 UTC 纪元对齐（原有行为）；
+/// `Daily`/`Weekly`/`Monthly` 为日历周期，按 `Session` 对齐，周线从周一开始
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Interval {
+    Seconds(u64),
+    Daily(Session),
+    Weekly(Session),
+    Monthly(Session),
+}
+
+impl Interval {
+    /// 周期标识，写入 `KLine::interval` 并用于缓存、存储和订阅的键
+    ///
+    /// 固定周期为秒数；日历周期带 `CALENDAR_TAG` 标记并编码种类和时段，
+    /// 不同时段的日线互不冲突。UTC 日线与 `Seconds(86400)` 相同，标识为 86400
+    pub fn id(&self) -> u64 {
+        let (kind, session) = match self.normalized() {
+            Interval::Seconds(secs) => return secs,
+            Interval::Daily(session) => (CALENDAR_DAILY, session),
+            Interval::Weekly(session) => (CALENDAR_WEEKLY, session),
+            Interval::Monthly(session) => (CALENDAR_MONTHLY, session),
+        };
+        let offset = (session.utc_offset as i64 + SECONDS_PER_DAY) as u64 & CALENDAR_FIELD_MASK;
+        CALENDAR_TAG
+            | (kind << CALENDAR_KIND_SHIFT)
+            | (offset << CALENDAR_OFFSET_SHIFT)
+            | (session.start as u64 & CALENDAR_FIELD_MASK)
+    }
+
+    /// 从周期标识还原，信息驱动 K 线等其它标记返回 None
+    pub fn from_id(id: u64) -> Option<Interval> {
+        match id & TAG_MASK {
+            0 if id > 0 => return Some(Interval::Seconds(id)),
+            CALENDAR_TAG => {}
+            _ => return None,
+        }
+        let offset = ((id >> CALENDAR_OFFSET_SHIFT) & CALENDAR_FIELD_MASK) as i64 - SECONDS_PER_DAY;
+        let session = Session::new(offset as i32, (id & CALENDAR_FIELD_MASK) as u32).ok()?;
+        match (id & !TAG_MASK) >> CALENDAR_KIND_SHIFT {
+            CALENDAR_DAILY => Some(Interval::Daily(session)),
+            CALENDAR_WEEKLY => Some(Interval::Weekly(session)),
+            CALENDAR_MONTHLY => Some(Interval::Monthly(session)),
+            _ => None,
+        }
+    }
+
+    /// UTC 日线与固定周期 `Seconds(86400)` 切分相同，统一为后者，其余周期不变
+    pub fn normalized(self) -> Interval {
+        match self {
+            Interval::Daily(session) if session == Session::utc() => Interval::Seconds(SECONDS_PER_DAY as u64),
+            interval => interval,
+        }
+    }
+
+    /// 计算时间戳（秒）所在周期的开始时间（秒）
+    pub fn bucket_start(&self, timestamp_sec: u64) -> u64 {
+        match self {
+            Interval::Seconds(secs) => (timestamp_sec / secs) * secs,
+            Interval::Daily(session) => {
+                let shift = session.shift();
+                let local = timestamp_sec as i64 + shift;
+                to_unsigned(local.div_euclid(SECONDS_PER_DAY) * SECONDS_PER_DAY - shift)
+            }
+            Interval::Weekly(session) => {
+                let shift = session.shift();
+                let date = local_date(timestamp_sec as i64 + shift);
+                let days_from_monday = date.weekday().num_days_from_monday() as i64;
+                let monday = date_to_seconds(date) - days_from_monday * SECONDS_PER_DAY;
+                to_unsigned(monday - shift)
+            }
+            Interval::Monthly(session) => {
+                let shift = session.shift();
+                let date = local_date(timestamp_sec as i64 + shift);
+                let first = NaiveDate::from_ymd_opt(date.year(), date.month(), 1)
+                    .expect("first day of month is always valid");
+                to_unsigned(date_to_seconds(first) - shift)
+            }
+        }
+    }
+
+    /// 计算周期结束时间（秒，不含），`bucket_start` 必须是周期开始时间
+    pub fn bucket_end(&self, bucket_start: u64) -> u64 {
+        match self {
+            Interval::Seconds(secs) => bucket_start + secs,
+            Interval::Daily(_) => bucket_start + SECONDS_PER_DAY as u64,
+            Interval::Weekly(_) => bucket_start + 7 * SECONDS_PER_DAY as u64,
+            Interval::Monthly(session) => {
+                let shift = session.shift();
+                let date = local_date(bucket_start as i64 + shift);
+                let (year, month) = if date.month() == 12 {
+                    (date.year() + 1, 1)
+                } else {
+                    (date.year(), date.month() + 1)
+                };
+                let next = NaiveDate::from_ymd_opt(year, month, 1)
+                    .expect("first day of month is always valid");
+                to_unsigned(date_to_seconds(next) - shift)
+            }
+        }
+    }
+
+    /// 周期边界是否都落在 `base_secs` 秒周期的边界上，即能否由该周期的 K 线汇总
+    pub fn is_aligned_to(&self, base_secs: u64) -> bool {
+        if base_secs == 0 {
+            return false;
+        }
+        match self {
+            Interval::Seconds(secs) => secs % base_secs == 0,
+            Interval::Daily(session) | Interval::Weekly(session) | Interval::Monthly(session) => {
+                (SECONDS_PER_DAY as u64).is_multiple_of(base_secs)
+                    && session.shift().rem_euclid(base_secs as i64) == 0
+            }
+        }
+    }
+
+    /// 是否为日历周期
+    pub fn is_calendar(&self) -> bool {
+        !matches!(self, Interval::Seconds(_))
+    }
+}
+
+impl From<u64> for Interval {
+    fn from(secs: u64) -> Self {
+        Interval::Seconds(secs)
+    }
+}
+
+impl fmt::Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let session = match self.normalized() {
+            Interval::Seconds(secs) => {
+                return match secs {
+                    s if s % 86400 == 0 => write!(f, "{}d", s / 86400),
+                    s if s % 3600 == 0 => write!(f, "{}h", s / 3600),
+                    s if s % 60 == 0 => write!(f, "{}m", s / 60),
+                    s => write!(f, "{}s", s),
+                };
+            }
+            Interval::Daily(session) => {
+                write!(f, "1d")?;
+                session
+            }
+            Interval::Weekly(session) => {
+                write!(f, "1w")?;
+                session
+            }
+            Interval::Monthly(session) => {
+                write!(f, "1M")?;
+                session
+            }
+        };
+
+        if session != Session::utc() {
+            write!(f, "@{}", format_offset(session.utc_offset as i64))?;
+            if session.start != 0 {
+                write!(f, "+{:02}:{:02}", session.start / 3600, session.start % 3600 / 60)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Interval {
+    type Err = MdiError;
+
+    /// 解析周期字符串，例如 "30s"、"1m"、"4h"、"1d"、"1w"、"1M"、"1d@+08:00"、"1d@+08:00+08:00"
+    ///
+    /// 日/周/月周期可通过 `@时区偏移[+时段开始]` 指定切分点，偏移格式为 `[+-]HH:MM`；
+    /// "1d" 和 "1d@+00:00" 都是 `Seconds(86400)`
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || MdiError::Other(format!("Invalid interval: {}", s));
+
+        let (period, session) = match s.split_once('@') {
+            Some((period, session)) => (period, Some(parse_session(session).ok_or_else(invalid)?)),
+            None => (s, None),
+        };
+
+        let unit_pos = period.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+        let (count, unit) = period.split_at(unit_pos);
+        let count: u64 = count.parse().map_err(|_| invalid())?;
+        if count == 0 {
+            return Err(invalid());
+        }
+
+        let explicit = session.is_some();
+        let session = session.unwrap_or_default();
+        let interval = match (unit, count) {
+            ("d", 1) if explicit => Interval::Daily(session),
+            ("w", 1) => Interval::Weekly(session),
+            ("M", 1) => Interval::Monthly(session),
+            _ if session != Session::utc() => return Err(invalid()),
+            ("s", n) => Interval::Seconds(n),
+            ("m", n) => Interval::Seconds(n * 60),
+            ("h", n) => Interval::Seconds(n * 3600),
+            ("d", n) => Interval::Seconds(n * 86400),
+            _ => return Err(invalid()),
+        };
+
+        Ok(interval.normalized())
+    }
+}
+
+fn local_date(local_seconds: i64) -> NaiveDate {
+    DateTime::from_timestamp(local_seconds, 0)
+        .expect("timestamp within chrono range")
+        .date_naive()
+}
+
+fn date_to_seconds(date: NaiveDate) -> i64 {
+    date.and_hms_opt(0, 0, 0)
+        .expect("midnight is always valid")
+        .and_utc()
+        .timestamp()
+}
+
+fn to_unsigned(seconds: i64) -> u64 {
+    seconds.max(0) as u64
+}
+
+fn format_offset(offset: i64) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let abs = offset.abs();
+    format!("{}{:02}:{:02}", sign, abs / 3600, abs % 3600 / 60)
+}
+
+/// 解析 "+08:00" 或 "+08:00+08:00"（时区偏移 + 时段开始）
+fn parse_session(s: &str) -> Option<Session> {
+    let (offset, rest) = parse_offset(s)?;
+    let start = if rest.is_empty() {
+        0
+    } else {
+        let (start, rest) = parse_offset(rest)?;
+        if !rest.is_empty() || !(0..SECONDS_PER_DAY).contains(&start) {
+            return None;
+        }
+        start as u32
+    };
+    Session::new(offset as i32, start).ok()
+}
+
+/// 解析开头的 `[+-]HH:MM`，返回秒数和剩余部分
+fn parse_offset(s: &str) -> Option<(i64, &str)> {
+    let bytes = s.as_bytes();
+    if bytes.len() < 6 || bytes[3] != b':' {
+        return None;
+    }
+    let sign = match bytes[0] {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let digits = |range: std::ops::Range<usize>| -> Option<i64> {
+        let field = &bytes[range];
+        if !field.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+        Some(field.iter().fold(0, |value, digit| value * 10 + (digit - b'0') as i64))
+    };
+    let hours = digits(1..3)?;
+    let minutes = digits(4..6)?;
+    if hours > 23 || minutes > 59 {
+        return None;
+    }
+    Some((sign * (hours * 3600 + minutes * 60), &s[6..]))
+}
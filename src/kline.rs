@@ -1,14 +1,64 @@
-use crate::{Tick, KLine};
-use std::collections::HashMap;
-use parking_lot::RwLock;
+use crate::distributor::KLineEvent;
+use crate::{Tick, KLine, Interval, TickStore, ProfileConfig, VolumeProfile, ThreadBuilder, CpuAffinity, MdiError, Result};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use parking_lot::{Mutex, RwLock};
 use std::sync::Arc;
+use std::thread::JoinHandle;
+use crossbeam::channel::{self, Sender};
+
+/// 单个品种的 K 线状态
+#[derive(Debug, Default)]
+pub struct SymbolKLines {
+    /// K 线缓存：interval -> timestamp -> KLine
+    pub klines: HashMap<u64, HashMap<u64, KLine>>,
+    /// 当前未完成的基础 K 线时间戳
+    pub open_base: Option<u64>,
+    /// 每个周期当前未完成的 K 线时间戳
+    pub open_bars: HashMap<u64, u64>,
+    /// 预热时回放的最大 trade_id，不超过它的实时 Tick 被忽略
+    pub replayed_trade_id: Option<u64>,
+    /// 成交量分布：interval -> timestamp -> VolumeProfile（仅在启用时），
+    /// 只保留未完成的分布，完成后移入 `closed_profiles`
+    pub profiles: HashMap<u64, HashMap<u64, VolumeProfile>>,
+    /// 每个周期当前未完成的成交量分布时间戳
+    pub open_profiles: HashMap<u64, u64>,
+    /// 已完成、尚未取走的成交量分布
+    pub closed_profiles: Vec<VolumeProfile>,
+    /// 上次取走之后有成交更新的周期
+    pub updated_profiles: HashSet<u64>,
+}
 
 /// K 线构建器 - 支持多个时间周期
+///
+/// 最小的秒数周期作为基础周期，由 Tick 直接更新；边界与基础周期对齐的更高周期
+/// （例如 1m 之上的 5m/15m/1h/4h/1d）由基础周期 K 线汇总：基础 K 线完成时合并进
+/// 更高周期，读取时再叠加当前未完成的基础 K 线。无法对齐的周期仍由 Tick 直接更新。
+///
+/// 因此汇总周期在缓存中只包含已完成的基础 K 线，请通过 `get_*` 方法读取完整数据
+///
+/// 状态按品种分片，每个品种一把锁；全局表只在出现新品种时加写锁，
+/// 不同品种的 Tick 可在多个线程上并行处理（见 `KLineWorkers`）
 pub struct KLineBuilder {
-    /// 支持的周期（秒）
-    intervals: Vec<u64>,
-    /// K 线缓存：symbol -> interval -> timestamp -> KLine
-    klines: Arc<RwLock<HashMap<String, HashMap<u64, HashMap<u64, KLine>>>>>,
+    /// 支持的周期
+    intervals: Vec<Interval>,
+    /// 基础周期
+    base: Option<Interval>,
+    /// 品种分片：symbol -> 品种状态
+    symbols: Arc<RwLock<HashMap<String, Arc<Mutex<SymbolKLines>>>>>,
+    /// 成交量分布配置，None 表示不统计
+    profile: Option<ProfileConfig>,
+}
+
+/// 周期的更新方式
+enum Route {
+    /// 由 Tick 直接更新
+    Direct,
+    /// 基础周期
+    Base,
+    /// 由基础周期汇总
+    Rollup,
 }
 
 impl KLineBuilder {
@@ -16,108 +66,382 @@ impl KLineBuilder {
     /// # Arguments
     /// * `intervals` - K 线周期列表（秒） 例如: vec![60, 300, 900, 3600]
     pub fn new(intervals: Vec<u64>) -> Self {
+        KLineBuilder::with_intervals(intervals.into_iter().map(Interval::Seconds).collect())
+    }
+
+    /// 使用日历周期创建 KLineBuilder
+    /// # Arguments
+    /// * `intervals` - 周期列表，例如: vec![Interval::Seconds(60), Interval::Daily(Session::new(8 * 3600, 0)?), Interval::Weekly(Session::utc())]
+    pub fn with_intervals(intervals: Vec<Interval>) -> Self {
+        // 相同标识的周期只保留一个，避免同一根 K 线被重复更新
+        let mut intervals: Vec<Interval> = intervals.into_iter().map(Interval::normalized).collect();
+        let mut seen = std::collections::HashSet::new();
+        intervals.retain(|interval| seen.insert(interval.id()));
+
+        let base = intervals
+            .iter()
+            .filter(|interval| !interval.is_calendar())
+            .min_by_key(|interval| interval.id())
+            .copied();
+
         KLineBuilder {
             intervals,
-            klines: Arc::new(RwLock::new(HashMap::new())),
+            base,
+            symbols: Arc::new(RwLock::new(HashMap::new())),
+            profile: None,
         }
     }
 
+    /// 为每个周期的 K 线同时统计成交量分布（Footprint）
+    pub fn with_profiles(mut self, config: ProfileConfig) -> Self {
+        self.profile = Some(config);
+        self
+    }
+
     /// 标准周期: 1m, 5m, 15m, 1h, 4h, 1d
     pub fn standard() -> Self {
         // 60s, 5m, 15m, 1h, 4h, 1d
         KLineBuilder::new(vec![60, 300, 900, 3600, 14400, 86400])
     }
 
+    fn route(&self, interval: &Interval) -> Route {
+        match &self.base {
+            Some(base) if base == interval => Route::Base,
+            Some(base) if interval.is_aligned_to(base.id()) => Route::Rollup,
+            _ => Route::Direct,
+        }
+    }
+
+    /// 获取品种分片，不存在时创建
+    fn symbol_state(&self, symbol: &str) -> Arc<Mutex<SymbolKLines>> {
+        if let Some(state) = self.symbols.read().get(symbol) {
+            return Arc::clone(state);
+        }
+        let mut symbols = self.symbols.write();
+        Arc::clone(symbols.entry(symbol.to_string()).or_default())
+    }
+
     /// 处理 Tick，更新相应周期的 K 线
-    pub fn process_tick(&self, tick: &Tick) -> Vec<KLine> {
+    ///
+    /// 每个周期返回更新后的当前 K 线（`is_closed = false`）；Tick 进入新周期时，
+    /// 先返回上一根已完成的 K 线（`is_closed = true`）。迟到 Tick 补到已完成的 K 线上，
+    /// 仍以 `is_closed = false` 返回，不会再次完成。
+    ///
+    /// 已在预热中回放过的 Tick（见 `warm_start`）被忽略，返回空
+    pub fn process_tick(&self, tick: &Tick) -> Vec<KLineEvent> {
         let timestamp_sec = tick.timestamp / 1000; // 转换为秒
-        let mut updated_klines = Vec::new();
-
-        let mut klines = self.klines.write();
-        
-        for &interval in &self.intervals {
-            let kline_ts = (timestamp_sec / interval) * interval;
-            
-            let symbol_klines = klines
-                .entry(tick.symbol.clone())
-                .or_insert_with(HashMap::new);
-            
-            let interval_klines = symbol_klines
-                .entry(interval)
-                .or_insert_with(HashMap::new);
-
-            let kline = interval_klines
-                .entry(kline_ts)
-                .or_insert_with(|| {
-                    KLine::new(
-                        tick.symbol.clone(),
-                        kline_ts,
-                        interval,
-                        tick.price,
-                    )
-                });
+        let mut updated_klines = Vec::with_capacity(self.intervals.len());
+
+        let state = self.symbol_state(&tick.symbol);
+        let mut state = state.lock();
+        if state.replayed_trade_id.is_some_and(|id| tick.trade_id <= id) {
+            return updated_klines;
+        }
+        let SymbolKLines { klines: symbol_klines, open_base, open_bars, .. } = &mut *state;
+
+        // 基础周期：完成的基础 K 线合并进汇总周期，迟到的 Tick 直接补到汇总周期
+        let mut base_bar = None;
+        if let Some(base) = &self.base {
+            let base_ts = base.bucket_start(timestamp_sec);
+
+            match *open_base {
+                Some(open_ts) if base_ts > open_ts => {
+                    let closed = symbol_klines
+                        .get(&base.id())
+                        .and_then(|bars| bars.get(&open_ts))
+                        .cloned();
+                    if let Some(closed) = closed {
+                        for interval in self.rollup_intervals() {
+                            fold_into(symbol_klines, interval, &closed);
+                        }
+                    }
+                    *open_base = Some(base_ts);
+                }
+                Some(open_ts) if base_ts < open_ts => {
+                    for interval in self.rollup_intervals() {
+                        update_bar(symbol_klines, interval, tick, timestamp_sec);
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    *open_base = Some(base_ts);
+                }
+            }
+
+            base_bar = Some(update_bar(symbol_klines, base, tick, timestamp_sec));
+        }
+
+        let open_bar = open_base.and_then(|open_ts| {
+            self.base
+                .as_ref()
+                .and_then(|base| symbol_klines.get(&base.id()))
+                .and_then(|bars| bars.get(&open_ts))
+                .cloned()
+        });
+
+        for interval in &self.intervals {
+            // 进入新周期时上一根 K 线完成，汇总周期此时已合并了刚完成的基础 K 线
+            let bucket = interval.bucket_start(timestamp_sec);
+            match open_bars.get(&interval.id()).copied() {
+                Some(open_ts) if bucket > open_ts => {
+                    let closed = symbol_klines
+                        .get(&interval.id())
+                        .and_then(|bars| bars.get(&open_ts))
+                        .cloned();
+                    if let Some(closed) = closed {
+                        updated_klines.push(KLineEvent { kline: closed, is_closed: true });
+                    }
+                    open_bars.insert(interval.id(), bucket);
+                }
+                Some(_) => {}
+                None => {
+                    open_bars.insert(interval.id(), bucket);
+                }
+            }
 
-            kline.update(tick);
-            updated_klines.push(kline.clone());
+            let kline = match self.route(interval) {
+                Route::Direct => update_bar(symbol_klines, interval, tick, timestamp_sec),
+                Route::Base => base_bar.clone().expect("base interval configured"),
+                Route::Rollup => {
+                    let closed = symbol_klines
+                        .get(&interval.id())
+                        .and_then(|bars| bars.get(&bucket));
+                    merge_open(interval, bucket, closed, open_bar.as_ref())
+                        .expect("bucket contains the current tick")
+                }
+            };
+            updated_klines.push(KLineEvent { kline, is_closed: false });
+        }
+
+        if let Some(config) = &self.profile {
+            for interval in &self.intervals {
+                update_profile(&mut state, interval, config, tick, timestamp_sec);
+            }
         }
 
         updated_klines
     }
 
+    /// Tick 是否已在预热中回放过（trade_id 不超过回放的最大值）
+    ///
+    /// 与 K 线一同由实时 Tick 驱动的其它处理（信息驱动 K 线、滚动行情、落盘）应先检查，
+    /// 避免重启后重复计数
+    pub fn is_replayed(&self, tick: &Tick) -> bool {
+        self.symbols
+            .read()
+            .get(&tick.symbol)
+            .and_then(|state| state.lock().replayed_trade_id)
+            .is_some_and(|id| tick.trade_id <= id)
+    }
+
+    /// 从存储回放指定品种当前各周期内的 Tick，恢复重启前的 K 线状态
+    ///
+    /// 回放范围从 `now`（毫秒）所在的最长周期开始时间起。应在处理实时 Tick 之前调用；
+    /// 之后 trade_id 不超过已回放最大值的实时 Tick 会被忽略，重复调用不会重复计数
+    pub fn warm_start(&self, storage: &dyn TickStore, symbol: &str, now: u64) -> Result<WarmStartStats> {
+        let now_sec = now / 1000;
+        let since = self
+            .intervals
+            .iter()
+            .map(|interval| interval.bucket_start(now_sec))
+            .min()
+            .unwrap_or(now_sec);
+
+        let ticks = storage.read_ticks_since(symbol, since * 1000)?;
+        let mut stats = WarmStartStats { ticks_replayed: 0, last_trade_id: None };
+        for tick in &ticks {
+            if !self.process_tick(tick).is_empty() {
+                stats.ticks_replayed += 1;
+            }
+            stats.last_trade_id = stats.last_trade_id.max(Some(tick.trade_id));
+        }
+
+        if let Some(last_trade_id) = stats.last_trade_id {
+            let state = self.symbol_state(symbol);
+            let mut state = state.lock();
+            state.replayed_trade_id = state.replayed_trade_id.max(Some(last_trade_id));
+        }
+
+        Ok(stats)
+    }
+
+    fn rollup_intervals(&self) -> impl Iterator<Item = &Interval> {
+        self.intervals
+            .iter()
+            .filter(|interval| matches!(self.route(interval), Route::Rollup))
+    }
+
+    /// 汇总周期及该品种当前未完成的基础 K 线（仅当 `interval_id` 为汇总周期）
+    fn open_overlay<'a>(
+        &'a self,
+        state: &'a SymbolKLines,
+        interval_id: u64,
+    ) -> Option<(&'a Interval, &'a KLine)> {
+        let interval = self
+            .rollup_intervals()
+            .find(|interval| interval.id() == interval_id)?;
+        let open_ts = state.open_base?;
+        let open_bar = self
+            .base
+            .as_ref()
+            .and_then(|base| state.klines.get(&base.id()))
+            .and_then(|bars| bars.get(&open_ts))?;
+        Some((interval, open_bar))
+    }
+
     /// 获取指定品种和周期的最新 K 线
     pub fn get_latest_kline(&self, symbol: &str, interval: u64) -> Option<KLine> {
-        let klines = self.klines.read();
-        klines
-            .get(symbol)
-            .and_then(|symbol_klines| symbol_klines.get(&interval))
-            .and_then(|interval_klines| {
-                // 返回最新的 K 线（最大时间戳）
-                interval_klines.iter().last().map(|(_, kline)| kline.clone())
-            })
+        let state = self.get_symbol_lock(symbol)?;
+        let state = state.lock();
+        let interval_klines = state.klines.get(&interval);
+
+        // 返回最新的 K 线（最大时间戳）
+        let latest_ts = interval_klines.and_then(|bars| bars.keys().max().copied());
+        let closed = |ts: u64| interval_klines.and_then(|bars| bars.get(&ts));
+
+        if let Some((rollup, open_bar)) = self.open_overlay(&state, interval) {
+            let bucket = rollup.bucket_start(open_bar.timestamp);
+            if latest_ts.is_none_or(|ts| bucket >= ts) {
+                return merge_open(rollup, bucket, closed(bucket), Some(open_bar));
+            }
+        }
+
+        latest_ts.and_then(closed).cloned()
     }
 
     /// 获取指定品种、周期的所有 K 线
     pub fn get_klines(&self, symbol: &str, interval: u64) -> Vec<KLine> {
-        let klines = self.klines.read();
-        klines
-            .get(symbol)
-            .and_then(|symbol_klines| symbol_klines.get(&interval))
-            .map(|interval_klines| {
-                let mut bars: Vec<_> = interval_klines.values().cloned().collect();
-                bars.sort_by_key(|k| k.timestamp);
-                bars
-            })
-            .unwrap_or_default()
+        let state = match self.get_symbol_lock(symbol) {
+            Some(state) => state,
+            None => return Vec::new(),
+        };
+        let state = state.lock();
+
+        let mut bars: HashMap<u64, KLine> = state
+            .klines
+            .get(&interval)
+            .cloned()
+            .unwrap_or_default();
+
+        // 汇总周期叠加未完成的基础 K 线
+        if let Some((rollup, open_bar)) = self.open_overlay(&state, interval) {
+            let bucket = rollup.bucket_start(open_bar.timestamp);
+            if let Some(merged) = merge_open(rollup, bucket, bars.get(&bucket), Some(open_bar)) {
+                bars.insert(bucket, merged);
+            }
+        }
+
+        let mut bars: Vec<_> = bars.into_values().collect();
+        bars.sort_by_key(|k| k.timestamp);
+        bars
     }
 
     /// 获取所有品种和周期的 K 线统计
     pub fn get_stats(&self) -> KLineStats {
-        let klines = self.klines.read();
+        let states: Vec<_> = self.symbols.read().values().cloned().collect();
         let mut total_klines = 0;
-        let mut symbols = std::collections::HashSet::new();
-        
-        for (symbol, symbol_klines) in klines.iter() {
-            symbols.insert(symbol.clone());
-            for (_, interval_klines) in symbol_klines.iter() {
+
+        for state in &states {
+            let state = state.lock();
+            for (interval, interval_klines) in state.klines.iter() {
                 total_klines += interval_klines.len();
+
+                // 尚无已完成基础 K 线的汇总周期
+                if let Some((rollup, open_bar)) = self.open_overlay(&state, *interval) {
+                    let bucket = rollup.bucket_start(open_bar.timestamp);
+                    if !interval_klines.contains_key(&bucket) {
+                        total_klines += 1;
+                    }
+                }
+            }
+
+            for rollup in self.rollup_intervals() {
+                if !state.klines.contains_key(&rollup.id())
+                    && self.open_overlay(&state, rollup.id()).is_some()
+                {
+                    total_klines += 1;
+                }
             }
         }
 
         KLineStats {
-            total_symbols: symbols.len(),
+            total_symbols: states.len(),
             total_klines,
-            intervals: self.intervals.clone(),
+            intervals: self.intervals.iter().map(Interval::id).collect(),
+        }
+    }
+
+    /// 获取指定品种、周期、开始时间的成交量分布
+    pub fn get_profile(&self, symbol: &str, interval: u64, timestamp: u64) -> Option<VolumeProfile> {
+        let config = self.profile.as_ref()?;
+        let state = self.get_symbol_lock(symbol)?;
+        let state = state.lock();
+        let mut profile = state.profiles.get(&interval)?.get(&timestamp)?.clone();
+        profile.refresh(config.value_area);
+        Some(profile)
+    }
+
+    /// 获取指定品种、周期当前的成交量分布
+    pub fn get_latest_profile(&self, symbol: &str, interval: u64) -> Option<VolumeProfile> {
+        let timestamp = {
+            let state = self.get_symbol_lock(symbol)?;
+            let state = state.lock();
+            *state.open_profiles.get(&interval)?
+        };
+        self.get_profile(symbol, interval, timestamp)
+    }
+
+    /// 取走上次调用之后有成交更新的未完成成交量分布，用于广播
+    pub fn drain_updated_profiles(&self) -> Vec<VolumeProfile> {
+        let Some(config) = &self.profile else {
+            return Vec::new();
+        };
+        let states: Vec<_> = self.symbols.read().values().cloned().collect();
+        let mut updated = Vec::new();
+        for state in states {
+            let mut state = state.lock();
+            for interval in std::mem::take(&mut state.updated_profiles) {
+                let profile = state
+                    .open_profiles
+                    .get(&interval)
+                    .and_then(|timestamp| state.profiles.get(&interval)?.get(timestamp));
+                if let Some(profile) = profile {
+                    let mut profile = profile.clone();
+                    profile.refresh(config.value_area);
+                    updated.push(profile);
+                }
+            }
         }
+        updated
+    }
+
+    /// 取走所有品种已完成的成交量分布，用于存储和广播
+    pub fn drain_closed_profiles(&self) -> Vec<VolumeProfile> {
+        let states: Vec<_> = self.symbols.read().values().cloned().collect();
+        states
+            .iter()
+            .flat_map(|state| std::mem::take(&mut state.lock().closed_profiles))
+            .collect()
+    }
+
+    /// 获取配置的周期
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
     }
 
     /// 清空所有 K 线数据
     pub fn clear(&self) {
-        self.klines.write().clear();
+        self.symbols.write().clear();
     }
 
-    /// 获取数据的写入权限（用于外部修改）
-    pub fn get_klines_lock(&self) -> Arc<RwLock<HashMap<String, HashMap<u64, HashMap<u64, KLine>>>>> {
-        Arc::clone(&self.klines)
+    /// 获取品种分片表的写入权限（用于外部修改），每个品种的状态在各自的锁内
+    pub fn get_klines_lock(&self) -> Arc<RwLock<HashMap<String, Arc<Mutex<SymbolKLines>>>>> {
+        Arc::clone(&self.symbols)
+    }
+
+    /// 获取指定品种的分片锁（用于外部修改）
+    pub fn get_symbol_lock(&self, symbol: &str) -> Option<Arc<Mutex<SymbolKLines>>> {
+        self.symbols.read().get(symbol).cloned()
     }
 }
 
@@ -125,9 +449,263 @@ impl Clone for KLineBuilder {
     fn clone(&self) -> Self {
         KLineBuilder {
             intervals: self.intervals.clone(),
-            klines: Arc::clone(&self.klines),
+            base: self.base,
+            symbols: Arc::clone(&self.symbols),
+            profile: self.profile.clone(),
+        }
+    }
+}
+
+/// 每个工作线程的默认队列容量（Tick 数）
+pub const DEFAULT_WORKER_QUEUE: usize = 10_000;
+
+/// 按品种分片的 K 线工作线程
+///
+/// 同一品种的 Tick 总是路由到同一个线程（单写者），线程通过 `ThreadBuilder` 绑定到
+/// 不同 CPU 核心。更新后的 K 线交给回调处理，例如分发给订阅者。每个线程的队列有界，
+/// 队列满时 `dispatch` 阻塞调用方，形成背压。
+///
+/// 这是供多品种部署使用的库设施：`mdi-cli` 只订阅单个品种，仍在一个处理任务中直接调用
+/// `KLineBuilder::process_tick`
+pub struct KLineWorkers {
+    builder: KLineBuilder,
+    senders: Vec<Sender<Tick>>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl KLineWorkers {
+    /// 启动工作线程
+    /// # Arguments
+    /// * `builder` - 共享的 K 线构建器
+    /// * `num_workers` - 线程数，按 `CpuAffinity::get_thread_affinity_config` 绑定核心
+    /// * `on_klines` - 每个 Tick 处理完成后的回调，参数为 Tick 和 K 线事件（见 `KLineBuilder::process_tick`）
+    pub fn spawn<F>(builder: KLineBuilder, num_workers: usize, on_klines: F) -> Self
+    where
+        F: Fn(&Tick, Vec<KLineEvent>) + Send + Sync + 'static,
+    {
+        KLineWorkers::spawn_with_queue(builder, num_workers, DEFAULT_WORKER_QUEUE, on_klines)
+    }
+
+    /// 启动工作线程，指定每个线程的队列容量
+    pub fn spawn_with_queue<F>(builder: KLineBuilder, num_workers: usize, queue_capacity: usize, on_klines: F) -> Self
+    where
+        F: Fn(&Tick, Vec<KLineEvent>) + Send + Sync + 'static,
+    {
+        let on_klines = Arc::new(on_klines);
+        let mut senders = Vec::with_capacity(num_workers);
+        let mut handles = Vec::with_capacity(num_workers);
+
+        for (worker_id, cpu_id) in CpuAffinity::get_thread_affinity_config(num_workers.max(1))
+            .into_iter()
+            .enumerate()
+        {
+            let (tx, rx) = channel::bounded::<Tick>(queue_capacity.max(1));
+            let builder = builder.clone();
+            let on_klines = Arc::clone(&on_klines);
+
+            let handle = ThreadBuilder::new()
+                .cpu(cpu_id)
+                .name(format!("kline-worker-{}", worker_id))
+                .spawn(move || {
+                    for tick in rx {
+                        let klines = builder.process_tick(&tick);
+                        on_klines(&tick, klines);
+                    }
+                });
+
+            senders.push(tx);
+            handles.push(handle);
+        }
+
+        KLineWorkers { builder, senders, handles }
+    }
+
+    /// 品种所属的线程编号
+    pub fn shard_of(&self, symbol: &str) -> usize {
+        let mut hasher = DefaultHasher::new();
+        symbol.hash(&mut hasher);
+        (hasher.finish() % self.senders.len() as u64) as usize
+    }
+
+    /// 将 Tick 路由到对应品种的线程，队列满时阻塞
+    pub fn dispatch(&self, tick: Tick) -> Result<()> {
+        let shard = self.shard_of(&tick.symbol);
+        self.senders[shard]
+            .send(tick)
+            .map_err(|_| MdiError::QueueError(format!("KLine worker {} stopped", shard)))
+    }
+
+    /// 线程数
+    pub fn num_workers(&self) -> usize {
+        self.senders.len()
+    }
+
+    /// 共享的 K 线构建器，用于查询
+    pub fn builder(&self) -> &KLineBuilder {
+        &self.builder
+    }
+
+    /// 处理完已路由的 Tick 后停止所有线程，有线程 panic 时返回第一个错误
+    pub fn shutdown(self) -> Result<()> {
+        drop(self.senders);
+        let mut result = Ok(());
+        for (worker_id, handle) in self.handles.into_iter().enumerate() {
+            if let Err(panic) = handle.join() {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                tracing::error!("KLine worker {} panicked: {}", worker_id, message);
+                if result.is_ok() {
+                    result = Err(MdiError::Other(format!("KLine worker {} panicked: {}", worker_id, message)));
+                }
+            }
+        }
+        result
+    }
+}
+
+/// 用 Tick 直接更新指定周期的 K 线，返回更新后的副本
+fn update_bar(
+    symbol_klines: &mut HashMap<u64, HashMap<u64, KLine>>,
+    interval: &Interval,
+    tick: &Tick,
+    timestamp_sec: u64,
+) -> KLine {
+    let kline_ts = interval.bucket_start(timestamp_sec);
+    let interval_id = interval.id();
+
+    let kline = symbol_klines
+        .entry(interval_id)
+        .or_default()
+        .entry(kline_ts)
+        .or_insert_with(|| {
+            let mut kline = KLine::new(
+                tick.symbol.clone(),
+                kline_ts,
+                interval_id,
+                tick.price,
+            );
+            kline.close_time = interval.bucket_end(kline_ts);
+            kline
+        });
+
+    kline.update(tick);
+    kline.clone()
+}
+
+/// 用 Tick 更新指定周期的成交量分布，进入新周期时上一周期的分布完成
+fn update_profile(
+    state: &mut SymbolKLines,
+    interval: &Interval,
+    config: &ProfileConfig,
+    tick: &Tick,
+    timestamp_sec: u64,
+) {
+    let interval_id = interval.id();
+    let bucket = interval.bucket_start(timestamp_sec);
+
+    match state.open_profiles.get(&interval_id).copied() {
+        Some(open_ts) if bucket > open_ts => {
+            let closed = state
+                .profiles
+                .get_mut(&interval_id)
+                .and_then(|profiles| profiles.remove(&open_ts));
+            if let Some(mut closed) = closed {
+                closed.refresh(config.value_area);
+                state.closed_profiles.push(closed);
+            }
+            state.open_profiles.insert(interval_id, bucket);
+        }
+        // 已完成的分布已经取走存储，迟到的成交不再计入
+        Some(open_ts) if bucket < open_ts => return,
+        Some(_) => {}
+        None => {
+            state.open_profiles.insert(interval_id, bucket);
         }
     }
+
+    state.updated_profiles.insert(interval_id);
+    state
+        .profiles
+        .entry(interval_id)
+        .or_default()
+        .entry(bucket)
+        .or_insert_with(|| {
+            VolumeProfile::new(tick.symbol.clone(), interval_id, bucket, config.bucket_for(&tick.symbol))
+        })
+        .update(tick);
+}
+
+/// 将一根已完成的低周期 K 线合并进高周期缓存
+fn fold_into(
+    symbol_klines: &mut HashMap<u64, HashMap<u64, KLine>>,
+    interval: &Interval,
+    bar: &KLine,
+) {
+    let bucket = interval.bucket_start(bar.timestamp);
+    symbol_klines
+        .entry(interval.id())
+        .or_default()
+        .entry(bucket)
+        .and_modify(|kline| kline.merge(bar))
+        .or_insert_with(|| rollup_bar(interval, bucket, bar));
+}
+
+/// 已完成部分叠加未完成的基础 K 线（若落在同一周期内）
+fn merge_open(
+    interval: &Interval,
+    bucket: u64,
+    closed: Option<&KLine>,
+    open_bar: Option<&KLine>,
+) -> Option<KLine> {
+    let open_bar = open_bar.filter(|bar| interval.bucket_start(bar.timestamp) == bucket);
+    match (closed, open_bar) {
+        (Some(closed), Some(open_bar)) => {
+            let mut kline = closed.clone();
+            kline.merge(open_bar);
+            Some(kline)
+        }
+        (Some(closed), None) => Some(closed.clone()),
+        (None, Some(open_bar)) => Some(rollup_bar(interval, bucket, open_bar)),
+        (None, None) => None,
+    }
+}
+
+/// 以低周期 K 线为第一根，创建高周期 K 线
+fn rollup_bar(interval: &Interval, bucket: u64, first: &KLine) -> KLine {
+    let mut kline = KLine::new(first.symbol.clone(), bucket, interval.id(), first.open);
+    kline.close_time = interval.bucket_end(bucket);
+    kline.merge(first);
+    kline
+}
+
+/// 由低周期 K 线汇总出任意对齐的高周期 K 线，例如从存储的 1m K 线重建 1h/1d
+///
+/// 输入无需排序；`interval` 的边界必须与输入周期对齐（见 `Interval::is_aligned_to`）
+pub fn rollup(bars: &[KLine], interval: &Interval) -> Vec<KLine> {
+    let mut sorted: Vec<&KLine> = bars.iter().collect();
+    sorted.sort_by_key(|bar| bar.timestamp);
+
+    let mut result: Vec<KLine> = Vec::new();
+    for bar in sorted {
+        let bucket = interval.bucket_start(bar.timestamp);
+        match result.last_mut() {
+            Some(last) if last.timestamp == bucket && last.symbol == bar.symbol => last.merge(bar),
+            _ => result.push(rollup_bar(interval, bucket, bar)),
+        }
+    }
+    result
+}
+
+/// 预热结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarmStartStats {
+    /// 回放的 Tick 数
+    pub ticks_replayed: usize,
+    /// 存储中最新的 trade_id
+    pub last_trade_id: Option<u64>,
 }
 
 /// K 线统计信息
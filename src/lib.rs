@@ -1,17 +1,49 @@
 pub mod models;
+pub mod interval;
 pub mod affinity;
 pub mod queue;
 pub mod receiver;
+pub mod dedup;
 pub mod kline;
+pub mod bars;
+pub mod profile;
+pub mod indicators;
+pub mod ticker;
+pub mod codec;
+pub mod retention;
+pub mod stats;
+pub mod verify;
 pub mod storage;
+pub mod store;
+pub mod memstore;
+pub mod segment;
+pub mod archive;
+pub mod writer;
+pub mod export;
+pub mod import;
 pub mod distributor;
 
-pub use models::{Tick, KLine};
+pub use models::{Tick, KLine, SymbolStats};
+pub use interval::{Interval, Session};
 pub use queue::RingBuffer;
 pub use receiver::TickReceiver;
-pub use kline::KLineBuilder;
-pub use storage::TickStorage;
-pub use distributor::Distributor;
+pub use dedup::TradeDedup;
+pub use kline::{KLineBuilder, KLineWorkers};
+pub use bars::{BarBuilder, BarSpec, HeikinAshiBuilder};
+pub use profile::{PriceLevel, ProfileConfig, VolumeProfile};
+pub use indicators::{IndicatorEngine, IndicatorSpec, IndicatorValue};
+pub use ticker::TickerBuilder;
+pub use codec::ValueFormat;
+pub use retention::{PurgeReport, RetentionPolicy};
+pub use stats::StorageStats;
+pub use verify::{VerifyOptions, VerifyReport};
+pub use storage::{StorageConfig, TickStorage};
+pub use store::{StoreConfig, TickStore};
+pub use memstore::MemoryStore;
+pub use segment::SegmentStore;
+pub use archive::{TickArchive, TieredStorage};
+pub use writer::{StorageWriter, WriterConfig};
+pub use distributor::{Distributor, SubscriptionPattern};
 pub use affinity::{CpuAffinity, ThreadBuilder};
 
 /// 错误类型定义
@@ -20,6 +52,8 @@ pub enum MdiError {
     ReceiverError(String),
     QueueError(String),
     StorageError(String),
+    ExportError(String),
+    ImportError(String),
     SerializationError(serde_json::Error),
     Other(String),
 }
@@ -36,6 +70,8 @@ impl std::fmt::Display for MdiError {
             MdiError::ReceiverError(e) => write!(f, "Receiver error: {}", e),
             MdiError::QueueError(e) => write!(f, "Queue error: {}", e),
             MdiError::StorageError(e) => write!(f, "Storage error: {}", e),
+            MdiError::ExportError(e) => write!(f, "Export error: {}", e),
+            MdiError::ImportError(e) => write!(f, "Import error: {}", e),
             MdiError::SerializationError(e) => write!(f, "Serialization error: {}", e),
             MdiError::Other(e) => write!(f, "Error: {}", e),
         }
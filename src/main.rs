@@ -1,10 +1,12 @@
 use mdi::{
-    TickReceiver, KLineBuilder, Distributor, TickStorage, CpuAffinity, Result as MdiResult,
+    TickReceiver, TradeDedup, KLineBuilder, BarBuilder, BarSpec, HeikinAshiBuilder, IndicatorEngine, IndicatorSpec, ProfileConfig, TickerBuilder, Distributor, TickStorage, TickStore, StoreConfig, TickArchive, TieredStorage, RetentionPolicy, VerifyOptions, StorageWriter, WriterConfig, CpuAffinity,
+    Result as MdiResult,
 };
+use mdi::export::{export_klines, export_ticks, ExportFormat, ExportOptions};
+use mdi::import::Importer;
 use tokio::task::JoinHandle;
 use std::sync::Arc;
 use std::time::Duration;
-use tracing_subscriber;
 
 #[tokio::main]
 async fn main() -> MdiResult<()> {
@@ -21,6 +23,89 @@ async fn main() -> MdiResult<()> {
     let symbol = "BTCUSDT";
     let buffer_capacity = 100000;
     let db_path = "./data/mdi.db";
+    let backup_dir = "./data/backup";
+    let archive_dir = "./data/archive";
+
+    // 子命令
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(command) = args.get(1) {
+        let arg = |index: usize| args.get(index).map(String::as_str);
+        let usage = || {
+            mdi::MdiError::Other(format!(
+                "Invalid command: {}\nusage:\n  \
+                 mdi-cli migrate [db_path]\n  \
+                 mdi-cli checkpoint <db_path> <checkpoint_dir>\n  \
+                 mdi-cli backup [db_path] [backup_dir] [keep] [archive_dir]\n  \
+                 mdi-cli backups [backup_dir]\n  \
+                 mdi-cli restore <target_path> [backup_dir] [backup_id|latest] [archive_target]\n  \
+                 mdi-cli archive [db_path] [archive_dir]\n  \
+                 mdi-cli verify [db_path] [--repair]\n  \
+                 mdi-cli stats [db_path] [--keys]\n  \
+                 mdi-cli export <db_path> <output> [--format csv|parquet] [--symbols A,B] \
+                 [--interval SECS] [--from MS] [--to MS] [--row-group ROWS] [--archive DIR]\n  \
+                 mdi-cli import <db_path> <file_or_dir> [--rebuild 1m,5m,1h]\n\
+                 checkpoint and backup open the database as a secondary instance and can run while ingestion is running;\n\
+                 backup also snapshots the tick archive, restore brings it back when archive_target is given",
+                command
+            ))
+        };
+        return match command.as_str() {
+            "migrate" => migrate(arg(2).unwrap_or(db_path)),
+            "checkpoint" => match (arg(2), arg(3)) {
+                (Some(db_path), Some(checkpoint_dir)) => checkpoint(db_path, checkpoint_dir),
+                _ => Err(usage()),
+            },
+            "backup" => {
+                let keep = match arg(4).map(str::parse) {
+                    None => 7,
+                    Some(Ok(keep)) => keep,
+                    Some(Err(_)) => return Err(usage()),
+                };
+                backup(arg(2).unwrap_or(db_path), arg(3).unwrap_or(backup_dir), keep, arg(5).unwrap_or(archive_dir))
+            }
+            "backups" => list_backups(arg(2).unwrap_or(backup_dir)),
+            "restore" => {
+                let backup_id = match arg(4) {
+                    None | Some("latest") => None,
+                    Some(id) => match id.parse() {
+                        Ok(backup_id) => Some(backup_id),
+                        Err(_) => return Err(usage()),
+                    },
+                };
+                match arg(2) {
+                    Some(target) => restore(arg(3).unwrap_or(backup_dir), target, backup_id, arg(5)),
+                    None => Err(usage()),
+                }
+            }
+            "verify" => match (arg(2), arg(3)) {
+                (None, None) => verify(db_path, false),
+                (Some("--repair"), None) => verify(db_path, true),
+                (Some(db_path), None) => verify(db_path, false),
+                (Some(db_path), Some("--repair")) => verify(db_path, true),
+                _ => Err(usage()),
+            },
+            "stats" => match (arg(2), arg(3)) {
+                (None, None) => stats(db_path, false),
+                (Some("--keys"), None) => stats(db_path, true),
+                (Some(db_path), None) => stats(db_path, false),
+                (Some(db_path), Some("--keys")) => stats(db_path, true),
+                _ => Err(usage()),
+            },
+            "archive" => archive(arg(2).unwrap_or(db_path), arg(3).unwrap_or(archive_dir)),
+            "import" => match (arg(2), arg(3), arg(4), arg(5)) {
+                (Some(db_path), Some(path), None, None) => import(db_path, path, None),
+                (Some(db_path), Some(path), Some("--rebuild"), Some(intervals)) => {
+                    import(db_path, path, Some(intervals))
+                }
+                _ => Err(usage()),
+            },
+            "export" => match (arg(2), arg(3)) {
+                (Some(db_path), Some(output)) => export(db_path, output, &args[4..]),
+                _ => Err(usage()),
+            },
+            _ => Err(usage()),
+        };
+    }
 
     // 1. 创建核心组件
     tracing::info!("Initializing components...");
@@ -30,12 +115,54 @@ async fn main() -> MdiResult<()> {
         buffer_capacity,
     ));
     
-    let kline_builder = Arc::new(KLineBuilder::standard());
+    let dedup = Arc::new(TradeDedup::default());
+    // 成交量分布按 10 USDT 分档
+    let kline_builder = Arc::new(KLineBuilder::standard().with_profiles(ProfileConfig::new(10.0)));
+    // 信息驱动 K 线: 100 笔 / 10 BTC / 100 万 USDT / 价差 50 / 砖块 100
+    let bar_builder = Arc::new(BarBuilder::new(vec![
+        BarSpec::Ticks(100),
+        BarSpec::Volume(10.0),
+        BarSpec::Notional(1_000_000.0),
+        BarSpec::Range(50.0),
+        BarSpec::Renko { box_size: 100.0, reversal: 2 },
+    ])?);
+    let heikin_ashi = Arc::new(HeikinAshiBuilder::new());
+    let indicators = Arc::new(IndicatorEngine::new(vec![
+        IndicatorSpec::Sma(20),
+        IndicatorSpec::Ema(20),
+        IndicatorSpec::Rsi(14),
+        IndicatorSpec::Macd { fast: 12, slow: 26, signal: 9 },
+        IndicatorSpec::Bollinger { period: 20, k: 2.0 },
+        IndicatorSpec::Atr(14),
+    ]));
+    let ticker = Arc::new(TickerBuilder::daily());
     let distributor = Arc::new(Distributor::new(1000));
-    let storage = Arc::new(TickStorage::open(db_path)?);
+    // 存储后端，默认 RocksDB + 按天归档，MDI_STORE 可选 rocksdb:PATH / segment:DIR / memory
+    let (storage, tiered): (Arc<dyn TickStore>, Option<TieredStorage>) = match std::env::var("MDI_STORE") {
+        Ok(value) => (value.parse::<StoreConfig>()?.open()?, None),
+        Err(_) => {
+            let tiered = TieredStorage::new(TickStorage::open(db_path)?, TickArchive::open(archive_dir)?);
+            (Arc::new(tiered.clone()), Some(tiered))
+        }
+    };
     
     let tick_buffer = receiver.buffer();
 
+    // 用已存储的 Tick 恢复当前周期的 K 线
+    let now = chrono::Utc::now().timestamp_millis() as u64;
+    let warm = kline_builder.warm_start(storage.as_ref(), symbol, now)?;
+    tracing::info!(
+        "Warm start: replayed {} ticks (last trade id {:?})",
+        warm.ticks_replayed,
+        warm.last_trade_id
+    );
+
+    // 独立线程批量写入存储，队列满时丢弃并计数，不阻塞处理任务
+    let writer = Arc::new(StorageWriter::spawn(
+        Arc::clone(&storage),
+        WriterConfig::default().overflow(mdi::writer::OverflowPolicy::Drop),
+    ));
+
     // 2. 启动 Binance WebSocket 接收器（后台任务）
     tracing::info!("Starting Binance WebSocket receiver for {}...", symbol);
     
@@ -47,38 +174,79 @@ async fn main() -> MdiResult<()> {
     // 3. 启动 K 线处理任务
     tracing::info!("Starting KLine processor...");
     
+    let dedup_clone = Arc::clone(&dedup);
     let kline_builder_clone = Arc::clone(&kline_builder);
+    let bar_builder_clone = Arc::clone(&bar_builder);
+    let heikin_ashi_clone = Arc::clone(&heikin_ashi);
+    let indicators_clone = Arc::clone(&indicators);
+    let ticker_clone = Arc::clone(&ticker);
     let distributor_clone = Arc::clone(&distributor);
-    let storage_clone = Arc::clone(&storage);
+    let writer_clone = Arc::clone(&writer);
     let buffer_clone = tick_buffer.clone();
     
-    let processor_handle: JoinHandle<()> = tokio::spawn(async move {
-        let mut last_storage_time = std::time::Instant::now();
-        let storage_interval = Duration::from_secs(60); // 每 60 秒写入一次
-        let mut tick_batch = Vec::with_capacity(1000);
-        
+    let mut processor_handle: JoinHandle<()> = tokio::spawn(async move {
         loop {
             // 批量处理 tick（每次最多 1000 个）
             while let Some(tick) = buffer_clone.pop() {
-                // 处理 K 线
-                let klines = kline_builder_clone.process_tick(&tick);
+                // 丢弃重连、补数据造成的重复成交
+                if !dedup_clone.check(&tick) {
+                    continue;
+                }
+                // 预热时已回放并存储过的成交，所有处理都跳过
+                if kline_builder_clone.is_replayed(&tick) {
+                    continue;
+                }
+
+                ticker_clone.process_tick(&tick);
+
+                // 处理 K 线，已收线的落盘
+                let events = kline_builder_clone.process_tick(&tick);
                 
                 // 分发 K 线
-                for kline in klines {
-                    let is_closed = false; // 简化处理
-                    distributor_clone.broadcast_kline(kline, is_closed);
+                for event in events {
+                    for ha in heikin_ashi_clone.process_kline(&event.kline, event.is_closed) {
+                        distributor_clone.broadcast_kline(ha.kline, ha.is_closed);
+                    }
+                    for value in indicators_clone.process_kline(&event.kline, event.is_closed) {
+                        distributor_clone.broadcast_indicator(value);
+                    }
+                    if event.is_closed {
+                        let _ = writer_clone.write_kline(event.kline.clone());
+                    }
+                    distributor_clone.broadcast_kline(event.kline, event.is_closed);
+                }
+
+                // 信息驱动 K 线，已收线的落盘
+                match bar_builder_clone.process_tick(&tick) {
+                    Ok(events) => {
+                        for event in events {
+                            if event.is_closed {
+                                let _ = writer_clone.write_kline(event.kline.clone());
+                            }
+                            distributor_clone.broadcast_kline(event.kline, event.is_closed);
+                        }
+                    }
+                    Err(e) => tracing::warn!("Skipping bars for trade: {}", e),
                 }
                 
-                tick_batch.push(tick);
+                let _ = writer_clone.write_tick(tick);
             }
 
-            // 批量写入存储
-            if !tick_batch.is_empty() && last_storage_time.elapsed() > storage_interval {
-                if let Err(e) = storage_clone.write_ticks(&tick_batch) {
-                    tracing::warn!("Failed to write ticks to storage: {}", e);
-                }
-                tick_batch.clear();
-                last_storage_time = std::time::Instant::now();
+            // 滚动行情按轮询节奏推送
+            ticker_clone.expire(chrono::Utc::now().timestamp_millis() as u64);
+            if let Some(stats) = ticker_clone.get_ticker(symbol) {
+                distributor_clone.broadcast_ticker(stats);
+            }
+
+            // 本轮有成交更新的成交量分布按轮询节奏推送
+            for profile in kline_builder_clone.drain_updated_profiles() {
+                distributor_clone.broadcast_profile(profile, false);
+            }
+
+            // 已完成的成交量分布落盘
+            for profile in kline_builder_clone.drain_closed_profiles() {
+                let _ = writer_clone.write_profile(profile.clone());
+                distributor_clone.broadcast_profile(profile, true);
             }
 
             // 短暂休眠，避免 busy loop
@@ -104,21 +272,18 @@ async fn main() -> MdiResult<()> {
         loop {
             // 异步等待任何一个 K 线更新
             for (interval, rx) in receivers.iter_mut() {
-                match rx.try_recv() {
-                    Ok(event) => {
-                        count += 1;
-                        if count % 100 == 0 {
-                            tracing::info!(
-                                "KLine {}s: time={}, price={:.2}, volume={:.8}, trades={}",
-                                interval,
-                                event.kline.timestamp,
-                                event.kline.close,
-                                event.kline.volume,
-                                event.kline.number_of_trades
-                            );
-                        }
+                if let Ok(event) = rx.try_recv() {
+                    count += 1;
+                    if count % 100 == 0 {
+                        tracing::info!(
+                            "KLine {}s: time={}, price={:.2}, volume={:.8}, trades={}",
+                            interval,
+                            event.kline.timestamp,
+                            event.kline.close,
+                            event.kline.volume,
+                            event.kline.number_of_trades
+                        );
                     }
-                    Err(_) => {}
                 }
             }
 
@@ -128,23 +293,35 @@ async fn main() -> MdiResult<()> {
 
     // 5. 启动监控任务
     let kline_builder_clone = Arc::clone(&kline_builder);
+    let dedup_clone = Arc::clone(&dedup);
+    let writer_clone = Arc::clone(&writer);
     let buffer_clone = tick_buffer.clone();
     
-    let monitor_handle: JoinHandle<()> = tokio::spawn(async move {
+    let mut monitor_handle: JoinHandle<()> = tokio::spawn(async move {
         loop {
             tokio::time::sleep(Duration::from_secs(10)).await;
 
             let stats = kline_builder_clone.get_stats();
             let buffer_usage = buffer_clone.usage_percent();
+            let dedup_stats = dedup_clone.get_stats();
+            let writer_stats = writer_clone.get_stats();
 
             tracing::info!(
                 "=== System Status ===\n\
                  Symbols: {}\n\
                  KLines: {}\n\
+                 Duplicate Trades: {} (stale {})\n\
+                 Stored Ticks: {} (pending {}, dropped {}, failed {})\n\
                  Buffer Usage: {:.2}%\n\
                  Buffer Size: {}/{}",
                 stats.total_symbols,
                 stats.total_klines,
+                dedup_stats.total_duplicates,
+                dedup_stats.total_stale,
+                writer_stats.ticks,
+                writer_stats.pending,
+                writer_stats.dropped,
+                writer_stats.failed,
                 buffer_usage,
                 buffer_clone.len(),
                 buffer_clone.capacity()
@@ -152,23 +329,321 @@ async fn main() -> MdiResult<()> {
         }
     });
 
-    // 等待任意任务完成（通常是接收器）
+    // 6. 启动数据保留任务（每小时清理一次过期数据）
+    let storage_clone = Arc::clone(&storage);
+    let retention_handle: JoinHandle<()> = tokio::spawn(async move {
+        let policy = RetentionPolicy::standard();
+        loop {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+
+            let storage = Arc::clone(&storage_clone);
+            let policy = policy.clone();
+            let now = chrono::Utc::now().timestamp_millis() as u64;
+            match tokio::task::spawn_blocking(move || storage.purge(&policy, now)).await {
+                Ok(Ok(report)) if !report.is_empty() => tracing::info!(
+                    "Purged {} ticks, {} index entries, {:?} klines, {} profiles",
+                    report.ticks,
+                    report.trade_index,
+                    report.klines,
+                    report.profiles
+                ),
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => tracing::warn!("Failed to purge expired data: {}", e),
+                Err(e) => tracing::warn!("Retention task error: {}", e),
+            }
+        }
+    });
+
+    // 7. 启动备份任务（每天一次增量备份，保留 7 份）
+    let storage_clone = Arc::clone(&storage);
+    let backup_handle: JoinHandle<()> = tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(86400)).await;
+
+            let storage = Arc::clone(&storage_clone);
+            match tokio::task::spawn_blocking(move || storage.backup(std::path::Path::new(backup_dir), 7)).await {
+                Ok(Ok(info)) => tracing::info!("Backup {} created ({} bytes)", info.backup_id, info.size),
+                Ok(Err(e)) => tracing::warn!("Failed to back up storage: {}", e),
+                Err(e) => tracing::warn!("Backup task error: {}", e),
+            }
+        }
+    });
+
+    // 8. 启动归档任务（每小时把已结束的自然日移入列式归档）
+    let archive_handle: JoinHandle<()> = tokio::spawn(async move {
+        // 其他后端没有冷热分层
+        let Some(tiered) = tiered else {
+            return std::future::pending().await;
+        };
+        loop {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+
+            let tiered = tiered.clone();
+            let now = chrono::Utc::now().timestamp_millis() as u64;
+            match tokio::task::spawn_blocking(move || tiered.compact(now)).await {
+                Ok(Ok(report)) if !report.is_empty() => tracing::info!(
+                    "Archived {} ticks into {} daily files ({} bytes)",
+                    report.ticks,
+                    report.days,
+                    report.bytes
+                ),
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => tracing::warn!("Failed to archive ticks: {}", e),
+                Err(e) => tracing::warn!("Archive task error: {}", e),
+            }
+        }
+    });
+
+    // 等待任意任务完成（通常是接收器），记录已结束的任务，结束后不能再次 await
+    let mut processor_done = false;
+    let mut monitor_done = false;
     tokio::select! {
         res = receiver_handle => {
             if let Err(e) = res {
                 tracing::error!("Receiver task error: {}", e);
             }
         }
-        _ = processor_handle => {
+        _ = &mut processor_handle => {
+            processor_done = true;
             tracing::info!("Processor task completed");
         }
         _ = subscriber_handle => {
             tracing::info!("Subscriber task completed");
         }
-        _ = monitor_handle => {
+        _ = &mut monitor_handle => {
+            monitor_done = true;
             tracing::info!("Monitor task completed");
         }
+        _ = retention_handle => {
+            tracing::info!("Retention task completed");
+        }
+        _ = backup_handle => {
+            tracing::info!("Backup task completed");
+        }
+        _ = archive_handle => {
+            tracing::info!("Archive task completed");
+        }
+        _ = tokio::signal::ctrl_c() => {
+            tracing::info!("Shutting down...");
+        }
+    }
+
+    // 停止持有写入器的任务后写完队列中的剩余记录
+    for (handle, done) in [(processor_handle, processor_done), (monitor_handle, monitor_done)] {
+        if !done {
+            handle.abort();
+            let _ = handle.await;
+        }
+    }
+    match Arc::try_unwrap(writer) {
+        Ok(writer) => {
+            let stats = writer.shutdown();
+            tracing::info!(
+                "Storage writer flushed: {} ticks, {} bars, {} profiles, {} dropped",
+                stats.ticks,
+                stats.klines,
+                stats.profiles,
+                stats.dropped
+            );
+        }
+        Err(writer) => tracing::warn!(
+            "Storage writer still has {} references, {} pending records were not flushed",
+            Arc::strong_count(&writer),
+            writer.get_stats().pending
+        ),
+    }
+
+    Ok(())
+}
+
+/// 将旧版数据库迁移为二进制键和二进制编码
+fn migrate(db_path: &str) -> MdiResult<()> {
+    tracing::info!("Migrating {}...", db_path);
+    let storage = TickStorage::open_for_migration(db_path)?;
+
+    let keys = storage.migrate_keys()?;
+    tracing::info!("Keys: migrated {} legacy records", keys.migrated);
+    let values = storage.migrate_values()?;
+    tracing::info!("Values: scanned {}, re-encoded {}", values.scanned, values.migrated);
+
+    Ok(())
+}
+
+/// 校验存储完整性，`repair` 时用 Tick 重新计算不一致的 K 线
+fn verify(db_path: &str, repair: bool) -> MdiResult<()> {
+    let storage = TickStorage::open(db_path)?;
+    let report = storage.verify(&VerifyOptions::new().repair(repair))?;
+    for issue in &report.issues {
+        tracing::warn!("{}", issue);
+    }
+    tracing::info!(
+        "Verified {} keys {:?}: {} issues {:?}, {} missing trades, {} bars checked, {} repaired",
+        report.keys.values().sum::<u64>(),
+        report.keys,
+        report.total_issues(),
+        report.counts,
+        report.missing_trades,
+        report.bars_checked,
+        report.bars_repaired
+    );
+    match report.total_issues() - report.bars_repaired {
+        0 => Ok(()),
+        unresolved => Err(mdi::MdiError::StorageError(format!("{} unresolved integrity issues", unresolved))),
+    }
+}
+
+/// 以 JSON 输出存储统计，`--keys` 时扫描全部键按数据类型和品种统计
+fn stats(db_path: &str, keys: bool) -> MdiResult<()> {
+    let storage = TickStorage::open(db_path)?;
+    let stats = if keys { storage.get_detailed_stats()? } else { storage.get_stats()? };
+    println!("{}", serde_json::to_string_pretty(&stats)?);
+    Ok(())
+}
+
+/// 把已结束的自然日的 Tick 移入列式归档
+fn archive(db_path: &str, archive_dir: &str) -> MdiResult<()> {
+    let tiered = TieredStorage::new(TickStorage::open(db_path)?, TickArchive::open(archive_dir)?);
+    let report = tiered.compact(chrono::Utc::now().timestamp_millis() as u64)?;
+    tracing::info!(
+        "Archived {} ticks of {} symbols into {} daily files ({} bytes) in {}",
+        report.ticks,
+        report.symbols.len(),
+        report.days,
+        report.bytes,
+        archive_dir
+    );
+    Ok(())
+}
+
+/// 以从实例打开数据库并追上主实例，采集进程运行时也可执行，之后调用 `f`
+///
+/// 从实例日志放在临时目录，用完删除
+fn with_secondary<T>(db_path: &str, f: impl FnOnce(&TickStorage) -> MdiResult<T>) -> MdiResult<T> {
+    let secondary_dir = std::env::temp_dir().join(format!("mdi-secondary-{}", std::process::id()));
+    let result = TickStorage::open_as_secondary(db_path, &secondary_dir).and_then(|storage| f(&storage));
+    let _ = std::fs::remove_dir_all(&secondary_dir);
+    result
+}
+
+/// 创建时间点检查点
+fn checkpoint(db_path: &str, checkpoint_dir: &str) -> MdiResult<()> {
+    with_secondary(db_path, |storage| storage.checkpoint(checkpoint_dir))?;
+    tracing::info!("Checkpoint of {} created at {}", db_path, checkpoint_dir);
+    Ok(())
+}
+
+/// 增量备份，保留最近 `keep` 份，同时硬链接归档快照（见 `TieredStorage` 的 `backup`）
+fn backup(db_path: &str, backup_dir: &str, keep: usize, archive_dir: &str) -> MdiResult<()> {
+    let info = with_secondary(db_path, |storage| {
+        let tiered = TieredStorage::new(storage.clone(), TickArchive::open(archive_dir)?);
+        TickStore::backup(&tiered, std::path::Path::new(backup_dir), keep)
+    })?;
+    tracing::info!(
+        "Backup {} of {} created in {} ({} files, {} bytes)",
+        info.backup_id,
+        db_path,
+        backup_dir,
+        info.num_files,
+        info.size
+    );
+    Ok(())
+}
+
+fn list_backups(backup_dir: &str) -> MdiResult<()> {
+    for info in TickStorage::list_backups(backup_dir)? {
+        let time = chrono::DateTime::from_timestamp(info.timestamp, 0).unwrap_or_default();
+        println!("{}\t{}\t{} files\t{} bytes", info.backup_id, time, info.num_files, info.size);
+    }
+    Ok(())
+}
+
+/// 从备份恢复到新路径，指定 `archive_target` 时同时恢复归档快照
+fn restore(backup_dir: &str, target: &str, backup_id: Option<u32>, archive_target: Option<&str>) -> MdiResult<()> {
+    match archive_target {
+        Some(archive_target) => {
+            TieredStorage::restore(backup_dir, target, archive_target, backup_id)?;
+            tracing::info!("Restored archive snapshot to {}", archive_target);
+        }
+        None => TickStorage::restore(backup_dir, target, backup_id)?,
+    }
+    match backup_id {
+        Some(backup_id) => tracing::info!("Restored backup {} to {}", backup_id, target),
+        None => tracing::info!("Restored latest backup to {}", target),
+    }
+    Ok(())
+}
+
+/// 导出 Tick（未指定 `--interval`）或 K 线到 CSV/Parquet，格式默认按文件扩展名
+fn export(db_path: &str, output: &str, flags: &[String]) -> MdiResult<()> {
+    let mut options = ExportOptions::new(if output.ends_with(".parquet") {
+        ExportFormat::Parquet
+    } else {
+        ExportFormat::Csv
+    });
+    let mut symbols = None;
+    let mut interval = None;
+    let mut archive_dir = None;
+    let (mut from_ms, mut to_ms) = (0, u64::MAX);
+
+    let invalid = |flag: &str| mdi::MdiError::Other(format!("Invalid value for {}", flag));
+    for pair in flags.chunks(2) {
+        let (flag, value) = match pair {
+            [flag, value] => (flag.as_str(), value.as_str()),
+            _ => return Err(invalid(&pair[0])),
+        };
+        match flag {
+            "--format" => options.format = value.parse()?,
+            "--symbols" => symbols = Some(value.split(',').map(str::to_string).collect::<Vec<_>>()),
+            "--interval" => interval = Some(value.parse().map_err(|_| invalid(flag))?),
+            "--from" => from_ms = value.parse().map_err(|_| invalid(flag))?,
+            "--to" => to_ms = value.parse().map_err(|_| invalid(flag))?,
+            "--archive" => archive_dir = Some(value),
+            "--row-group" => options = options.row_group_size(value.parse().map_err(|_| invalid(flag))?),
+            _ => return Err(mdi::MdiError::Other(format!("Unknown flag: {}", flag))),
+        }
     }
 
+    // 指定归档目录时同时导出已归档的 Tick
+    let storage: Box<dyn TickStore> = match archive_dir {
+        Some(archive_dir) => Box::new(TieredStorage::new(TickStorage::open(db_path)?, TickArchive::open(archive_dir)?)),
+        None => Box::new(TickStorage::open(db_path)?),
+    };
+    let symbols = symbols.unwrap_or_else(|| storage.symbols());
+    let file = std::fs::File::create(output)
+        .map_err(|e| mdi::MdiError::ExportError(format!("Failed to create {}: {}", output, e)))?;
+    let out = std::io::BufWriter::new(file);
+
+    let stats = match interval {
+        // K 线时间戳为秒
+        Some(interval) => export_klines(storage.as_ref(), &symbols, interval, from_ms / 1000, to_ms / 1000, &options, out)?,
+        None => export_ticks(storage.as_ref(), &symbols, from_ms, to_ms, &options, out)?,
+    };
+    tracing::info!("Exported {} rows ({} row groups) to {}", stats.rows, stats.row_groups, output);
+    Ok(())
+}
+
+/// 导入 Binance 历史数据，可选用导入的成交重新生成 K 线
+fn import(db_path: &str, path: &str, rebuild: Option<&str>) -> MdiResult<()> {
+    let intervals = rebuild
+        .map(|intervals| intervals.split(',').map(str::parse).collect::<MdiResult<Vec<mdi::Interval>>>())
+        .transpose()?;
+
+    let importer = Importer::new(Arc::new(TickStorage::open(db_path)?));
+    let stats = importer.import_path(path)?;
+    tracing::info!(
+        "Imported {} files ({} already complete): {} ticks, {} klines, resumed after {} rows",
+        stats.files,
+        stats.skipped_files,
+        stats.ticks,
+        stats.klines,
+        stats.resumed_rows
+    );
+
+    if let Some(intervals) = intervals {
+        for (symbol, (from_ms, to_ms)) in &stats.tick_ranges {
+            let written = importer.rebuild_klines(symbol, &intervals, *from_ms, *to_ms)?;
+            tracing::info!("Rebuilt {} klines for {}", written, symbol);
+        }
+    }
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,210 @@
+use crate::retention::{PurgeReport, RetentionPolicy};
+use crate::storage::{Page, RangeOptions};
+use crate::store::{scan_map, TickStore};
+use crate::{Tick, KLine, VolumeProfile, Result};
+use parking_lot::RwLock;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// 单个品种的数据
+#[derive(Debug, Default)]
+struct SymbolData {
+    /// (timestamp, trade_id) -> Tick
+    ticks: BTreeMap<(u64, u64), Tick>,
+    /// trade_id -> timestamp
+    trade_index: HashMap<u64, u64>,
+    /// interval -> (timestamp, 0) -> KLine
+    klines: HashMap<u64, BTreeMap<(u64, u64), KLine>>,
+    /// interval -> timestamp -> VolumeProfile
+    profiles: HashMap<u64, BTreeMap<u64, VolumeProfile>>,
+}
+
+/// 内存存储 - 用于测试和嵌入式场景，进程退出后数据丢失
+pub struct MemoryStore {
+    /// symbol -> 数据
+    symbols: Arc<RwLock<HashMap<String, SymbolData>>>,
+    meta: Arc<RwLock<HashMap<String, Vec<u8>>>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        MemoryStore {
+            symbols: Arc::new(RwLock::new(HashMap::new())),
+            meta: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        MemoryStore::new()
+    }
+}
+
+impl Clone for MemoryStore {
+    fn clone(&self) -> Self {
+        MemoryStore {
+            symbols: Arc::clone(&self.symbols),
+            meta: Arc::clone(&self.meta),
+        }
+    }
+}
+
+impl TickStore for MemoryStore {
+    fn write_ticks(&self, ticks: &[Tick]) -> Result<()> {
+        let mut symbols = self.symbols.write();
+        for tick in ticks {
+            let data = symbols.entry(tick.symbol.clone()).or_default();
+            // 同一 trade_id 以最后一次写入为准
+            if let Some(old) = data.trade_index.insert(tick.trade_id, tick.timestamp) {
+                data.ticks.remove(&(old, tick.trade_id));
+            }
+            data.ticks.insert((tick.timestamp, tick.trade_id), tick.clone());
+        }
+        Ok(())
+    }
+
+    fn read_tick(&self, symbol: &str, trade_id: u64) -> Result<Option<Tick>> {
+        let symbols = self.symbols.read();
+        Ok(symbols.get(symbol).and_then(|data| {
+            let timestamp = data.trade_index.get(&trade_id)?;
+            data.ticks.get(&(*timestamp, trade_id)).cloned()
+        }))
+    }
+
+    fn read_ticks_range(&self, symbol: &str, from_ms: u64, to_ms: u64, options: &RangeOptions) -> Result<Page<Tick>> {
+        let symbols = self.symbols.read();
+        Ok(symbols
+            .get(symbol)
+            .map(|data| scan_map(&data.ticks, from_ms, to_ms, options))
+            .unwrap_or_default())
+    }
+
+    fn write_klines(&self, klines: &[KLine]) -> Result<()> {
+        let mut symbols = self.symbols.write();
+        for kline in klines {
+            symbols
+                .entry(kline.symbol.clone())
+                .or_default()
+                .klines
+                .entry(kline.interval)
+                .or_default()
+                .insert((kline.timestamp, 0), kline.clone());
+        }
+        Ok(())
+    }
+
+    fn read_kline(&self, symbol: &str, interval: u64, timestamp: u64) -> Result<Option<KLine>> {
+        let symbols = self.symbols.read();
+        Ok(symbols
+            .get(symbol)
+            .and_then(|data| data.klines.get(&interval)?.get(&(timestamp, 0)).cloned()))
+    }
+
+    fn read_klines_range(
+        &self,
+        symbol: &str,
+        interval: u64,
+        from: u64,
+        to: u64,
+        options: &RangeOptions,
+    ) -> Result<Page<KLine>> {
+        let symbols = self.symbols.read();
+        Ok(symbols
+            .get(symbol)
+            .and_then(|data| data.klines.get(&interval))
+            .map(|klines| scan_map(klines, from, to, options))
+            .unwrap_or_default())
+    }
+
+    fn write_profiles(&self, profiles: &[VolumeProfile]) -> Result<()> {
+        let mut symbols = self.symbols.write();
+        for profile in profiles {
+            symbols
+                .entry(profile.symbol.clone())
+                .or_default()
+                .profiles
+                .entry(profile.interval)
+                .or_default()
+                .insert(profile.timestamp, profile.clone());
+        }
+        Ok(())
+    }
+
+    fn read_profile(&self, symbol: &str, interval: u64, timestamp: u64) -> Result<Option<VolumeProfile>> {
+        let symbols = self.symbols.read();
+        Ok(symbols
+            .get(symbol)
+            .and_then(|data| data.profiles.get(&interval)?.get(&timestamp).cloned()))
+    }
+
+    fn read_meta(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.meta.read().get(name).cloned())
+    }
+
+    fn write_meta(&self, name: &str, value: &[u8]) -> Result<()> {
+        self.meta.write().insert(name.to_string(), value.to_vec());
+        Ok(())
+    }
+
+    fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<_> = self.symbols.read().keys().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+
+    fn purge(&self, policy: &RetentionPolicy, now_ms: u64) -> Result<PurgeReport> {
+        let mut report = PurgeReport::default();
+        let mut symbols = self.symbols.write();
+
+        for (symbol, data) in symbols.iter_mut() {
+            let mut removed = 0;
+
+            if let Some(keep_ms) = policy.ticks {
+                let cutoff = RetentionPolicy::cutoff(keep_ms, now_ms);
+                let kept = data.ticks.split_off(&(cutoff, 0));
+                let expired = std::mem::replace(&mut data.ticks, kept);
+                for (_, trade_id) in expired.keys() {
+                    data.trade_index.remove(trade_id);
+                }
+                report.ticks += expired.len() as u64;
+                report.trade_index += expired.len() as u64;
+                removed += expired.len() as u64 * 2;
+            }
+
+            for (&interval, &keep_ms) in &policy.klines {
+                let cutoff = RetentionPolicy::cutoff(keep_ms, now_ms) / 1000;
+                if let Some(klines) = data.klines.get_mut(&interval) {
+                    let kept = klines.split_off(&(cutoff, 0));
+                    let expired = std::mem::replace(klines, kept).len() as u64;
+                    if expired > 0 {
+                        *report.klines.entry(interval).or_default() += expired;
+                        removed += expired;
+                    }
+                }
+            }
+
+            if let Some(keep_ms) = policy.profiles {
+                let cutoff = RetentionPolicy::cutoff(keep_ms, now_ms) / 1000;
+                for profiles in data.profiles.values_mut() {
+                    let kept = profiles.split_off(&cutoff);
+                    let expired = std::mem::replace(profiles, kept).len() as u64;
+                    report.profiles += expired;
+                    removed += expired;
+                }
+            }
+
+            if removed > 0 {
+                report.symbols.insert(symbol.clone(), removed);
+            }
+        }
+
+        Ok(report)
+    }
+
+    fn clear(&self) -> Result<()> {
+        self.symbols.write().clear();
+        self.meta.write().clear();
+        Ok(())
+    }
+}
@@ -53,7 +53,7 @@ pub struct KLine {
     pub symbol: String,
     /// 时间戳（秒）
     pub timestamp: u64,
-    /// K 线间隔（秒）
+    /// K 线间隔（秒），日历周期和信息驱动 K 线为带类型标记的标识，见 `Interval::id`
     pub interval: u64,
     /// 开盘价
     pub open: f64,
@@ -69,6 +69,24 @@ pub struct KLine {
     pub quote_asset_volume: f64,
     /// 成交笔数
     pub number_of_trades: u64,
+    /// 主动买入成交量（taker buy base volume）
+    #[serde(default)]
+    pub taker_buy_volume: f64,
+    /// 主动买入成交额（taker buy quote volume）
+    #[serde(default)]
+    pub taker_buy_quote_volume: f64,
+    /// 主动买入成交笔数
+    #[serde(default)]
+    pub taker_buy_trades: u64,
+    /// 主动卖出成交量
+    #[serde(default)]
+    pub taker_sell_volume: f64,
+    /// 主动卖出成交额
+    #[serde(default)]
+    pub taker_sell_quote_volume: f64,
+    /// 主动卖出成交笔数
+    #[serde(default)]
+    pub taker_sell_trades: u64,
     /// 时间范围
     pub open_time: u64,
     pub close_time: u64,
@@ -92,6 +110,12 @@ impl KLine {
             volume: 0.0,
             quote_asset_volume: 0.0,
             number_of_trades: 0,
+            taker_buy_volume: 0.0,
+            taker_buy_quote_volume: 0.0,
+            taker_buy_trades: 0,
+            taker_sell_volume: 0.0,
+            taker_sell_quote_volume: 0.0,
+            taker_sell_trades: 0,
             open_time: timestamp,
             close_time: timestamp + interval,
         }
@@ -105,6 +129,33 @@ impl KLine {
         self.volume += tick.quantity;
         self.quote_asset_volume += tick.price * tick.quantity;
         self.number_of_trades += 1;
+
+        // is_buyer_maker = true 表示买方挂单、卖方主动成交
+        if tick.is_buyer_maker {
+            self.taker_sell_volume += tick.quantity;
+            self.taker_sell_quote_volume += tick.price * tick.quantity;
+            self.taker_sell_trades += 1;
+        } else {
+            self.taker_buy_volume += tick.quantity;
+            self.taker_buy_quote_volume += tick.price * tick.quantity;
+            self.taker_buy_trades += 1;
+        }
+    }
+
+    /// 合并一根时间上更晚的 K 线（用于由低周期汇总高周期）
+    pub fn merge(&mut self, other: &KLine) {
+        self.high = self.high.max(other.high);
+        self.low = self.low.min(other.low);
+        self.close = other.close;
+        self.volume += other.volume;
+        self.quote_asset_volume += other.quote_asset_volume;
+        self.number_of_trades += other.number_of_trades;
+        self.taker_buy_volume += other.taker_buy_volume;
+        self.taker_buy_quote_volume += other.taker_buy_quote_volume;
+        self.taker_buy_trades += other.taker_buy_trades;
+        self.taker_sell_volume += other.taker_sell_volume;
+        self.taker_sell_quote_volume += other.taker_sell_quote_volume;
+        self.taker_sell_trades += other.taker_sell_trades;
     }
 
     /// 获取 K 线关键指标
@@ -116,6 +167,21 @@ impl KLine {
         }
     }
 
+    /// 主动买卖量差（delta）: 主动买入量 - 主动卖出量
+    pub fn delta(&self) -> f64 {
+        self.taker_buy_volume - self.taker_sell_volume
+    }
+
+    /// 订单流不平衡度: delta / 总成交量，取值 [-1, 1]
+    pub fn order_flow_imbalance(&self) -> f64 {
+        let total = self.taker_buy_volume + self.taker_sell_volume;
+        if total == 0.0 {
+            0.0
+        } else {
+            self.delta() / total
+        }
+    }
+
     /// K 线涨跌幅
     pub fn change_percent(&self) -> f64 {
         if self.open == 0.0 {
@@ -127,6 +193,8 @@ impl KLine {
 }
 
 /// 聚合品种数据
+///
+/// `update` 自启动起累计；滚动 24 小时行情见 `ticker::TickerBuilder`
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SymbolStats {
     pub symbol: String,
@@ -137,6 +205,12 @@ pub struct SymbolStats {
     pub low: f64,
     pub last_price: f64,
     pub last_update: u64,
+    /// 成交额
+    #[serde(default)]
+    pub quote_volume: f64,
+    /// 第一笔成交时间（毫秒）
+    #[serde(default)]
+    pub open_time: u64,
 }
 
 impl SymbolStats {
@@ -150,6 +224,8 @@ impl SymbolStats {
             low: 0.0,
             last_price: 0.0,
             last_update: 0,
+            quote_volume: 0.0,
+            open_time: 0,
         }
     }
 
@@ -158,13 +234,38 @@ impl SymbolStats {
             self.open = tick.price;
             self.high = tick.price;
             self.low = tick.price;
+            self.open_time = tick.timestamp;
         }
 
         self.tick_count += 1;
         self.volume += tick.quantity;
+        self.quote_volume += tick.price * tick.quantity;
         self.high = self.high.max(tick.price);
         self.low = self.low.min(tick.price);
         self.last_price = tick.price;
         self.last_update = tick.timestamp;
     }
+
+    /// 价格变动
+    pub fn price_change(&self) -> f64 {
+        self.last_price - self.open
+    }
+
+    /// 价格变动百分比
+    pub fn price_change_percent(&self) -> f64 {
+        if self.open == 0.0 {
+            0.0
+        } else {
+            self.price_change() / self.open * 100.0
+        }
+    }
+
+    /// 成交量加权平均价
+    pub fn weighted_avg_price(&self) -> f64 {
+        if self.volume == 0.0 {
+            0.0
+        } else {
+            self.quote_volume / self.volume
+        }
+    }
 }
@@ -0,0 +1,167 @@
+use crate::Tick;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 价格档位
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceLevel {
+    /// 档位下沿价格
+    pub price: f64,
+    /// 主动买入成交量
+    pub buy_volume: f64,
+    /// 主动卖出成交量
+    pub sell_volume: f64,
+    pub trades: u64,
+}
+
+impl PriceLevel {
+    pub fn volume(&self) -> f64 {
+        self.buy_volume + self.sell_volume
+    }
+
+    /// 主动买卖差
+    pub fn delta(&self) -> f64 {
+        self.buy_volume - self.sell_volume
+    }
+}
+
+/// 成交量分布（Footprint）- 单根 K 线内按价格档位统计的成交量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VolumeProfile {
+    pub symbol: String,
+    /// K 线周期
+    pub interval: u64,
+    /// K 线开始时间
+    pub timestamp: u64,
+    /// 档位宽度
+    pub bucket_size: f64,
+    /// 按价格升序的档位
+    pub levels: Vec<PriceLevel>,
+    /// 成交量最大的档位（Point of Control）
+    pub poc: f64,
+    /// 价值区上沿档位
+    pub value_area_high: f64,
+    /// 价值区下沿档位
+    pub value_area_low: f64,
+}
+
+impl VolumeProfile {
+    pub fn new(symbol: String, interval: u64, timestamp: u64, bucket_size: f64) -> Self {
+        VolumeProfile {
+            symbol,
+            interval,
+            timestamp,
+            bucket_size,
+            levels: Vec::new(),
+            poc: 0.0,
+            value_area_high: 0.0,
+            value_area_low: 0.0,
+        }
+    }
+
+    /// 按成交价格所在档位累加成交量；POC 和价值区需调用 `refresh` 更新
+    pub fn update(&mut self, tick: &Tick) {
+        let price = self.level_price(tick.price);
+        let index = match self.levels.binary_search_by(|level| level.price.total_cmp(&price)) {
+            Ok(index) => index,
+            Err(index) => {
+                self.levels.insert(index, PriceLevel { price, buy_volume: 0.0, sell_volume: 0.0, trades: 0 });
+                index
+            }
+        };
+
+        let level = &mut self.levels[index];
+        // 买方是 maker 即卖方主动成交
+        if tick.is_buyer_maker {
+            level.sell_volume += tick.quantity;
+        } else {
+            level.buy_volume += tick.quantity;
+        }
+        level.trades += 1;
+    }
+
+    /// 价格所在档位的下沿
+    pub fn level_price(&self, price: f64) -> f64 {
+        if self.bucket_size <= 0.0 {
+            return price;
+        }
+        // 避免 0.1 + 0.2 之类的浮点误差落入下一档
+        (price / self.bucket_size + 1e-9).floor() * self.bucket_size
+    }
+
+    pub fn total_volume(&self) -> f64 {
+        self.levels.iter().map(PriceLevel::volume).sum()
+    }
+
+    /// 重新计算 POC 和价值区
+    ///
+    /// 价值区从 POC 开始，每次向成交量较大的一侧扩展一档，直到覆盖 `value_area`（例如 0.7）的成交量
+    pub fn refresh(&mut self, value_area: f64) {
+        let volumes: Vec<f64> = self.levels.iter().map(PriceLevel::volume).collect();
+        let poc = match volumes
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+        {
+            Some((index, _)) => index,
+            None => return,
+        };
+
+        let target = volumes.iter().sum::<f64>() * value_area;
+        let (mut low, mut high) = (poc, poc);
+        let mut covered = volumes[poc];
+        while covered < target && (low > 0 || high + 1 < volumes.len()) {
+            let below = if low > 0 { volumes[low - 1] } else { f64::NEG_INFINITY };
+            let above = volumes.get(high + 1).copied().unwrap_or(f64::NEG_INFINITY);
+            if above >= below {
+                high += 1;
+                covered += above;
+            } else {
+                low -= 1;
+                covered += below;
+            }
+        }
+
+        self.poc = self.levels[poc].price;
+        self.value_area_low = self.levels[low].price;
+        self.value_area_high = self.levels[high].price;
+    }
+}
+
+/// 成交量分布配置
+#[derive(Debug, Clone)]
+pub struct ProfileConfig {
+    /// 默认档位宽度
+    pub bucket_size: f64,
+    /// 按品种设置的档位宽度（通常为交易所 tick size 的整数倍）
+    pub symbol_buckets: HashMap<String, f64>,
+    /// 价值区覆盖的成交量比例
+    pub value_area: f64,
+}
+
+impl ProfileConfig {
+    pub fn new(bucket_size: f64) -> Self {
+        ProfileConfig {
+            bucket_size,
+            symbol_buckets: HashMap::new(),
+            value_area: 0.7,
+        }
+    }
+
+    /// 设置指定品种的档位宽度
+    pub fn symbol_bucket(mut self, symbol: &str, bucket_size: f64) -> Self {
+        self.symbol_buckets.insert(symbol.to_string(), bucket_size);
+        self
+    }
+
+    /// 设置价值区比例
+    pub fn value_area(mut self, value_area: f64) -> Self {
+        self.value_area = value_area;
+        self
+    }
+
+    /// 品种的档位宽度
+    pub fn bucket_for(&self, symbol: &str) -> f64 {
+        self.symbol_buckets.get(symbol).copied().unwrap_or(self.bucket_size)
+    }
+}
@@ -39,9 +39,8 @@ impl RingBuffer {
 
     /// 弹出数据（非阻塞）
     pub fn pop(&self) -> Option<Tick> {
-        self.queue.pop().and_then(|tick| {
+        self.queue.pop().inspect(|_| {
             self.size.fetch_sub(1, Ordering::Release);
-            Some(tick)
         })
     }
 
@@ -70,7 +69,7 @@ impl RingBuffer {
 
     /// 清空队列
     pub fn clear(&self) {
-        while let Some(_) = self.pop() {}
+        while self.pop().is_some() {}
     }
 
     /// 获取队列容量
@@ -63,7 +63,7 @@ impl TickReceiver {
                     Ok(tick) => match self.ring_buffer.push(tick) {
                         Ok(_) => {
                             tick_count += 1;
-                            if tick_count % 10000 == 0 {
+                            if tick_count.is_multiple_of(10000) {
                                 let elapsed = start_time.elapsed().as_secs_f64();
                                 let tps = tick_count as f64 / elapsed;
                                 tracing::info!(
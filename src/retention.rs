@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 一天的毫秒数
+pub const DAY_MS: u64 = 86_400_000;
+
+/// 数据保留策略 - 按数据类型设置保留时长（毫秒），None 表示永久保留
+///
+/// K 线按周期分别设置，未配置的周期永久保留
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub ticks: Option<u64>,
+    /// interval -> 保留时长
+    pub klines: HashMap<u64, u64>,
+    pub profiles: Option<u64>,
+}
+
+impl RetentionPolicy {
+    /// 不删除任何数据
+    pub fn new() -> Self {
+        RetentionPolicy::default()
+    }
+
+    /// Tick 保留 30 天，1 分钟 K 线保留 1 年，其余 K 线永久保留
+    pub fn standard() -> Self {
+        RetentionPolicy::new()
+            .ticks(30 * DAY_MS)
+            .klines(60, 365 * DAY_MS)
+            .profiles(30 * DAY_MS)
+    }
+
+    /// 设置 Tick（含 trade_id 索引）保留时长
+    pub fn ticks(mut self, keep_ms: u64) -> Self {
+        self.ticks = Some(keep_ms);
+        self
+    }
+
+    /// 设置指定周期 K 线的保留时长
+    pub fn klines(mut self, interval: u64, keep_ms: u64) -> Self {
+        self.klines.insert(interval, keep_ms);
+        self
+    }
+
+    /// 设置成交量分布保留时长
+    pub fn profiles(mut self, keep_ms: u64) -> Self {
+        self.profiles = Some(keep_ms);
+        self
+    }
+
+    /// 截止时间（毫秒），早于该时间的数据过期
+    pub fn cutoff(keep_ms: u64, now_ms: u64) -> u64 {
+        now_ms.saturating_sub(keep_ms)
+    }
+}
+
+/// 单次清理的结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PurgeReport {
+    pub ticks: u64,
+    pub trade_index: u64,
+    /// interval -> 删除的 K 线数
+    pub klines: HashMap<u64, u64>,
+    pub profiles: u64,
+    /// symbol -> 删除的记录数（所有类型合计）
+    pub symbols: HashMap<String, u64>,
+}
+
+impl PurgeReport {
+    /// 删除的记录总数
+    pub fn total(&self) -> u64 {
+        self.ticks + self.trade_index + self.klines.values().sum::<u64>() + self.profiles
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
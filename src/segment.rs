@@ -0,0 +1,294 @@
+use crate::codec::{self, ValueFormat};
+use crate::memstore::MemoryStore;
+use crate::retention::{PurgeReport, RetentionPolicy};
+use crate::storage::{Page, RangeOptions};
+use crate::store::TickStore;
+use crate::{Tick, KLine, VolumeProfile, MdiError, Result};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const RECORD_TICK: u8 = 1;
+const RECORD_KLINE: u8 = 2;
+const RECORD_PROFILE: u8 = 3;
+const RECORD_META: u8 = 4;
+const RECORD_PURGE: u8 = 5;
+
+/// 记录头：payload 长度 u32 LE | 类型 u8 | CRC32(类型 + payload) u32 LE
+const HEADER_LEN: usize = 9;
+
+#[derive(Serialize, Deserialize)]
+struct PurgeRecord {
+    policy: RetentionPolicy,
+    now_ms: u64,
+}
+
+/// 当前写入的段文件
+struct ActiveSegment {
+    file: File,
+    id: u64,
+    size: u64,
+}
+
+/// 段文件存储 - 所有写入以记录形式追加到 `segment-NNNNNNNN.log`，打开时回放到内存索引
+///
+/// 段超过 `segment_bytes` 后切换到新文件；purge 也作为记录回放，不回收已写入的段。
+/// 进程崩溃留下的不完整或校验失败的尾部记录在打开时被截断
+pub struct SegmentStore {
+    dir: PathBuf,
+    segment_bytes: u64,
+    active: Arc<Mutex<ActiveSegment>>,
+    memory: MemoryStore,
+}
+
+impl Clone for SegmentStore {
+    fn clone(&self) -> Self {
+        SegmentStore {
+            dir: self.dir.clone(),
+            segment_bytes: self.segment_bytes,
+            active: Arc::clone(&self.active),
+            memory: self.memory.clone(),
+        }
+    }
+}
+
+impl SegmentStore {
+    /// 打开或创建段文件目录并回放已有记录
+    pub fn open<P: AsRef<Path>>(dir: P, segment_bytes: u64) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&dir).map_err(|e| io_error(&dir, e))?;
+
+        let memory = MemoryStore::new();
+        let segments = list_segments(&dir)?;
+        for (index, &id) in segments.iter().enumerate() {
+            let path = segment_path(&dir, id);
+            let bytes = std::fs::read(&path).map_err(|e| io_error(&path, e))?;
+            let valid = replay(&memory, &bytes)?;
+            if valid < bytes.len() {
+                if index + 1 < segments.len() {
+                    return Err(MdiError::StorageError(format!("Truncated segment: {}", path.display())));
+                }
+                tracing::warn!("Truncating {} bytes of partial or corrupt records in {}", bytes.len() - valid, path.display());
+                let file = OpenOptions::new().write(true).open(&path).map_err(|e| io_error(&path, e))?;
+                file.set_len(valid as u64).map_err(|e| io_error(&path, e))?;
+            }
+        }
+
+        let active = open_segment(&dir, segments.last().copied().unwrap_or(0))?;
+        Ok(SegmentStore {
+            dir,
+            segment_bytes: segment_bytes.max(1),
+            active: Arc::new(Mutex::new(active)),
+            memory,
+        })
+    }
+
+    /// 段文件数量
+    pub fn segment_count(&self) -> Result<usize> {
+        Ok(list_segments(&self.dir)?.len())
+    }
+
+    /// 追加记录，写满后切换段；写入失败时截断已写入的部分
+    fn append(&self, records: &[u8]) -> Result<()> {
+        if records.is_empty() {
+            return Ok(());
+        }
+        let mut active = self.active.lock();
+        if active.size > 0 && active.size + records.len() as u64 > self.segment_bytes {
+            *active = open_segment(&self.dir, active.id + 1)?;
+        }
+        let path = segment_path(&self.dir, active.id);
+        if let Err(e) = active.file.write_all(records).and_then(|_| active.file.flush()) {
+            // 截断到上一条完整记录之后，避免部分写入的记录夹在之后追加的记录之前
+            if let Err(truncate) = active.file.set_len(active.size) {
+                tracing::error!("Failed to truncate {} to {} bytes: {}", path.display(), active.size, truncate);
+            }
+            return Err(io_error(&path, e));
+        }
+        active.size += records.len() as u64;
+        Ok(())
+    }
+}
+
+impl TickStore for SegmentStore {
+    fn write_ticks(&self, ticks: &[Tick]) -> Result<()> {
+        let mut buf = Vec::new();
+        for tick in ticks {
+            put_record(&mut buf, RECORD_TICK, &codec::encode(tick, ValueFormat::Binary)?);
+        }
+        self.append(&buf)?;
+        self.memory.write_ticks(ticks)
+    }
+
+    fn read_tick(&self, symbol: &str, trade_id: u64) -> Result<Option<Tick>> {
+        self.memory.read_tick(symbol, trade_id)
+    }
+
+    fn read_ticks_range(&self, symbol: &str, from_ms: u64, to_ms: u64, options: &RangeOptions) -> Result<Page<Tick>> {
+        self.memory.read_ticks_range(symbol, from_ms, to_ms, options)
+    }
+
+    fn write_klines(&self, klines: &[KLine]) -> Result<()> {
+        let mut buf = Vec::new();
+        for kline in klines {
+            put_record(&mut buf, RECORD_KLINE, &codec::encode(kline, ValueFormat::Binary)?);
+        }
+        self.append(&buf)?;
+        self.memory.write_klines(klines)
+    }
+
+    fn read_kline(&self, symbol: &str, interval: u64, timestamp: u64) -> Result<Option<KLine>> {
+        self.memory.read_kline(symbol, interval, timestamp)
+    }
+
+    fn read_klines_range(
+        &self,
+        symbol: &str,
+        interval: u64,
+        from: u64,
+        to: u64,
+        options: &RangeOptions,
+    ) -> Result<Page<KLine>> {
+        self.memory.read_klines_range(symbol, interval, from, to, options)
+    }
+
+    fn write_profiles(&self, profiles: &[VolumeProfile]) -> Result<()> {
+        let mut buf = Vec::new();
+        for profile in profiles {
+            put_record(&mut buf, RECORD_PROFILE, &serde_json::to_vec(profile)?);
+        }
+        self.append(&buf)?;
+        self.memory.write_profiles(profiles)
+    }
+
+    fn read_profile(&self, symbol: &str, interval: u64, timestamp: u64) -> Result<Option<VolumeProfile>> {
+        self.memory.read_profile(symbol, interval, timestamp)
+    }
+
+    fn read_meta(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        self.memory.read_meta(name)
+    }
+
+    fn write_meta(&self, name: &str, value: &[u8]) -> Result<()> {
+        let name_len = u16::try_from(name.len())
+            .map_err(|_| MdiError::StorageError(format!("Meta name too long: {}", name)))?;
+        let mut payload = Vec::with_capacity(2 + name.len() + value.len());
+        payload.extend_from_slice(&name_len.to_le_bytes());
+        payload.extend_from_slice(name.as_bytes());
+        payload.extend_from_slice(value);
+
+        let mut buf = Vec::new();
+        put_record(&mut buf, RECORD_META, &payload);
+        self.append(&buf)?;
+        self.memory.write_meta(name, value)
+    }
+
+    fn symbols(&self) -> Vec<String> {
+        self.memory.symbols()
+    }
+
+    fn purge(&self, policy: &RetentionPolicy, now_ms: u64) -> Result<PurgeReport> {
+        let record = PurgeRecord { policy: policy.clone(), now_ms };
+        let mut buf = Vec::new();
+        put_record(&mut buf, RECORD_PURGE, &serde_json::to_vec(&record)?);
+        self.append(&buf)?;
+        self.memory.purge(policy, now_ms)
+    }
+
+    /// 删除所有段文件
+    fn clear(&self) -> Result<()> {
+        let mut active = self.active.lock();
+        for id in list_segments(&self.dir)? {
+            let path = segment_path(&self.dir, id);
+            std::fs::remove_file(&path).map_err(|e| io_error(&path, e))?;
+        }
+        *active = open_segment(&self.dir, 0)?;
+        self.memory.clear()
+    }
+}
+
+fn put_record(buf: &mut Vec<u8>, kind: u8, payload: &[u8]) {
+    buf.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    buf.push(kind);
+    buf.extend_from_slice(&checksum(kind, payload).to_le_bytes());
+    buf.extend_from_slice(payload);
+}
+
+fn checksum(kind: u8, payload: &[u8]) -> u32 {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(&[kind]);
+    hasher.update(payload);
+    hasher.finalize()
+}
+
+/// 回放段内的完整记录，返回有效字节数，遇到不完整或校验失败的记录时停止
+fn replay(memory: &MemoryStore, bytes: &[u8]) -> Result<usize> {
+    let mut offset = 0;
+    while bytes.len() - offset >= HEADER_LEN {
+        let len = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
+        let kind = bytes[offset + 4];
+        let crc = u32::from_le_bytes(bytes[offset + 5..offset + 9].try_into().unwrap());
+        let start = offset + HEADER_LEN;
+        if bytes.len() - start < len {
+            break;
+        }
+        let payload = &bytes[start..start + len];
+        if checksum(kind, payload) != crc {
+            break;
+        }
+        match kind {
+            RECORD_TICK => memory.write_tick(&codec::decode(payload)?)?,
+            RECORD_KLINE => memory.write_kline(&codec::decode(payload)?)?,
+            RECORD_PROFILE => memory.write_profiles(&[serde_json::from_slice(payload)?])?,
+            RECORD_META => {
+                let corrupt = || MdiError::StorageError("Corrupt meta record".to_string());
+                let name_len = u16::from_le_bytes(payload.get(..2).ok_or_else(corrupt)?.try_into().unwrap()) as usize;
+                let name = payload.get(2..2 + name_len).ok_or_else(corrupt)?;
+                let name = std::str::from_utf8(name).map_err(|_| corrupt())?;
+                memory.write_meta(name, &payload[2 + name_len..])?;
+            }
+            RECORD_PURGE => {
+                let record: PurgeRecord = serde_json::from_slice(payload)?;
+                memory.purge(&record.policy, record.now_ms)?;
+            }
+            _ => return Err(MdiError::StorageError(format!("Unknown segment record type: {}", kind))),
+        }
+        offset = start + len;
+    }
+    Ok(offset)
+}
+
+fn segment_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("segment-{:08}.log", id))
+}
+
+/// 目录下的段编号，升序
+fn list_segments(dir: &Path) -> Result<Vec<u64>> {
+    let mut ids: Vec<u64> = std::fs::read_dir(dir)
+        .map_err(|e| io_error(dir, e))?
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name();
+            name.to_str()?.strip_prefix("segment-")?.strip_suffix(".log")?.parse().ok()
+        })
+        .collect();
+    ids.sort_unstable();
+    Ok(ids)
+}
+
+fn open_segment(dir: &Path, id: u64) -> Result<ActiveSegment> {
+    let path = segment_path(dir, id);
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| io_error(&path, e))?;
+    let size = file.metadata().map_err(|e| io_error(&path, e))?.len();
+    Ok(ActiveSegment { file, id, size })
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> MdiError {
+    MdiError::StorageError(format!("Segment I/O error on {}: {}", path.display(), e))
+}
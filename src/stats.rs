@@ -0,0 +1,150 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// 数据类型名，对应键的首字节
+pub const DATA_TICKS: &str = "ticks";
+pub const DATA_TRADE_INDEX: &str = "trade_index";
+pub const DATA_KLINES: &str = "klines";
+pub const DATA_PROFILES: &str = "profiles";
+pub const DATA_META: &str = "meta";
+
+/// 键数和字节数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct KeyStats {
+    pub keys: u64,
+    pub key_bytes: u64,
+    pub value_bytes: u64,
+}
+
+impl KeyStats {
+    pub(crate) fn add(&mut self, key: &[u8], value: &[u8]) {
+        self.keys += 1;
+        self.key_bytes += key.len() as u64;
+        self.value_bytes += value.len() as u64;
+    }
+
+    /// 键和值的总字节数（未压缩）
+    pub fn bytes(&self) -> u64 {
+        self.key_bytes + self.value_bytes
+    }
+}
+
+/// 一类数据的合计及按品种的分布
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DataTypeStats {
+    pub total: KeyStats,
+    /// 品种 -> 统计，元数据不按品种划分，未注册的品种 id 记为 `#id`
+    pub symbols: BTreeMap<String, KeyStats>,
+}
+
+/// 块缓存用量（字节）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CacheUsage {
+    pub capacity: u64,
+    pub usage: u64,
+    pub pinned: u64,
+}
+
+/// 单个列族的引擎指标
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ColumnFamilyStats {
+    /// RocksDB 估算的键数，包含未压实的删除和覆盖
+    pub estimated_keys: u64,
+    /// 所有版本的 SST 文件大小
+    pub sst_bytes: u64,
+    /// 当前版本引用的 SST 文件大小
+    pub live_sst_bytes: u64,
+    /// 每层 SST 文件数，下标为层号
+    pub sst_files: Vec<u64>,
+    /// 活跃和未刷盘的只读 memtable 占用
+    pub memtable_bytes: u64,
+    pub immutable_memtables: u64,
+    /// 压实需要重写的估算字节数
+    pub pending_compaction_bytes: u64,
+    /// 写入限速和停写次数，如 `l0-file-count-limit-delays`、`total-stops`
+    pub write_stalls: BTreeMap<String, u64>,
+    pub block_cache: CacheUsage,
+}
+
+/// 写入停顿指标
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct WriteStallStats {
+    /// 当前是否停写
+    pub stopped: bool,
+    /// 当前限速（字节/秒），0 表示未限速
+    pub delayed_write_rate: u64,
+    /// 启动以来写入等待的总微秒数
+    pub stall_micros: u64,
+    /// 所有列族的限速次数之和
+    pub delays: u64,
+    /// 所有列族的停写次数之和
+    pub stops: u64,
+}
+
+/// 块缓存命中统计，自数据库打开起累计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct BlockCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// 命中率，尚无读取时为 0
+    pub hit_rate: f64,
+}
+
+impl BlockCacheStats {
+    pub(crate) fn new(hits: u64, misses: u64) -> Self {
+        let lookups = hits + misses;
+        let hit_rate = if lookups == 0 { 0.0 } else { hits as f64 / lookups as f64 };
+        BlockCacheStats { hits, misses, hit_rate }
+    }
+}
+
+/// 存储统计信息，可直接序列化为 JSON 供监控面板和告警使用
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StorageStats {
+    /// 列族 -> 引擎指标
+    pub column_families: BTreeMap<String, ColumnFamilyStats>,
+    pub write_stall: WriteStallStats,
+    pub block_cache: BlockCacheStats,
+    pub running_compactions: u64,
+    pub running_flushes: u64,
+    /// 数据类型 -> 键统计，只有 `get_detailed_stats` 会扫描填充
+    pub data: BTreeMap<String, DataTypeStats>,
+}
+
+impl StorageStats {
+    pub fn sst_bytes(&self) -> u64 {
+        self.column_families.values().map(|cf| cf.sst_bytes).sum()
+    }
+
+    pub fn memtable_bytes(&self) -> u64 {
+        self.column_families.values().map(|cf| cf.memtable_bytes).sum()
+    }
+
+    pub fn pending_compaction_bytes(&self) -> u64 {
+        self.column_families.values().map(|cf| cf.pending_compaction_bytes).sum()
+    }
+
+    /// 扫描到的键总数
+    pub fn total_keys(&self) -> u64 {
+        self.data.values().map(|data| data.total.keys).sum()
+    }
+
+    pub(crate) fn add_key(&mut self, data_type: &str, symbol: Option<String>, key: &[u8], value: &[u8]) {
+        let data = self.data.entry(data_type.to_string()).or_default();
+        data.total.add(key, value);
+        if let Some(symbol) = symbol {
+            data.symbols.entry(symbol).or_default().add(key, value);
+        }
+    }
+}
+
+/// 解析 `rocksdb.cf-write-stall-stats` 形如 `Write Stall (count): a: 0, b: 1` 的输出
+pub(crate) fn parse_write_stalls(text: &str) -> BTreeMap<String, u64> {
+    let body = text.split_once("):").map_or(text, |(_, body)| body);
+    body.split(',')
+        .filter_map(|item| {
+            let (name, count) = item.split_once(':')?;
+            Some((name.trim().to_string(), count.trim().parse().ok()?))
+        })
+        .collect()
+}
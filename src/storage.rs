@@ -1,54 +1,340 @@
-use crate::{Tick, KLine, MdiError, Result};
-use rocksdb::{DB, Options, IteratorMode};
+use crate::codec::{self, ValueFormat};
+use crate::retention::{PurgeReport, RetentionPolicy};
+use crate::stats::{self, BlockCacheStats, CacheUsage, ColumnFamilyStats, StorageStats, WriteStallStats};
+use crate::verify::{self, Issue, VerifyOptions, VerifyReport};
+use crate::{Interval, Tick, KLine, VolumeProfile, MdiError, Result};
+use parking_lot::RwLock;
+use rocksdb::backup::{BackupEngine, BackupEngineOptions, RestoreOptions};
+use rocksdb::checkpoint::Checkpoint;
+use rocksdb::{
+    BlockBasedOptions, Cache, ColumnFamily, ColumnFamilyDescriptor, DBCompactionStyle, DBCompressionType,
+    DB, Options, IteratorMode, ReadOptions, SliceTransform,
+};
+use rocksdb::statistics::Ticker;
+use serde::{Deserialize, Serialize};
 use serde_json;
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 
+/// 键类型（首字节），0x00 为元数据
+const KEY_TICK: u8 = 0x01;
+const KEY_TRADE_INDEX: u8 = 0x02;
+const KEY_KLINE: u8 = 0x03;
+const KEY_PROFILE: u8 = 0x04;
+/// 前缀提取长度：键类型 + 品种 id
+const PREFIX_LEN: usize = 5;
+/// 品种注册表键前缀，值为大端 u32 品种 id
+const SYMBOL_META_PREFIX: &[u8] = b"\x00symbol:";
+/// 批量写入时每个 WriteBatch 的最大操作数
+const BATCH_CHUNK: usize = 10000;
+/// LSM 层数，列族使用 RocksDB 默认值
+const NUM_LEVELS: usize = 7;
+
+/// 列族：Tick 及 trade_id 索引
+pub const CF_TICKS: &str = "ticks";
+/// 列族：K 线
+pub const CF_KLINES: &str = "klines";
+/// 列族：成交量分布等行情衍生数据
+pub const CF_QUOTES: &str = "quotes";
+/// 列族：品种注册表等元数据
+pub const CF_META: &str = "meta";
+
+/// 单个列族的调优参数
+#[derive(Debug, Clone)]
+pub struct ColumnFamilyConfig {
+    pub compression: DBCompressionType,
+    /// 独立的块缓存大小（MB），0 表示使用 RocksDB 默认缓存
+    pub block_cache_mb: usize,
+    /// 布隆过滤器每个键的位数，None 表示不启用
+    pub bloom_bits_per_key: Option<f64>,
+    pub compaction_style: DBCompactionStyle,
+}
+
+impl ColumnFamilyConfig {
+    fn options(&self) -> Options {
+        let mut opts = Options::default();
+        opts.set_compression_type(self.compression);
+        opts.set_compaction_style(self.compaction_style);
+        opts.set_prefix_extractor(SliceTransform::create_fixed_prefix(PREFIX_LEN));
+        opts.set_memtable_prefix_bloom_ratio(0.1);
+
+        let mut block_opts = BlockBasedOptions::default();
+        if self.block_cache_mb > 0 {
+            block_opts.set_block_cache(&Cache::new_lru_cache(self.block_cache_mb << 20));
+        }
+        if let Some(bits) = self.bloom_bits_per_key {
+            block_opts.set_bloom_filter(bits, false);
+        }
+        opts.set_block_based_table_factory(&block_opts);
+        opts
+    }
+}
+
+/// 存储配置 - 每类数据一个列族，分别设置压缩、缓存、布隆过滤器和压实方式
+#[derive(Debug, Clone)]
+pub struct StorageConfig {
+    /// 新写入 Tick 和 K 线的编码格式
+    pub format: ValueFormat,
+    /// 数据量最大、读取最少，默认 ZSTD
+    pub ticks: ColumnFamilyConfig,
+    pub klines: ColumnFamilyConfig,
+    pub quotes: ColumnFamilyConfig,
+    pub meta: ColumnFamilyConfig,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        StorageConfig {
+            format: ValueFormat::default(),
+            ticks: ColumnFamilyConfig {
+                compression: DBCompressionType::Zstd,
+                block_cache_mb: 64,
+                bloom_bits_per_key: Some(10.0),
+                compaction_style: DBCompactionStyle::Level,
+            },
+            klines: ColumnFamilyConfig {
+                compression: DBCompressionType::Lz4,
+                block_cache_mb: 128,
+                bloom_bits_per_key: Some(10.0),
+                compaction_style: DBCompactionStyle::Level,
+            },
+            quotes: ColumnFamilyConfig {
+                compression: DBCompressionType::Lz4,
+                block_cache_mb: 32,
+                bloom_bits_per_key: Some(10.0),
+                compaction_style: DBCompactionStyle::Level,
+            },
+            meta: ColumnFamilyConfig {
+                compression: DBCompressionType::None,
+                block_cache_mb: 0,
+                bloom_bits_per_key: None,
+                compaction_style: DBCompactionStyle::Level,
+            },
+        }
+    }
+}
+
 /// RocksDB 存储层
+///
+/// 键为大端二进制，按时间排序：
+/// - Tick: `0x01 | symbol_id(u32) | timestamp(u64) | trade_id(u64)`
+/// - trade_id 索引: `0x02 | symbol_id | trade_id` -> timestamp
+/// - K 线: `0x03 | symbol_id | interval(u64) | timestamp(u64)`
+/// - 成交量分布: `0x04 | symbol_id | interval | timestamp`
+///
+/// Tick 和索引、K 线、成交量分布、元数据分别存放在 `ticks`/`klines`/`quotes`/`meta` 列族，
+/// 品种名与 id 的映射保存在 `0x00` 元数据键中。前 5 字节作为前缀提取器，
+/// 同一品种、同一类型的数据共享前缀
 pub struct TickStorage {
     db: Arc<DB>,
+    /// 新写入 Tick 和 K 线的编码格式，读取时自动识别
+    format: ValueFormat,
+    /// 品种名 -> 品种 id
+    symbols: Arc<RwLock<HashMap<String, u32>>>,
+    /// 打开数据库的选项，与数据库共享统计计数器
+    options: Arc<Options>,
+}
+
+/// 数据库的打开方式
+enum OpenMode<'a> {
+    /// 读写，持有文件锁
+    Primary,
+    /// 从实例，日志写入给定目录
+    Secondary(&'a Path),
 }
 
 impl TickStorage {
-    /// 创建或打开数据库
+    /// 创建或打开数据库，新记录使用二进制编码
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        TickStorage::open_with_format(path, ValueFormat::default())
+    }
+
+    /// 创建或打开数据库，指定新记录的编码格式
+    pub fn open_with_format<P: AsRef<Path>>(path: P, format: ValueFormat) -> Result<Self> {
+        TickStorage::open_with_config(path, StorageConfig { format, ..StorageConfig::default() })
+    }
+
+    /// 按配置创建或打开数据库
+    ///
+    /// 默认列族中仍有未迁移的旧数据时返回错误：此时读取会得到空结果，
+    /// 新分配的品种 id 也可能与旧数据冲突，需要先执行 `mdi-cli migrate`
+    pub fn open_with_config<P: AsRef<Path>>(path: P, config: StorageConfig) -> Result<Self> {
+        let path = path.as_ref();
+        let storage = TickStorage::open_unchecked(path, config)?;
+        if storage.has_legacy_keys()? {
+            return Err(MdiError::StorageError(format!(
+                "Database {} contains records from an older layout, run `mdi-cli migrate {}` first",
+                path.display(),
+                path.display()
+            )));
+        }
+        Ok(storage)
+    }
+
+    /// 打开数据库用于 `migrate_keys`，不检查默认列族中的旧数据
+    pub fn open_for_migration<P: AsRef<Path>>(path: P) -> Result<Self> {
+        TickStorage::open_unchecked(path.as_ref(), StorageConfig::default())
+    }
+
+    /// 以从实例（secondary）方式打开数据库，不获取文件锁，可在采集进程写入时创建检查点和备份
+    ///
+    /// `secondary_path` 存放从实例自己的日志，不能与其他从实例共用。打开后立即追上主实例，
+    /// 包括尚未刷盘的 WAL；之后主实例的写入需要 `catch_up` 才可见，写入操作返回错误
+    pub fn open_as_secondary<P: AsRef<Path>, Q: AsRef<Path>>(path: P, secondary_path: Q) -> Result<Self> {
+        let storage = TickStorage::open_with_mode(
+            path.as_ref(),
+            StorageConfig::default(),
+            OpenMode::Secondary(secondary_path.as_ref()),
+        )?;
+        storage.catch_up()?;
+        Ok(storage)
+    }
+
+    /// 从实例追上主实例的最新写入，并重新加载品种注册表
+    pub fn catch_up(&self) -> Result<()> {
+        self.db.try_catch_up_with_primary().map_err(|e| {
+            MdiError::StorageError(format!("Failed to catch up with primary: {}", e))
+        })?;
+        self.load_symbols()
+    }
+
+    fn open_unchecked(path: &Path, config: StorageConfig) -> Result<Self> {
+        TickStorage::open_with_mode(path, config, OpenMode::Primary)
+    }
+
+    fn open_with_mode(path: &Path, config: StorageConfig, mode: OpenMode) -> Result<Self> {
         let mut opts = Options::default();
         opts.create_if_missing(true);
         opts.create_missing_column_families(true);
+        // 块缓存命中和写入停顿等计数器，供 get_stats 读取
+        opts.enable_statistics();
 
-        let db = DB::open(&opts, path).map_err(|e| {
-            MdiError::StorageError(format!("Failed to open RocksDB: {}", e))
-        })?;
+        let cfs = vec![
+            ColumnFamilyDescriptor::new(CF_TICKS, config.ticks.options()),
+            ColumnFamilyDescriptor::new(CF_KLINES, config.klines.options()),
+            ColumnFamilyDescriptor::new(CF_QUOTES, config.quotes.options()),
+            ColumnFamilyDescriptor::new(CF_META, config.meta.options()),
+        ];
+        let db = match mode {
+            OpenMode::Primary => DB::open_cf_descriptors(&opts, path, cfs),
+            OpenMode::Secondary(secondary_path) => {
+                // 从实例要求保持所有 SST 文件打开，主实例压实删除文件后仍可读取
+                opts.set_max_open_files(-1);
+                DB::open_cf_descriptors_as_secondary(&opts, path, secondary_path, cfs)
+            }
+        }
+        .map_err(|e| MdiError::StorageError(format!("Failed to open RocksDB: {}", e)))?;
 
-        Ok(TickStorage {
+        let storage = TickStorage {
             db: Arc::new(db),
-        })
+            format: config.format,
+            symbols: Arc::new(RwLock::new(HashMap::new())),
+            options: Arc::new(opts),
+        };
+        storage.load_symbols()?;
+        Ok(storage)
     }
 
-    /// 存储单个 Tick
-    pub fn write_tick(&self, tick: &Tick) -> Result<()> {
-        let key = format!("tick:{}:{}", tick.symbol, tick.trade_id);
-        let value = serde_json::to_vec(tick).map_err(|e| {
-            MdiError::StorageError(format!("Serialization error: {}", e))
-        })?;
+    /// 从元数据键加载品种注册表
+    fn load_symbols(&self) -> Result<()> {
+        let mut symbols = self.symbols.write();
+        for result in self.db.iterator_cf_opt(
+            self.cf(CF_META),
+            total_order(),
+            IteratorMode::From(SYMBOL_META_PREFIX, rocksdb::Direction::Forward),
+        ) {
+            let (key, value) = result.map_err(|e| {
+                MdiError::StorageError(format!("Iterator error: {}", e))
+            })?;
+            if !key.starts_with(SYMBOL_META_PREFIX) {
+                break;
+            }
+            let symbol = String::from_utf8_lossy(&key[SYMBOL_META_PREFIX.len()..]).into_owned();
+            let id = value
+                .as_ref()
+                .try_into()
+                .map(u32::from_be_bytes)
+                .map_err(|_| MdiError::StorageError(format!("Invalid symbol id for {}", symbol)))?;
+            symbols.insert(symbol, id);
+        }
+        Ok(())
+    }
 
-        self.db.put(&key, &value).map_err(|e| {
+    /// 默认列族中是否有 `migrate_keys` 需要迁移的记录
+    fn has_legacy_keys(&self) -> Result<bool> {
+        for result in self.db.iterator_opt(IteratorMode::Start, total_order()) {
+            let (key, _) = result.map_err(|e| {
+                MdiError::StorageError(format!("Iterator error: {}", e))
+            })?;
+            let legacy = key.starts_with(SYMBOL_META_PREFIX)
+                || key.starts_with(b"tick:")
+                || key.starts_with(b"kline:")
+                || key.starts_with(b"profile:")
+                || matches!(key.first(), Some(&(KEY_TICK..=KEY_PROFILE)));
+            if legacy {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// 查询品种 id，未注册返回 None
+    fn lookup_symbol(&self, symbol: &str) -> Option<u32> {
+        self.symbols.read().get(symbol).copied()
+    }
+
+    /// 获取品种 id，未注册时分配新 id 并持久化
+    fn register_symbol(&self, symbol: &str) -> Result<u32> {
+        if let Some(id) = self.lookup_symbol(symbol) {
+            return Ok(id);
+        }
+
+        let mut symbols = self.symbols.write();
+        if let Some(id) = symbols.get(symbol) {
+            return Ok(*id);
+        }
+        let id = symbols.values().max().map_or(0, |max| max + 1);
+        let mut key = SYMBOL_META_PREFIX.to_vec();
+        key.extend_from_slice(symbol.as_bytes());
+        self.db.put_cf(self.cf(CF_META), &key, id.to_be_bytes()).map_err(|e| {
             MdiError::StorageError(format!("Put error: {}", e))
         })?;
+        symbols.insert(symbol.to_string(), id);
+        Ok(id)
+    }
 
-        Ok(())
+    /// 读取 `meta` 列族中的元数据，键为 `\x00` + `name`（`symbol:` 前缀保留给品种注册表）
+    pub fn read_meta(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        self.get(CF_META, &meta_key(name))
+    }
+
+    /// 写入元数据
+    pub fn write_meta(&self, name: &str, value: &[u8]) -> Result<()> {
+        self.db.put_cf(self.cf(CF_META), meta_key(name), value).map_err(|e| {
+            MdiError::StorageError(format!("Put error: {}", e))
+        })
+    }
+
+    /// 已注册的品种
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<_> = self.symbols.read().keys().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// 存储单个 Tick
+    pub fn write_tick(&self, tick: &Tick) -> Result<()> {
+        self.write_ticks(std::slice::from_ref(tick))
     }
 
     /// 批量存储 Tick
     pub fn write_ticks(&self, ticks: &[Tick]) -> Result<()> {
         let mut batch = rocksdb::WriteBatch::default();
+        let mut written = HashMap::new();
 
         for tick in ticks {
-            let key = format!("tick:{}:{}", tick.symbol, tick.trade_id);
-            let value = serde_json::to_vec(tick).map_err(|e| {
-                MdiError::StorageError(format!("Serialization error: {}", e))
-            })?;
-            batch.put(&key, &value);
+            self.put_tick(&mut batch, tick, &mut written)?;
         }
 
         self.db.write(batch).map_err(|e| {
@@ -58,47 +344,97 @@ impl TickStorage {
         Ok(())
     }
 
+    /// Tick 及其 trade_id 索引写入批次
+    ///
+    /// 同一 trade_id 以不同时间戳重写时删除旧的 Tick 键，`written` 记录本批次中已写入的索引，
+    /// 批次内的重写同样生效
+    fn put_tick(
+        &self,
+        batch: &mut rocksdb::WriteBatch,
+        tick: &Tick,
+        written: &mut HashMap<(u32, u64), u64>,
+    ) -> Result<()> {
+        let symbol_id = self.register_symbol(&tick.symbol)?;
+        let value = codec::encode(tick, self.format)?;
+        let cf = self.cf(CF_TICKS);
+        let index_key = trade_index_key(symbol_id, tick.trade_id);
+
+        let old_timestamp = match written.insert((symbol_id, tick.trade_id), tick.timestamp) {
+            Some(timestamp) => Some(timestamp),
+            None => self.get(CF_TICKS, &index_key)?.map(|value| read_u64(&value)).transpose()?,
+        };
+        if let Some(old_timestamp) = old_timestamp.filter(|&timestamp| timestamp != tick.timestamp) {
+            batch.delete_cf(cf, tick_key(symbol_id, old_timestamp, tick.trade_id));
+        }
+
+        batch.put_cf(cf, tick_key(symbol_id, tick.timestamp, tick.trade_id), &value);
+        batch.put_cf(cf, index_key, tick.timestamp.to_be_bytes());
+        Ok(())
+    }
+
     /// 读取特定 Tick
     pub fn read_tick(&self, symbol: &str, trade_id: u64) -> Result<Option<Tick>> {
-        let key = format!("tick:{}:{}", symbol, trade_id);
-        
-        match self.db.get(&key).map_err(|e| {
-            MdiError::StorageError(format!("Get error: {}", e))
-        })? {
-            Some(value) => {
-                let tick = serde_json::from_slice(&value).map_err(|e| {
-                    MdiError::StorageError(format!("Deserialization error: {}", e))
-                })?;
-                Ok(Some(tick))
-            }
+        let symbol_id = match self.lookup_symbol(symbol) {
+            Some(symbol_id) => symbol_id,
+            None => return Ok(None),
+        };
+
+        let timestamp = match self.get(CF_TICKS, &trade_index_key(symbol_id, trade_id))? {
+            Some(value) => read_u64(&value)?,
+            None => return Ok(None),
+        };
+
+        match self.get(CF_TICKS, &tick_key(symbol_id, timestamp, trade_id))? {
+            Some(value) => Ok(Some(codec::decode(&value)?)),
             None => Ok(None),
         }
     }
 
     /// 存储 K 线
     pub fn write_kline(&self, kline: &KLine) -> Result<()> {
-        let key = format!("kline:{}:{}:{}", kline.symbol, kline.interval, kline.timestamp);
-        let value = serde_json::to_vec(kline).map_err(|e| {
-            MdiError::StorageError(format!("Serialization error: {}", e))
-        })?;
+        self.write_klines(std::slice::from_ref(kline))
+    }
 
-        self.db.put(&key, &value).map_err(|e| {
-            MdiError::StorageError(format!("Put error: {}", e))
+    /// 批量存储 K 线
+    pub fn write_klines(&self, klines: &[KLine]) -> Result<()> {
+        let mut batch = rocksdb::WriteBatch::default();
+
+        for kline in klines {
+            let symbol_id = self.register_symbol(&kline.symbol)?;
+            let value = codec::encode(kline, self.format)?;
+            batch.put_cf(self.cf(CF_KLINES), bar_key(KEY_KLINE, symbol_id, kline.interval, kline.timestamp), &value);
+        }
+
+        self.db.write(batch).map_err(|e| {
+            MdiError::StorageError(format!("Batch write error: {}", e))
         })?;
 
         Ok(())
     }
 
-    /// 批量存储 K 线
-    pub fn write_klines(&self, klines: &[KLine]) -> Result<()> {
+    /// 读取特定 K 线
+    pub fn read_kline(&self, symbol: &str, interval: u64, timestamp: u64) -> Result<Option<KLine>> {
+        let symbol_id = match self.lookup_symbol(symbol) {
+            Some(symbol_id) => symbol_id,
+            None => return Ok(None),
+        };
+
+        match self.get(CF_KLINES, &bar_key(KEY_KLINE, symbol_id, interval, timestamp))? {
+            Some(value) => Ok(Some(codec::decode(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 批量存储成交量分布
+    pub fn write_profiles(&self, profiles: &[VolumeProfile]) -> Result<()> {
         let mut batch = rocksdb::WriteBatch::default();
 
-        for kline in klines {
-            let key = format!("kline:{}:{}:{}", kline.symbol, kline.interval, kline.timestamp);
-            let value = serde_json::to_vec(kline).map_err(|e| {
+        for profile in profiles {
+            let symbol_id = self.register_symbol(&profile.symbol)?;
+            let value = serde_json::to_vec(profile).map_err(|e| {
                 MdiError::StorageError(format!("Serialization error: {}", e))
             })?;
-            batch.put(&key, &value);
+            batch.put_cf(self.cf(CF_QUOTES), bar_key(KEY_PROFILE, symbol_id, profile.interval, profile.timestamp), &value);
         }
 
         self.db.write(batch).map_err(|e| {
@@ -108,112 +444,922 @@ impl TickStorage {
         Ok(())
     }
 
-    /// 读取特定 K 线
-    pub fn read_kline(&self, symbol: &str, interval: u64, timestamp: u64) -> Result<Option<KLine>> {
-        let key = format!("kline:{}:{}:{}", symbol, interval, timestamp);
-        
-        match self.db.get(&key).map_err(|e| {
-            MdiError::StorageError(format!("Get error: {}", e))
-        })? {
+    /// 读取特定成交量分布
+    pub fn read_profile(&self, symbol: &str, interval: u64, timestamp: u64) -> Result<Option<VolumeProfile>> {
+        let symbol_id = match self.lookup_symbol(symbol) {
+            Some(symbol_id) => symbol_id,
+            None => return Ok(None),
+        };
+
+        match self.get(CF_QUOTES, &bar_key(KEY_PROFILE, symbol_id, interval, timestamp))? {
             Some(value) => {
-                let kline = serde_json::from_slice(&value).map_err(|e| {
+                let profile = serde_json::from_slice(&value).map_err(|e| {
                     MdiError::StorageError(format!("Deserialization error: {}", e))
                 })?;
-                Ok(Some(kline))
+                Ok(Some(profile))
             }
             None => Ok(None),
         }
     }
 
-    /// 读取指定品种的所有 Tick
+    /// 读取指定品种最早的 `limit` 个 Tick，按时间排序
     pub fn read_ticks_by_symbol(&self, symbol: &str, limit: usize) -> Result<Vec<Tick>> {
-        let prefix = format!("tick:{}:", symbol);
-        let iter = self.db.iterator(IteratorMode::From(prefix.as_bytes(), rocksdb::Direction::Forward));
-        
+        let symbol_id = match self.lookup_symbol(symbol) {
+            Some(symbol_id) if limit > 0 => symbol_id,
+            _ => return Ok(Vec::new()),
+        };
+
+        let prefix = key_prefix(KEY_TICK, symbol_id);
         let mut ticks = Vec::new();
-        for result in iter.take(limit) {
-            match result {
-                Ok((key, value)) => {
-                    let key_str = String::from_utf8_lossy(&key);
-                    if !key_str.starts_with(&prefix) {
-                        break;
-                    }
+        self.scan_prefix(CF_TICKS, &prefix, &prefix, |key, value| {
+            match codec::decode::<Tick>(value) {
+                Ok(tick) => ticks.push(tick),
+                Err(e) => skip_corrupt(CF_TICKS, key, &e),
+            }
+            ticks.len() < limit
+        })?;
 
-                    if let Ok(tick) = serde_json::from_slice::<Tick>(&value) {
-                        ticks.push(tick);
-                    }
-                }
-                Err(_) => break,
+        Ok(ticks)
+    }
+
+    /// 读取指定品种时间戳（毫秒）不早于 `since` 的 Tick，按时间和 trade_id 排序
+    pub fn read_ticks_since(&self, symbol: &str, since: u64) -> Result<Vec<Tick>> {
+        let symbol_id = match self.lookup_symbol(symbol) {
+            Some(symbol_id) => symbol_id,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut ticks = Vec::new();
+        self.scan_prefix(CF_TICKS, &tick_key(symbol_id, since, 0), &key_prefix(KEY_TICK, symbol_id), |key, value| {
+            match codec::decode::<Tick>(value) {
+                Ok(tick) => ticks.push(tick),
+                Err(e) => skip_corrupt(CF_TICKS, key, &e),
             }
-        }
+            true
+        })?;
 
         Ok(ticks)
     }
 
-    /// 读取指定品种的所有 K 线
+    /// 读取指定品种、周期的所有 K 线，按时间排序
     pub fn read_klines_by_symbol(&self, symbol: &str, interval: u64) -> Result<Vec<KLine>> {
-        let prefix = format!("kline:{}:{}:", symbol, interval);
-        let iter = self.db.iterator(IteratorMode::From(prefix.as_bytes(), rocksdb::Direction::Forward));
-        
+        let symbol_id = match self.lookup_symbol(symbol) {
+            Some(symbol_id) => symbol_id,
+            None => return Ok(Vec::new()),
+        };
+
+        let prefix = bar_prefix(KEY_KLINE, symbol_id, interval);
         let mut klines = Vec::new();
+        self.scan_prefix(CF_KLINES, &prefix, &prefix, |key, value| {
+            match codec::decode::<KLine>(value) {
+                Ok(kline) => klines.push(kline),
+                Err(e) => skip_corrupt(CF_KLINES, key, &e),
+            }
+            true
+        })?;
+
+        Ok(klines)
+    }
+
+    /// 读取指定品种 `[from_ms, to_ms)` 时间范围内的 Tick，按时间和 trade_id 排序
+    pub fn read_ticks_range(
+        &self,
+        symbol: &str,
+        from_ms: u64,
+        to_ms: u64,
+        options: &RangeOptions,
+    ) -> Result<Page<Tick>> {
+        let symbol_id = match self.lookup_symbol(symbol) {
+            Some(symbol_id) => symbol_id,
+            None => return Ok(Page::default()),
+        };
+
+        let mut lower = tick_key(symbol_id, from_ms, 0);
+        let mut upper = tick_key(symbol_id, to_ms, 0);
+        if let Some(cursor) = &options.cursor {
+            match options.direction {
+                ScanDirection::Forward => {
+                    let after = match cursor.trade_id.checked_add(1) {
+                        Some(trade_id) => tick_key(symbol_id, cursor.timestamp, trade_id),
+                        None => tick_key(symbol_id, cursor.timestamp.saturating_add(1), 0),
+                    };
+                    lower = lower.max(after);
+                }
+                ScanDirection::Reverse => {
+                    upper = upper.min(tick_key(symbol_id, cursor.timestamp, cursor.trade_id));
+                }
+            }
+        }
+
+        self.scan_range(CF_TICKS, &lower, &upper, options, |value| {
+            let tick = codec::decode::<Tick>(value)?;
+            let cursor = Cursor { timestamp: tick.timestamp, trade_id: tick.trade_id };
+            Ok((tick, cursor))
+        })
+    }
+
+    /// 读取指定品种、周期 `[from, to)` 时间范围（K 线开始时间，秒）内的 K 线
+    pub fn read_klines_range(
+        &self,
+        symbol: &str,
+        interval: u64,
+        from: u64,
+        to: u64,
+        options: &RangeOptions,
+    ) -> Result<Page<KLine>> {
+        let symbol_id = match self.lookup_symbol(symbol) {
+            Some(symbol_id) => symbol_id,
+            None => return Ok(Page::default()),
+        };
+
+        let mut lower = bar_key(KEY_KLINE, symbol_id, interval, from);
+        let mut upper = bar_key(KEY_KLINE, symbol_id, interval, to);
+        if let Some(cursor) = &options.cursor {
+            match options.direction {
+                ScanDirection::Forward => {
+                    let after = bar_key(KEY_KLINE, symbol_id, interval, cursor.timestamp.saturating_add(1));
+                    lower = lower.max(after);
+                }
+                ScanDirection::Reverse => {
+                    upper = upper.min(bar_key(KEY_KLINE, symbol_id, interval, cursor.timestamp));
+                }
+            }
+        }
+
+        self.scan_range(CF_KLINES, &lower, &upper, options, |value| {
+            let kline = codec::decode::<KLine>(value)?;
+            let cursor = Cursor { timestamp: kline.timestamp, trade_id: 0 };
+            Ok((kline, cursor))
+        })
+    }
+
+    /// 按方向遍历 `[lower, upper)` 内的键，最多返回 `limit` 条；达到上限时返回下一页游标
+    fn scan_range<T, F>(
+        &self,
+        cf: &str,
+        lower: &[u8],
+        upper: &[u8],
+        options: &RangeOptions,
+        mut decode: F,
+    ) -> Result<Page<T>>
+    where
+        F: FnMut(&[u8]) -> Result<(T, Cursor)>,
+    {
+        let mut page = Page::default();
+        if lower >= upper || options.limit == Some(0) {
+            return Ok(page);
+        }
+
+        let mode = match options.direction {
+            ScanDirection::Forward => IteratorMode::From(lower, rocksdb::Direction::Forward),
+            ScanDirection::Reverse => IteratorMode::From(upper, rocksdb::Direction::Reverse),
+        };
+
+        for result in self.db.iterator_cf_opt(self.cf(cf), total_order(), mode) {
+            let (key, value) = result.map_err(|e| {
+                MdiError::StorageError(format!("Iterator error: {}", e))
+            })?;
+            match options.direction {
+                ScanDirection::Forward if &*key >= upper => break,
+                ScanDirection::Reverse if &*key < lower => break,
+                // 反向定位可能落在上界本身
+                ScanDirection::Reverse if &*key >= upper => continue,
+                _ => {}
+            }
+
+            let (item, cursor) = decode(&value)?;
+            page.items.push(item);
+            if options.limit.is_some_and(|limit| page.items.len() >= limit) {
+                page.next_cursor = Some(cursor);
+                break;
+            }
+        }
+
+        Ok(page)
+    }
+
+    /// 从 `start` 开始正向遍历以 `prefix` 开头的键，`f` 返回 false 时停止
+    fn scan_prefix<F>(&self, cf: &str, start: &[u8], prefix: &[u8], mut f: F) -> Result<()>
+    where
+        F: FnMut(&[u8], &[u8]) -> bool,
+    {
+        let mut opts = ReadOptions::default();
+        opts.set_prefix_same_as_start(true);
+        let iter = self.db.iterator_cf_opt(
+            self.cf(cf),
+            opts,
+            IteratorMode::From(start, rocksdb::Direction::Forward),
+        );
+
         for result in iter {
-            match result {
-                Ok((key, value)) => {
-                    let key_str = String::from_utf8_lossy(&key);
-                    if !key_str.starts_with(&prefix) {
-                        break;
+            let (key, value) = result.map_err(|e| {
+                MdiError::StorageError(format!("Iterator error: {}", e))
+            })?;
+            if !key.starts_with(prefix) || !f(&key, &value) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn get(&self, cf: &str, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.db.get_cf(self.cf(cf), key).map_err(|e| {
+            MdiError::StorageError(format!("Get error: {}", e))
+        })
+    }
+
+    /// 列族句柄，打开时已创建所有列族
+    fn cf(&self, name: &str) -> &ColumnFamily {
+        self.db.cf_handle(name).expect("column family created on open")
+    }
+
+    /// 将 Tick 和 K 线记录重写为当前编码格式，返回扫描和重写的记录数
+    ///
+    /// 可重复执行，已是当前格式的记录不会被改写
+    pub fn migrate_values(&self) -> Result<MigrationStats> {
+        let mut stats = MigrationStats::default();
+
+        for cf_name in [CF_TICKS, CF_KLINES] {
+            let cf = self.cf(cf_name);
+            let mut batch = rocksdb::WriteBatch::default();
+
+            for result in self.db.iterator_cf_opt(cf, total_order(), IteratorMode::Start) {
+                let (key, value) = result.map_err(|e| {
+                    MdiError::StorageError(format!("Iterator error: {}", e))
+                })?;
+                let is_tick = key.first() == Some(&KEY_TICK);
+                if !is_tick && key.first() != Some(&KEY_KLINE) {
+                    continue;
+                }
+                stats.scanned += 1;
+                if ValueFormat::detect(&value) == Some(self.format) {
+                    continue;
+                }
+
+                let encoded = if is_tick {
+                    codec::encode(&codec::decode::<Tick>(&value)?, self.format)?
+                } else {
+                    codec::encode(&codec::decode::<KLine>(&value)?, self.format)?
+                };
+                stats.migrated += 1;
+                batch.put_cf(cf, &key, &encoded);
+
+                if batch.len() >= BATCH_CHUNK {
+                    self.write_batch(std::mem::take(&mut batch))?;
+                }
+            }
+
+            if !batch.is_empty() {
+                self.write_batch(batch)?;
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// 将默认列族中的旧数据迁移到各自的列族，返回扫描和迁移的记录数
+    ///
+    /// 旧版字符串键（`tick:SYM:ID`、`kline:SYM:INTERVAL:TS`、`profile:SYM:INTERVAL:TS`）
+    /// 同时转换为二进制键并按当前格式重新编码。可重复执行，迁移完成后旧键被删除
+    pub fn migrate_keys(&self) -> Result<MigrationStats> {
+        let mut stats = MigrationStats::default();
+
+        // 先迁移品种注册表，保证已有二进制键中的品种 id 不变
+        let mut batch = rocksdb::WriteBatch::default();
+        for result in self.db.iterator_opt(
+            IteratorMode::From(SYMBOL_META_PREFIX, rocksdb::Direction::Forward),
+            total_order(),
+        ) {
+            let (key, value) = result.map_err(|e| {
+                MdiError::StorageError(format!("Iterator error: {}", e))
+            })?;
+            if !key.starts_with(SYMBOL_META_PREFIX) {
+                break;
+            }
+            batch.put_cf(self.cf(CF_META), &key, &value);
+            batch.delete(&key);
+            stats.scanned += 1;
+            stats.migrated += 1;
+        }
+        if !batch.is_empty() {
+            self.write_batch(batch)?;
+            self.load_symbols()?;
+        }
+
+        let mut batch = rocksdb::WriteBatch::default();
+        let mut written = HashMap::new();
+        for result in self.db.iterator_opt(IteratorMode::Start, total_order()) {
+            let (key, value) = result.map_err(|e| {
+                MdiError::StorageError(format!("Iterator error: {}", e))
+            })?;
+
+            if key.starts_with(b"tick:") {
+                let tick = codec::decode::<Tick>(&value)?;
+                self.put_tick(&mut batch, &tick, &mut written)?;
+            } else if key.starts_with(b"kline:") {
+                let kline = codec::decode::<KLine>(&value)?;
+                let symbol_id = self.register_symbol(&kline.symbol)?;
+                let encoded = codec::encode(&kline, self.format)?;
+                batch.put_cf(self.cf(CF_KLINES), bar_key(KEY_KLINE, symbol_id, kline.interval, kline.timestamp), &encoded);
+            } else if key.starts_with(b"profile:") {
+                let profile: VolumeProfile = serde_json::from_slice(&value)?;
+                let symbol_id = self.register_symbol(&profile.symbol)?;
+                batch.put_cf(self.cf(CF_QUOTES), bar_key(KEY_PROFILE, symbol_id, profile.interval, profile.timestamp), &value);
+            } else {
+                // 列族拆分前写入的二进制键，原样移动
+                let cf = match key.first() {
+                    Some(&KEY_TICK) | Some(&KEY_TRADE_INDEX) => CF_TICKS,
+                    Some(&KEY_KLINE) => CF_KLINES,
+                    Some(&KEY_PROFILE) => CF_QUOTES,
+                    _ => continue,
+                };
+                batch.put_cf(self.cf(cf), &key, &value);
+            }
+            batch.delete(&key);
+            stats.scanned += 1;
+            stats.migrated += 1;
+
+            if batch.len() >= BATCH_CHUNK {
+                // 已写入的索引之后从数据库读取
+                self.write_batch(std::mem::take(&mut batch))?;
+                written.clear();
+            }
+        }
+
+        if !batch.is_empty() {
+            self.write_batch(batch)?;
+        }
+
+        Ok(stats)
+    }
+
+    fn write_batch(&self, batch: rocksdb::WriteBatch) -> Result<()> {
+        self.db.write(batch).map_err(|e| {
+            MdiError::StorageError(format!("Batch write error: {}", e))
+        })
+    }
+
+    /// 按保留策略删除过期数据，返回删除的记录数
+    ///
+    /// Tick 和 K 线按时间区间 `delete_range`，同时删除对应的 trade_id 索引；
+    /// K 线和成交量分布的时间戳为秒，按 K 线开始时间判断是否过期
+    pub fn purge(&self, policy: &RetentionPolicy, now_ms: u64) -> Result<PurgeReport> {
+        let mut report = PurgeReport::default();
+        let symbols: Vec<(String, u32)> = self
+            .symbols
+            .read()
+            .iter()
+            .map(|(symbol, id)| (symbol.clone(), *id))
+            .collect();
+
+        for (symbol, symbol_id) in symbols {
+            let mut batch = rocksdb::WriteBatch::default();
+            let mut removed = 0;
+
+            if let Some(keep_ms) = policy.ticks {
+                let ticks = self.delete_ticks(&mut batch, symbol_id, 0, RetentionPolicy::cutoff(keep_ms, now_ms))?;
+                report.ticks += ticks;
+                report.trade_index += ticks;
+                removed += ticks * 2;
+            }
+
+            for (&interval, &keep_ms) in &policy.klines {
+                let prefix = bar_prefix(KEY_KLINE, symbol_id, interval);
+                let cutoff = RetentionPolicy::cutoff(keep_ms, now_ms) / 1000;
+                let upper = bar_key(KEY_KLINE, symbol_id, interval, cutoff);
+                let mut klines = 0;
+                self.scan_prefix(CF_KLINES, &prefix, &prefix, |key, _| {
+                    if key >= upper.as_slice() {
+                        return false;
                     }
+                    klines += 1;
+                    true
+                })?;
+                if klines > 0 {
+                    batch.delete_range_cf(self.cf(CF_KLINES), &prefix, &upper);
+                    *report.klines.entry(interval).or_default() += klines;
+                    removed += klines;
+                }
+            }
 
-                    if let Ok(kline) = serde_json::from_slice::<KLine>(&value) {
-                        klines.push(kline);
+            if let Some(keep_ms) = policy.profiles {
+                // 成交量分布跨周期存放，逐条判断
+                let quotes_cf = self.cf(CF_QUOTES);
+                let prefix = key_prefix(KEY_PROFILE, symbol_id);
+                let cutoff = RetentionPolicy::cutoff(keep_ms, now_ms) / 1000;
+                self.scan_prefix(CF_QUOTES, &prefix, &prefix, |key, _| {
+                    let timestamp = u64::from_be_bytes(key[13..21].try_into().expect("profile key"));
+                    if timestamp < cutoff {
+                        batch.delete_cf(quotes_cf, key);
+                        report.profiles += 1;
+                        removed += 1;
                     }
+                    true
+                })?;
+            }
+
+            if removed > 0 {
+                self.write_batch(batch)?;
+                report.symbols.insert(symbol, removed);
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// 删除指定品种 `[from_ms, to_ms)` 内的 Tick 及其 trade_id 索引，返回删除的 Tick 数
+    pub fn delete_ticks_range(&self, symbol: &str, from_ms: u64, to_ms: u64) -> Result<u64> {
+        let symbol_id = match self.lookup_symbol(symbol) {
+            Some(id) => id,
+            None => return Ok(0),
+        };
+        let mut batch = rocksdb::WriteBatch::default();
+        let ticks = self.delete_ticks(&mut batch, symbol_id, from_ms, to_ms)?;
+        if ticks > 0 {
+            self.write_batch(batch)?;
+        }
+        Ok(ticks)
+    }
+
+    /// 删除给定的 Tick 及仍指向它们的 trade_id 索引，其他 Tick 不受影响
+    ///
+    /// 用于归档等先读后删的场景：读取之后写入的同一时间区间的 Tick 会被保留
+    pub fn remove_ticks(&self, ticks: &[Tick]) -> Result<()> {
+        let cf = self.cf(CF_TICKS);
+        let mut batch = rocksdb::WriteBatch::default();
+        for tick in ticks {
+            let symbol_id = match self.lookup_symbol(&tick.symbol) {
+                Some(id) => id,
+                None => continue,
+            };
+            batch.delete_cf(cf, tick_key(symbol_id, tick.timestamp, tick.trade_id));
+            // 同一 trade_id 已重写为其他时间戳时保留索引
+            let index_key = trade_index_key(symbol_id, tick.trade_id);
+            if let Some(value) = self.get(CF_TICKS, &index_key)? {
+                if read_u64(&value)? == tick.timestamp {
+                    batch.delete_cf(cf, index_key);
                 }
-                Err(_) => break,
+            }
+            if batch.len() >= BATCH_CHUNK {
+                self.write_batch(std::mem::take(&mut batch))?;
             }
         }
+        if !batch.is_empty() {
+            self.write_batch(batch)?;
+        }
+        Ok(())
+    }
 
-        Ok(klines)
+    /// 把 `[from_ms, to_ms)` 内 Tick 的删除操作加入 `batch`
+    ///
+    /// 索引删除每满 `BATCH_CHUNK` 条就连同已扫描区间的 Tick 一起提交，
+    /// 剩余部分留在 `batch` 中由调用方提交
+    fn delete_ticks(&self, batch: &mut rocksdb::WriteBatch, symbol_id: u32, from_ms: u64, to_ms: u64) -> Result<u64> {
+        let ticks_cf = self.cf(CF_TICKS);
+        let prefix = key_prefix(KEY_TICK, symbol_id);
+        let lower = tick_key(symbol_id, from_ms, 0);
+        let upper = tick_key(symbol_id, to_ms, 0);
+        let mut chunk_start = lower.clone();
+        let mut ticks = 0;
+        let mut error = None;
+        self.scan_prefix(CF_TICKS, &lower, &prefix, |key, _| {
+            if key >= upper.as_slice() {
+                return false;
+            }
+            if batch.len() >= BATCH_CHUNK {
+                batch.delete_range_cf(ticks_cf, chunk_start.as_slice(), key);
+                if let Err(e) = self.write_batch(std::mem::take(batch)) {
+                    error = Some(e);
+                    return false;
+                }
+                chunk_start = key.to_vec();
+            }
+            let trade_id = u64::from_be_bytes(key[13..21].try_into().expect("tick key"));
+            batch.delete_cf(ticks_cf, trade_index_key(symbol_id, trade_id));
+            ticks += 1;
+            true
+        })?;
+        if let Some(e) = error {
+            return Err(e);
+        }
+        if ticks > 0 {
+            batch.delete_range_cf(ticks_cf, &chunk_start, &upper);
+        }
+        Ok(ticks)
     }
 
-    /// 获取数据库统计信息
-    pub fn get_stats(&self) -> Result<StorageStats> {
-        let property = self.db.property_value("rocksdb.stats")
-            .map_err(|e| MdiError::StorageError(format!("Failed to get stats: {}", e)))?
-            .unwrap_or_default();
+    /// 扫描所有列族，报告无法识别的键、损坏的值、与键不一致的值、trade_id 索引错误和 trade_id 缺口，
+    /// 并用 Tick 核对 K 线的 OHLCV；`repair` 时用 Tick 重新计算并覆盖不一致的 K 线
+    ///
+    /// 只核对时间 K 线，且 K 线区间需在该品种已存储 Tick 的时间范围内；迭代错误直接返回
+    pub fn verify(&self, options: &VerifyOptions) -> Result<VerifyReport> {
+        let mut report = VerifyReport::new(options.max_issues);
+        let names: HashMap<u32, String> = self
+            .symbols
+            .read()
+            .iter()
+            .map(|(symbol, id)| (*id, symbol.clone()))
+            .collect();
 
-        Ok(StorageStats {
-            info: property,
-        })
+        let coverage = self.verify_ticks(&names, &mut report)?;
+        self.verify_klines(&names, &coverage, options, &mut report)?;
+
+        self.verify_cf(CF_QUOTES, &mut report, |key, value, report| {
+            let (symbol_id, interval, timestamp) = match parse_bar_key(KEY_PROFILE, key) {
+                Some(parts) => parts,
+                None => {
+                    report.push(Issue::MalformedKey { cf: CF_QUOTES.to_string(), key: key.to_vec() });
+                    return Ok(());
+                }
+            };
+            let symbol = match names.get(&symbol_id) {
+                Some(symbol) => symbol,
+                None => {
+                    report.push(Issue::UnknownSymbol { cf: CF_QUOTES.to_string(), key: key.to_vec() });
+                    return Ok(());
+                }
+            };
+            match serde_json::from_slice::<VolumeProfile>(value) {
+                Err(e) => report.push(Issue::CorruptValue { cf: CF_QUOTES.to_string(), key: key.to_vec(), error: e.to_string() }),
+                Ok(profile) if profile.symbol != *symbol || profile.interval != interval || profile.timestamp != timestamp => {
+                    report.push(Issue::KeyMismatch {
+                        cf: CF_QUOTES.to_string(),
+                        key: key.to_vec(),
+                        detail: format!("profile {} {} {}", profile.symbol, profile.interval, profile.timestamp),
+                    })
+                }
+                Ok(_) => {}
+            }
+            Ok(())
+        })?;
+
+        self.verify_cf(CF_META, &mut report, |key, value, report| {
+            if key.first() != Some(&0) {
+                report.push(Issue::MalformedKey { cf: CF_META.to_string(), key: key.to_vec() });
+            } else if key.starts_with(SYMBOL_META_PREFIX) && value.len() != 4 {
+                report.push(Issue::CorruptValue {
+                    cf: CF_META.to_string(),
+                    key: key.to_vec(),
+                    error: format!("symbol id of {} bytes", value.len()),
+                });
+            }
+            Ok(())
+        })?;
+
+        Ok(report)
     }
 
-    /// 清空数据库
-    pub fn clear(&self) -> Result<()> {
-        // RocksDB 没有直接的 clear 操作，需要删除所有键
-        let iter = self.db.iterator(IteratorMode::Start);
+    /// 校验 Tick 和 trade_id 索引，返回 symbol_id -> 首末 Tick 时间戳（毫秒）
+    fn verify_ticks(&self, names: &HashMap<u32, String>, report: &mut VerifyReport) -> Result<HashMap<u32, (u64, u64)>> {
+        let mut coverage: HashMap<u32, (u64, u64)> = HashMap::new();
+        let mut last_trade: Option<(u32, u64)> = None;
+
+        self.verify_cf(CF_TICKS, report, |key, value, report| {
+            let issue_cf = || CF_TICKS.to_string();
+            let (kind, symbol_id) = match (key.first(), key.len()) {
+                (Some(&KEY_TICK), 21) | (Some(&KEY_TRADE_INDEX), 13) => (key[0], be_u32(&key[1..5])),
+                _ => {
+                    report.push(Issue::MalformedKey { cf: issue_cf(), key: key.to_vec() });
+                    return Ok(());
+                }
+            };
+            let symbol = match names.get(&symbol_id) {
+                Some(symbol) => symbol,
+                None => {
+                    report.push(Issue::UnknownSymbol { cf: issue_cf(), key: key.to_vec() });
+                    return Ok(());
+                }
+            };
+
+            if kind == KEY_TICK {
+                let timestamp = be_u64(&key[5..13]);
+                let trade_id = be_u64(&key[13..21]);
+                coverage.entry(symbol_id).or_insert((timestamp, timestamp)).1 = timestamp;
+
+                // 同一品种的 trade_id 随时间递增，相邻两笔应连续
+                match last_trade {
+                    Some((last_symbol, last_id)) if last_symbol == symbol_id => {
+                        if trade_id > last_id + 1 {
+                            report.push(Issue::TradeIdGap { symbol: symbol.clone(), from: last_id + 1, to: trade_id - 1 });
+                        }
+                        last_trade = Some((symbol_id, last_id.max(trade_id)));
+                    }
+                    _ => last_trade = Some((symbol_id, trade_id)),
+                }
+
+                match codec::decode::<Tick>(value) {
+                    Err(e) => report.push(Issue::CorruptValue { cf: issue_cf(), key: key.to_vec(), error: e.to_string() }),
+                    Ok(tick) if tick.symbol != *symbol || tick.timestamp != timestamp || tick.trade_id != trade_id => {
+                        report.push(Issue::KeyMismatch {
+                            cf: issue_cf(),
+                            key: key.to_vec(),
+                            detail: format!("tick {} {} {}", tick.symbol, tick.timestamp, tick.trade_id),
+                        })
+                    }
+                    Ok(_) => {}
+                }
+                let indexed = self.get(CF_TICKS, &trade_index_key(symbol_id, trade_id))?;
+                if indexed.and_then(|value| read_u64(&value).ok()) != Some(timestamp) {
+                    report.push(Issue::MissingIndex { symbol: symbol.clone(), trade_id });
+                }
+            } else {
+                let trade_id = be_u64(&key[5..13]);
+                match read_u64(value) {
+                    Err(e) => report.push(Issue::CorruptValue { cf: issue_cf(), key: key.to_vec(), error: e.to_string() }),
+                    Ok(timestamp) => {
+                        if self.get(CF_TICKS, &tick_key(symbol_id, timestamp, trade_id))?.is_none() {
+                            report.push(Issue::OrphanIndex { symbol: symbol.clone(), trade_id, timestamp });
+                        }
+                    }
+                }
+            }
+            Ok(())
+        })?;
+
+        Ok(coverage)
+    }
+
+    /// 校验 K 线，按需用 Tick 核对并修复
+    fn verify_klines(
+        &self,
+        names: &HashMap<u32, String>,
+        coverage: &HashMap<u32, (u64, u64)>,
+        options: &VerifyOptions,
+        report: &mut VerifyReport,
+    ) -> Result<()> {
         let mut batch = rocksdb::WriteBatch::default();
-        let mut count = 0;
 
-        for result in iter {
-            if let Ok((key, _)) = result {
-                batch.delete(&key);
-                count += 1;
-                
-                if count >= 10000 {
-                    self.db.write(batch).map_err(|e| {
-                        MdiError::StorageError(format!("Batch delete error: {}", e))
-                    })?;
-                    batch = rocksdb::WriteBatch::default();
-                    count = 0;
+        self.verify_cf(CF_KLINES, report, |key, value, report| {
+            let issue_cf = || CF_KLINES.to_string();
+            let (symbol_id, interval, timestamp) = match parse_bar_key(KEY_KLINE, key) {
+                Some(parts) => parts,
+                None => {
+                    report.push(Issue::MalformedKey { cf: issue_cf(), key: key.to_vec() });
+                    return Ok(());
+                }
+            };
+            let symbol = match names.get(&symbol_id) {
+                Some(symbol) => symbol,
+                None => {
+                    report.push(Issue::UnknownSymbol { cf: issue_cf(), key: key.to_vec() });
+                    return Ok(());
+                }
+            };
+            let stored = match codec::decode::<KLine>(value) {
+                Ok(kline) => kline,
+                Err(e) => {
+                    report.push(Issue::CorruptValue { cf: issue_cf(), key: key.to_vec(), error: e.to_string() });
+                    return Ok(());
                 }
+            };
+            if stored.symbol != *symbol || stored.interval != interval || stored.timestamp != timestamp {
+                report.push(Issue::KeyMismatch {
+                    cf: issue_cf(),
+                    key: key.to_vec(),
+                    detail: format!("kline {} {} {}", stored.symbol, stored.interval, stored.timestamp),
+                });
+                return Ok(());
             }
+
+            // 信息驱动 K 线和 Heikin-Ashi 不能按时间区间重新计算
+            if !options.check_bars || Interval::from_id(interval).is_none() {
+                return Ok(());
+            }
+            let (from_ms, to_ms) = (timestamp * 1000, stored.close_time * 1000);
+            match coverage.get(&symbol_id) {
+                Some(&(first, last)) if first <= from_ms && to_ms <= last => {}
+                _ => return Ok(()),
+            }
+
+            let mut ticks = Vec::new();
+            let upper = tick_key(symbol_id, to_ms, 0);
+            self.scan_prefix(CF_TICKS, &tick_key(symbol_id, from_ms, 0), &key_prefix(KEY_TICK, symbol_id), |key, value| {
+                if key >= upper.as_slice() {
+                    return false;
+                }
+                // 损坏的 Tick 已在前面报告
+                if let Ok(tick) = codec::decode::<Tick>(value) {
+                    ticks.push(tick);
+                }
+                true
+            })?;
+
+            report.bars_checked += 1;
+            let rebuilt = verify::rebuild_bar(&stored, &ticks);
+            let fields = verify::bar_differences(&stored, rebuilt.as_ref());
+            if fields.is_empty() {
+                return Ok(());
+            }
+            report.push(Issue::BarMismatch { symbol: symbol.clone(), interval, timestamp, fields });
+            if let (true, Some(rebuilt)) = (options.repair, rebuilt) {
+                batch.put_cf(self.cf(CF_KLINES), key, codec::encode(&rebuilt, self.format)?);
+                report.bars_repaired += 1;
+            }
+            Ok(())
+        })?;
+
+        if !batch.is_empty() {
+            self.write_batch(batch)?;
         }
+        Ok(())
+    }
 
-        if count > 0 {
-            self.db.write(batch).map_err(|e| {
-                MdiError::StorageError(format!("Batch delete error: {}", e))
+    /// 遍历整个列族并计数，迭代错误直接返回
+    fn verify_cf<F>(&self, cf: &str, report: &mut VerifyReport, mut f: F) -> Result<()>
+    where
+        F: FnMut(&[u8], &[u8], &mut VerifyReport) -> Result<()>,
+    {
+        for result in self.db.iterator_cf_opt(self.cf(cf), total_order(), IteratorMode::Start) {
+            let (key, value) = result.map_err(|e| {
+                MdiError::StorageError(format!("Iterator error: {}", e))
             })?;
+            *report.keys.entry(cf.to_string()).or_default() += 1;
+            f(&key, &value, report)?;
         }
+        Ok(())
+    }
 
+    /// 将 memtable 刷新到磁盘并压实全部列族，之后 SST 大小反映去重、压缩后的实际占用
+    pub fn compact_all(&self) -> Result<()> {
+        for cf_name in [CF_TICKS, CF_KLINES, CF_QUOTES, CF_META] {
+            let cf = self.cf(cf_name);
+            self.db.flush_cf(cf).map_err(|e| {
+                MdiError::StorageError(format!("Failed to flush {}: {}", cf_name, e))
+            })?;
+            self.db.compact_range_cf(cf, None::<&[u8]>, None::<&[u8]>);
+        }
+        Ok(())
+    }
+
+    /// 创建时间点检查点，同一文件系统上以硬链接共享 SST 文件，`path` 不能已存在
+    ///
+    /// 检查点是可直接用 `TickStorage::open` 打开的完整数据库，写入不需要暂停
+    pub fn checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let checkpoint = Checkpoint::new(&self.db).map_err(|e| {
+            MdiError::StorageError(format!("Failed to create checkpoint: {}", e))
+        })?;
+        checkpoint.create_checkpoint(path).map_err(|e| {
+            MdiError::StorageError(format!("Failed to create checkpoint: {}", e))
+        })
+    }
+
+    /// 增量备份到 `backup_dir`（已有备份中的 SST 文件不重复复制），只保留最近 `keep` 份
+    pub fn backup<P: AsRef<Path>>(&self, backup_dir: P, keep: usize) -> Result<BackupInfo> {
+        let mut engine = open_backup_engine(backup_dir)?;
+        engine.create_new_backup_flush(&self.db, true).map_err(|e| {
+            MdiError::StorageError(format!("Failed to create backup: {}", e))
+        })?;
+        engine.purge_old_backups(keep.max(1)).map_err(|e| {
+            MdiError::StorageError(format!("Failed to purge old backups: {}", e))
+        })?;
+
+        backup_infos(&engine)
+            .pop()
+            .ok_or_else(|| MdiError::StorageError("Backup not found after creation".to_string()))
+    }
+
+    /// 列出 `backup_dir` 中的备份，按 id 升序
+    pub fn list_backups<P: AsRef<Path>>(backup_dir: P) -> Result<Vec<BackupInfo>> {
+        Ok(backup_infos(&open_backup_engine(backup_dir)?))
+    }
+
+    /// 将备份恢复到新路径，`backup_id` 为 None 时恢复最新备份
+    ///
+    /// `target` 必须不存在或为空目录，避免覆盖正在使用的数据库
+    pub fn restore<P: AsRef<Path>, Q: AsRef<Path>>(
+        backup_dir: P,
+        target: Q,
+        backup_id: Option<u32>,
+    ) -> Result<()> {
+        let target = target.as_ref();
+        if target.read_dir().is_ok_and(|mut entries| entries.next().is_some()) {
+            return Err(MdiError::StorageError(format!(
+                "Restore target {} is not empty",
+                target.display()
+            )));
+        }
+
+        let mut engine = open_backup_engine(backup_dir)?;
+        let opts = RestoreOptions::default();
+        match backup_id {
+            Some(backup_id) => engine.restore_from_backup(target, target, &opts, backup_id),
+            None => engine.restore_from_latest_backup(target, target, &opts),
+        }
+        .map_err(|e| MdiError::StorageError(format!("Failed to restore backup: {}", e)))
+    }
+
+    /// 获取引擎统计信息：各列族 SST 大小、memtable 占用、待压实字节数、写入停顿和块缓存
+    ///
+    /// 只读取 RocksDB 属性和计数器，开销很小，可以定期采集；`data` 为空
+    pub fn get_stats(&self) -> Result<StorageStats> {
+        let mut stats = StorageStats::default();
+        for cf_name in [CF_TICKS, CF_KLINES, CF_QUOTES, CF_META] {
+            let cf = self.cf(cf_name);
+            let int = |name: &str| -> Result<u64> {
+                let value = self.db.property_int_value_cf(cf, name).map_err(|e| {
+                    MdiError::StorageError(format!("Failed to get {} of {}: {}", name, cf_name, e))
+                })?;
+                Ok(value.unwrap_or(0))
+            };
+
+            let sst_files = (0..NUM_LEVELS)
+                .map(|level| int(&format!("rocksdb.num-files-at-level{}", level)))
+                .collect::<Result<Vec<_>>>()?;
+            let write_stalls = self
+                .db
+                .property_value_cf(cf, "rocksdb.cf-write-stall-stats")
+                .map_err(|e| MdiError::StorageError(format!("Failed to get write stalls of {}: {}", cf_name, e)))?
+                .map(|text| stats::parse_write_stalls(&text))
+                .unwrap_or_default();
+
+            let cf_stats = ColumnFamilyStats {
+                estimated_keys: int("rocksdb.estimate-num-keys")?,
+                sst_bytes: int("rocksdb.total-sst-files-size")?,
+                live_sst_bytes: int("rocksdb.live-sst-files-size")?,
+                sst_files,
+                memtable_bytes: int("rocksdb.cur-size-all-mem-tables")?,
+                immutable_memtables: int("rocksdb.num-immutable-mem-table")?,
+                pending_compaction_bytes: int("rocksdb.estimate-pending-compaction-bytes")?,
+                write_stalls,
+                block_cache: CacheUsage {
+                    capacity: int("rocksdb.block-cache-capacity")?,
+                    usage: int("rocksdb.block-cache-usage")?,
+                    pinned: int("rocksdb.block-cache-pinned-usage")?,
+                },
+            };
+            stats.write_stall.delays += cf_stats.write_stalls.get("total-delays").copied().unwrap_or(0);
+            stats.write_stall.stops += cf_stats.write_stalls.get("total-stops").copied().unwrap_or(0);
+            stats.column_families.insert(cf_name.to_string(), cf_stats);
+        }
+
+        let int = |name: &str| -> Result<u64> {
+            let value = self.db.property_int_value(name).map_err(|e| {
+                MdiError::StorageError(format!("Failed to get {}: {}", name, e))
+            })?;
+            Ok(value.unwrap_or(0))
+        };
+        stats.write_stall = WriteStallStats {
+            stopped: int("rocksdb.is-write-stopped")? != 0,
+            delayed_write_rate: int("rocksdb.actual-delayed-write-rate")?,
+            stall_micros: self.options.get_ticker_count(Ticker::StallMicros),
+            ..stats.write_stall
+        };
+        stats.running_compactions = int("rocksdb.num-running-compactions")?;
+        stats.running_flushes = int("rocksdb.num-running-flushes")?;
+        stats.block_cache = BlockCacheStats::new(
+            self.options.get_ticker_count(Ticker::BlockCacheHit),
+            self.options.get_ticker_count(Ticker::BlockCacheMiss),
+        );
+        Ok(stats)
+    }
+
+    /// 在 `get_stats` 的基础上扫描全部键，按数据类型和品种统计键数和字节数
+    ///
+    /// 需要遍历整个数据库，适合离线巡检而不是高频采集
+    pub fn get_detailed_stats(&self) -> Result<StorageStats> {
+        let mut stats = self.get_stats()?;
+        let names: HashMap<u32, String> = self
+            .symbols
+            .read()
+            .iter()
+            .map(|(symbol, &id)| (id, symbol.clone()))
+            .collect();
+        let symbol = |key: &[u8]| {
+            let id = be_u32(key.get(1..PREFIX_LEN)?);
+            Some(names.get(&id).cloned().unwrap_or_else(|| format!("#{}", id)))
+        };
+
+        for cf_name in [CF_TICKS, CF_KLINES, CF_QUOTES, CF_META] {
+            for result in self.db.iterator_cf_opt(self.cf(cf_name), total_order(), IteratorMode::Start) {
+                let (key, value) = result.map_err(|e| {
+                    MdiError::StorageError(format!("Iterator error: {}", e))
+                })?;
+                let data_type = match key.first() {
+                    Some(&KEY_TICK) => stats::DATA_TICKS,
+                    Some(&KEY_TRADE_INDEX) => stats::DATA_TRADE_INDEX,
+                    Some(&KEY_KLINE) => stats::DATA_KLINES,
+                    Some(&KEY_PROFILE) => stats::DATA_PROFILES,
+                    _ => {
+                        stats.add_key(stats::DATA_META, None, &key, &value);
+                        continue;
+                    }
+                };
+                stats.add_key(data_type, symbol(&key), &key, &value);
+            }
+        }
+        Ok(stats)
+    }
+
+    /// 清空数据库
+    pub fn clear(&self) -> Result<()> {
+        // 所有键的首字节都小于 0xff，每个列族一次 delete_range 即可
+        let mut batch = rocksdb::WriteBatch::default();
+        for cf_name in [CF_TICKS, CF_KLINES, CF_QUOTES, CF_META] {
+            batch.delete_range_cf(self.cf(cf_name), [0x00u8], [0xffu8]);
+        }
+        self.write_batch(batch)?;
+
+        self.symbols.write().clear();
         Ok(())
     }
 }
@@ -222,12 +1368,193 @@ impl Clone for TickStorage {
     fn clone(&self) -> Self {
         TickStorage {
             db: Arc::clone(&self.db),
+            format: self.format,
+            symbols: Arc::clone(&self.symbols),
+            options: Arc::clone(&self.options),
         }
     }
 }
 
-/// 存储统计信息
+/// 批量读取时跳过无法解码的记录，用 `verify` 查找和修复
+fn skip_corrupt(cf: &str, key: &[u8], error: &MdiError) {
+    tracing::warn!("Skipping corrupt record in {} at {:02x?}: {}", cf, key, error);
+}
+
+fn open_backup_engine<P: AsRef<Path>>(backup_dir: P) -> Result<BackupEngine> {
+    let opts = BackupEngineOptions::new(backup_dir).map_err(|e| {
+        MdiError::StorageError(format!("Invalid backup dir: {}", e))
+    })?;
+    let env = rocksdb::Env::new().map_err(|e| {
+        MdiError::StorageError(format!("Failed to create env: {}", e))
+    })?;
+    BackupEngine::open(&opts, &env).map_err(|e| {
+        MdiError::StorageError(format!("Failed to open backup engine: {}", e))
+    })
+}
+
+fn backup_infos(engine: &BackupEngine) -> Vec<BackupInfo> {
+    let mut infos: Vec<_> = engine
+        .get_backup_info()
+        .into_iter()
+        .map(|info| BackupInfo {
+            backup_id: info.backup_id,
+            timestamp: info.timestamp,
+            size: info.size,
+            num_files: info.num_files,
+        })
+        .collect();
+    infos.sort_by_key(|info| info.backup_id);
+    infos
+}
+
+/// 跨前缀的全量遍历
+fn total_order() -> ReadOptions {
+    let mut opts = ReadOptions::default();
+    opts.set_total_order_seek(true);
+    opts
+}
+
+fn meta_key(name: &str) -> Vec<u8> {
+    let mut key = vec![0x00];
+    key.extend_from_slice(name.as_bytes());
+    key
+}
+
+fn key_prefix(kind: u8, symbol_id: u32) -> Vec<u8> {
+    let mut key = Vec::with_capacity(21);
+    key.push(kind);
+    key.extend_from_slice(&symbol_id.to_be_bytes());
+    key
+}
+
+fn tick_key(symbol_id: u32, timestamp: u64, trade_id: u64) -> Vec<u8> {
+    let mut key = key_prefix(KEY_TICK, symbol_id);
+    key.extend_from_slice(&timestamp.to_be_bytes());
+    key.extend_from_slice(&trade_id.to_be_bytes());
+    key
+}
+
+fn trade_index_key(symbol_id: u32, trade_id: u64) -> Vec<u8> {
+    let mut key = key_prefix(KEY_TRADE_INDEX, symbol_id);
+    key.extend_from_slice(&trade_id.to_be_bytes());
+    key
+}
+
+fn bar_prefix(kind: u8, symbol_id: u32, interval: u64) -> Vec<u8> {
+    let mut key = key_prefix(kind, symbol_id);
+    key.extend_from_slice(&interval.to_be_bytes());
+    key
+}
+
+fn bar_key(kind: u8, symbol_id: u32, interval: u64, timestamp: u64) -> Vec<u8> {
+    let mut key = bar_prefix(kind, symbol_id, interval);
+    key.extend_from_slice(&timestamp.to_be_bytes());
+    key
+}
+
+/// 解析 `类型 | symbol_id | interval | timestamp`
+fn parse_bar_key(kind: u8, key: &[u8]) -> Option<(u32, u64, u64)> {
+    if key.len() != 21 || key[0] != kind {
+        return None;
+    }
+    Some((be_u32(&key[1..5]), be_u64(&key[5..13]), be_u64(&key[13..21])))
+}
+
+fn be_u32(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes(bytes.try_into().expect("4 bytes"))
+}
+
+fn be_u64(bytes: &[u8]) -> u64 {
+    u64::from_be_bytes(bytes.try_into().expect("8 bytes"))
+}
+
+fn read_u64(bytes: &[u8]) -> Result<u64> {
+    bytes
+        .try_into()
+        .map(u64::from_be_bytes)
+        .map_err(|_| MdiError::StorageError("Invalid u64 value".to_string()))
+}
+
+/// 范围查询方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScanDirection {
+    /// 从早到晚
+    #[default]
+    Forward,
+    /// 从晚到早
+    Reverse,
+}
+
+/// 分页游标，指向上一页最后一条记录（K 线的 `trade_id` 为 0）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub timestamp: u64,
+    pub trade_id: u64,
+}
+
+/// 范围查询选项
+#[derive(Debug, Clone, Default)]
+pub struct RangeOptions {
+    pub direction: ScanDirection,
+    /// 单页最大条数，None 表示不限
+    pub limit: Option<usize>,
+    /// 从该游标之后继续读取
+    pub cursor: Option<Cursor>,
+}
+
+impl RangeOptions {
+    pub fn new() -> Self {
+        RangeOptions::default()
+    }
+
+    /// 从晚到早读取
+    pub fn reverse(mut self) -> Self {
+        self.direction = ScanDirection::Reverse;
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// 从上一页返回的游标之后继续
+    pub fn after(mut self, cursor: Cursor) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+}
+
+/// 一页查询结果
 #[derive(Debug, Clone)]
-pub struct StorageStats {
-    pub info: String,
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// 下一页游标，None 表示已读完（达到 limit 时总会返回游标，下一页可能为空）
+    pub next_cursor: Option<Cursor>,
+}
+
+impl<T> Default for Page<T> {
+    fn default() -> Self {
+        Page { items: Vec::new(), next_cursor: None }
+    }
+}
+
+/// 迁移结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MigrationStats {
+    /// 扫描的记录数
+    pub scanned: usize,
+    /// 重写的记录数
+    pub migrated: usize,
+}
+
+/// 备份信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub backup_id: u32,
+    /// 备份时间（Unix 秒）
+    pub timestamp: i64,
+    /// 备份大小（字节），共享的 SST 文件在每份备份中都计入
+    pub size: u64,
+    pub num_files: u32,
 }
@@ -0,0 +1,245 @@
+use crate::memstore::MemoryStore;
+use crate::retention::{PurgeReport, RetentionPolicy};
+use crate::segment::SegmentStore;
+use crate::storage::{BackupInfo, Cursor, Page, RangeOptions, ScanDirection, StorageConfig};
+use crate::{Tick, KLine, VolumeProfile, TickStorage, MdiError, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// 行情存储后端
+///
+/// 由 `TickStorage`（RocksDB）、`MemoryStore`（内存）和 `SegmentStore`（追加写段文件）实现，
+/// 查询语义一致：Tick 按 (timestamp, trade_id) 排序，K 线和成交量分布的时间戳为秒
+pub trait TickStore: Send + Sync {
+    fn write_ticks(&self, ticks: &[Tick]) -> Result<()>;
+
+    fn write_tick(&self, tick: &Tick) -> Result<()> {
+        self.write_ticks(std::slice::from_ref(tick))
+    }
+
+    /// 按 trade_id 读取单个 Tick
+    fn read_tick(&self, symbol: &str, trade_id: u64) -> Result<Option<Tick>>;
+
+    /// 读取指定品种 `[from_ms, to_ms)` 时间范围内的 Tick
+    fn read_ticks_range(&self, symbol: &str, from_ms: u64, to_ms: u64, options: &RangeOptions) -> Result<Page<Tick>>;
+
+    /// 读取指定品种最早的 `limit` 条 Tick
+    fn read_ticks_by_symbol(&self, symbol: &str, limit: usize) -> Result<Vec<Tick>> {
+        Ok(self.read_ticks_range(symbol, 0, u64::MAX, &RangeOptions::new().limit(limit))?.items)
+    }
+
+    /// 读取指定品种 `since`（毫秒）及之后的 Tick
+    fn read_ticks_since(&self, symbol: &str, since: u64) -> Result<Vec<Tick>> {
+        Ok(self.read_ticks_range(symbol, since, u64::MAX, &RangeOptions::new())?.items)
+    }
+
+    fn write_klines(&self, klines: &[KLine]) -> Result<()>;
+
+    fn write_kline(&self, kline: &KLine) -> Result<()> {
+        self.write_klines(std::slice::from_ref(kline))
+    }
+
+    fn read_kline(&self, symbol: &str, interval: u64, timestamp: u64) -> Result<Option<KLine>>;
+
+    /// 读取指定品种、周期 `[from, to)`（秒）内的 K 线
+    fn read_klines_range(
+        &self,
+        symbol: &str,
+        interval: u64,
+        from: u64,
+        to: u64,
+        options: &RangeOptions,
+    ) -> Result<Page<KLine>>;
+
+    /// 读取指定品种、周期的所有 K 线
+    fn read_klines_by_symbol(&self, symbol: &str, interval: u64) -> Result<Vec<KLine>> {
+        Ok(self.read_klines_range(symbol, interval, 0, u64::MAX, &RangeOptions::new())?.items)
+    }
+
+    fn write_profiles(&self, profiles: &[VolumeProfile]) -> Result<()>;
+
+    fn read_profile(&self, symbol: &str, interval: u64, timestamp: u64) -> Result<Option<VolumeProfile>>;
+
+    fn read_meta(&self, name: &str) -> Result<Option<Vec<u8>>>;
+
+    fn write_meta(&self, name: &str, value: &[u8]) -> Result<()>;
+
+    /// 已有数据的品种，按名称排序
+    fn symbols(&self) -> Vec<String>;
+
+    /// 按保留策略删除过期数据
+    fn purge(&self, policy: &RetentionPolicy, now_ms: u64) -> Result<PurgeReport>;
+
+    /// 增量备份，默认不支持
+    fn backup(&self, _backup_dir: &Path, _keep: usize) -> Result<BackupInfo> {
+        Err(MdiError::StorageError("Backup is not supported by this backend".to_string()))
+    }
+
+    fn clear(&self) -> Result<()>;
+}
+
+impl TickStore for TickStorage {
+    fn write_ticks(&self, ticks: &[Tick]) -> Result<()> {
+        TickStorage::write_ticks(self, ticks)
+    }
+
+    fn read_tick(&self, symbol: &str, trade_id: u64) -> Result<Option<Tick>> {
+        TickStorage::read_tick(self, symbol, trade_id)
+    }
+
+    fn read_ticks_range(&self, symbol: &str, from_ms: u64, to_ms: u64, options: &RangeOptions) -> Result<Page<Tick>> {
+        TickStorage::read_ticks_range(self, symbol, from_ms, to_ms, options)
+    }
+
+    fn read_ticks_by_symbol(&self, symbol: &str, limit: usize) -> Result<Vec<Tick>> {
+        TickStorage::read_ticks_by_symbol(self, symbol, limit)
+    }
+
+    fn read_ticks_since(&self, symbol: &str, since: u64) -> Result<Vec<Tick>> {
+        TickStorage::read_ticks_since(self, symbol, since)
+    }
+
+    fn write_klines(&self, klines: &[KLine]) -> Result<()> {
+        TickStorage::write_klines(self, klines)
+    }
+
+    fn read_kline(&self, symbol: &str, interval: u64, timestamp: u64) -> Result<Option<KLine>> {
+        TickStorage::read_kline(self, symbol, interval, timestamp)
+    }
+
+    fn read_klines_range(
+        &self,
+        symbol: &str,
+        interval: u64,
+        from: u64,
+        to: u64,
+        options: &RangeOptions,
+    ) -> Result<Page<KLine>> {
+        TickStorage::read_klines_range(self, symbol, interval, from, to, options)
+    }
+
+    fn read_klines_by_symbol(&self, symbol: &str, interval: u64) -> Result<Vec<KLine>> {
+        TickStorage::read_klines_by_symbol(self, symbol, interval)
+    }
+
+    fn write_profiles(&self, profiles: &[VolumeProfile]) -> Result<()> {
+        TickStorage::write_profiles(self, profiles)
+    }
+
+    fn read_profile(&self, symbol: &str, interval: u64, timestamp: u64) -> Result<Option<VolumeProfile>> {
+        TickStorage::read_profile(self, symbol, interval, timestamp)
+    }
+
+    fn read_meta(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        TickStorage::read_meta(self, name)
+    }
+
+    fn write_meta(&self, name: &str, value: &[u8]) -> Result<()> {
+        TickStorage::write_meta(self, name, value)
+    }
+
+    fn symbols(&self) -> Vec<String> {
+        TickStorage::symbols(self)
+    }
+
+    fn purge(&self, policy: &RetentionPolicy, now_ms: u64) -> Result<PurgeReport> {
+        TickStorage::purge(self, policy, now_ms)
+    }
+
+    fn backup(&self, backup_dir: &Path, keep: usize) -> Result<BackupInfo> {
+        TickStorage::backup(self, backup_dir, keep)
+    }
+
+    fn clear(&self) -> Result<()> {
+        TickStorage::clear(self)
+    }
+}
+
+/// 存储后端配置
+#[derive(Debug, Clone)]
+pub enum StoreConfig {
+    RocksDb { path: PathBuf, config: StorageConfig },
+    Memory,
+    Segment { dir: PathBuf, segment_bytes: u64 },
+}
+
+impl StoreConfig {
+    /// 默认配置的 RocksDB
+    pub fn rocksdb<P: Into<PathBuf>>(path: P) -> Self {
+        StoreConfig::RocksDb { path: path.into(), config: StorageConfig::default() }
+    }
+
+    /// 段文件存储，单个段默认 64MB
+    pub fn segment<P: Into<PathBuf>>(dir: P) -> Self {
+        StoreConfig::Segment { dir: dir.into(), segment_bytes: 64 << 20 }
+    }
+
+    /// 打开存储后端
+    pub fn open(&self) -> Result<Arc<dyn TickStore>> {
+        Ok(match self {
+            StoreConfig::RocksDb { path, config } => Arc::new(TickStorage::open_with_config(path, config.clone())?),
+            StoreConfig::Memory => Arc::new(MemoryStore::new()),
+            StoreConfig::Segment { dir, segment_bytes } => Arc::new(SegmentStore::open(dir, *segment_bytes)?),
+        })
+    }
+}
+
+/// 解析 `rocksdb:PATH`、`segment:DIR` 或 `memory`
+impl FromStr for StoreConfig {
+    type Err = MdiError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once(':') {
+            Some(("rocksdb", path)) if !path.is_empty() => Ok(StoreConfig::rocksdb(path)),
+            Some(("segment", dir)) if !dir.is_empty() => Ok(StoreConfig::segment(dir)),
+            None if s == "memory" => Ok(StoreConfig::Memory),
+            _ => Err(MdiError::StorageError(format!("Invalid store config: {}", s))),
+        }
+    }
+}
+
+/// 在按 (timestamp, trade_id) 排序的 map 上执行范围查询，语义与 `TickStorage` 相同
+///
+/// K 线等没有 trade_id 的记录以 0 作为第二键
+pub(crate) fn scan_map<T: Clone>(
+    map: &BTreeMap<(u64, u64), T>,
+    from: u64,
+    to: u64,
+    options: &RangeOptions,
+) -> Page<T> {
+    let mut lower = (from, 0);
+    let mut upper = (to, 0);
+    if let Some(cursor) = &options.cursor {
+        match options.direction {
+            ScanDirection::Forward => {
+                let after = match cursor.trade_id.checked_add(1) {
+                    Some(trade_id) => (cursor.timestamp, trade_id),
+                    None => (cursor.timestamp.saturating_add(1), 0),
+                };
+                lower = lower.max(after);
+            }
+            ScanDirection::Reverse => upper = upper.min((cursor.timestamp, cursor.trade_id)),
+        }
+    }
+
+    let mut page = Page::default();
+    if lower >= upper || options.limit == Some(0) {
+        return page;
+    }
+
+    let range = map.range(lower..upper);
+    let iter: Box<dyn Iterator<Item = (&(u64, u64), &T)>> = match options.direction {
+        ScanDirection::Forward => Box::new(range),
+        ScanDirection::Reverse => Box::new(range.rev()),
+    };
+    for (&(timestamp, trade_id), item) in iter {
+        page.items.push(item.clone());
+        if options.limit.is_some_and(|limit| page.items.len() >= limit) {
+            page.next_cursor = Some(Cursor { timestamp, trade_id });
+            break;
+        }
+    }
+    page
+}
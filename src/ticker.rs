@@ -0,0 +1,199 @@
+use crate::{SymbolStats, Tick};
+use parking_lot::RwLock;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+/// 时间桶内的成交汇总
+#[derive(Debug, Clone)]
+struct Bucket {
+    start: u64,
+    open: f64,
+    close: f64,
+    volume: f64,
+    quote_volume: f64,
+    count: u64,
+    first_time: u64,
+    last_time: u64,
+}
+
+/// 单个品种的滚动窗口
+#[derive(Debug, Default)]
+struct RollingWindow {
+    buckets: VecDeque<Bucket>,
+    volume: f64,
+    quote_volume: f64,
+    count: u64,
+    /// 单调递减的 (桶开始时间, 价格)，队首为窗口最高价
+    highs: VecDeque<(u64, f64)>,
+    /// 单调递增的 (桶开始时间, 价格)，队首为窗口最低价
+    lows: VecDeque<(u64, f64)>,
+}
+
+impl RollingWindow {
+    fn push(&mut self, tick: &Tick, bucket_start: u64) {
+        match self.buckets.back_mut() {
+            // 迟到的 Tick 计入最新的桶
+            Some(bucket) if bucket.start >= bucket_start => {
+                bucket.close = tick.price;
+                bucket.volume += tick.quantity;
+                bucket.quote_volume += tick.price * tick.quantity;
+                bucket.count += 1;
+                bucket.last_time = bucket.last_time.max(tick.timestamp);
+            }
+            _ => self.buckets.push_back(Bucket {
+                start: bucket_start,
+                open: tick.price,
+                close: tick.price,
+                volume: tick.quantity,
+                quote_volume: tick.price * tick.quantity,
+                count: 1,
+                first_time: tick.timestamp,
+                last_time: tick.timestamp,
+            }),
+        }
+
+        let start = self.buckets.back().expect("bucket pushed above").start;
+        while self.highs.back().is_some_and(|&(_, high)| high <= tick.price) {
+            self.highs.pop_back();
+        }
+        self.highs.push_back((start, tick.price));
+        while self.lows.back().is_some_and(|&(_, low)| low >= tick.price) {
+            self.lows.pop_back();
+        }
+        self.lows.push_back((start, tick.price));
+
+        self.volume += tick.quantity;
+        self.quote_volume += tick.price * tick.quantity;
+        self.count += 1;
+    }
+
+    /// 移除开始时间早于 `cutoff` 的桶
+    fn expire(&mut self, cutoff: u64) {
+        while let Some(bucket) = self.buckets.front() {
+            if bucket.start >= cutoff {
+                break;
+            }
+            let bucket = self.buckets.pop_front().expect("front bucket exists");
+            self.volume -= bucket.volume;
+            self.quote_volume -= bucket.quote_volume;
+            self.count -= bucket.count;
+        }
+        while self.highs.front().is_some_and(|&(start, _)| start < cutoff) {
+            self.highs.pop_front();
+        }
+        while self.lows.front().is_some_and(|&(start, _)| start < cutoff) {
+            self.lows.pop_front();
+        }
+        if self.buckets.is_empty() {
+            // 消除浮点累计误差
+            self.volume = 0.0;
+            self.quote_volume = 0.0;
+        }
+    }
+
+    fn snapshot(&self, symbol: &str) -> Option<SymbolStats> {
+        let first = self.buckets.front()?;
+        let last = self.buckets.back()?;
+        Some(SymbolStats {
+            symbol: symbol.to_string(),
+            tick_count: self.count,
+            volume: self.volume,
+            open: first.open,
+            high: self.highs.front()?.1,
+            low: self.lows.front()?.1,
+            last_price: last.close,
+            last_update: last.last_time,
+            quote_volume: self.quote_volume,
+            open_time: first.first_time,
+        })
+    }
+}
+
+/// 滚动窗口行情 - 与 Binance 24hr ticker 对应
+///
+/// 成交按 `bucket_ms` 分桶，窗口滑动时整桶移出，统计量增量维护，
+/// 最高/最低价由单调队列维护，每笔 Tick 均摊 O(1)
+pub struct TickerBuilder {
+    window_ms: u64,
+    bucket_ms: u64,
+    /// symbol -> 滚动窗口
+    windows: Arc<RwLock<HashMap<String, RollingWindow>>>,
+}
+
+impl TickerBuilder {
+    /// 创建滚动行情
+    /// # Arguments
+    /// * `window_ms` - 窗口长度（毫秒），例如 24 小时: 86_400_000
+    /// * `bucket_ms` - 分桶粒度（毫秒），决定窗口边界精度，例如: 1000
+    ///
+    /// 分桶粒度不会超过窗口长度，否则当前成交所在的桶会被立即移出
+    pub fn new(window_ms: u64, bucket_ms: u64) -> Self {
+        let window_ms = window_ms.max(1);
+        TickerBuilder {
+            window_ms,
+            bucket_ms: bucket_ms.clamp(1, window_ms),
+            windows: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// 24 小时窗口，秒级分桶
+    pub fn daily() -> Self {
+        TickerBuilder::new(86_400_000, 1000)
+    }
+
+    /// 处理 Tick，返回更新后的行情
+    pub fn process_tick(&self, tick: &Tick) -> SymbolStats {
+        let bucket_start = tick.timestamp / self.bucket_ms * self.bucket_ms;
+        let mut windows = self.windows.write();
+        let window = windows.entry(tick.symbol.clone()).or_default();
+
+        window.push(tick, bucket_start);
+        window.expire(self.cutoff(tick.timestamp));
+        window.snapshot(&tick.symbol).expect("window contains the current tick")
+    }
+
+    /// 按当前时间（毫秒）移出过期成交，长时间无成交的品种也能及时更新
+    pub fn expire(&self, now: u64) {
+        let cutoff = self.cutoff(now);
+        let mut windows = self.windows.write();
+        for window in windows.values_mut() {
+            window.expire(cutoff);
+        }
+    }
+
+    /// 窗口内最早允许的桶开始时间
+    fn cutoff(&self, now: u64) -> u64 {
+        (now + self.bucket_ms).saturating_sub(self.window_ms) / self.bucket_ms * self.bucket_ms
+    }
+
+    /// 获取指定品种的行情，窗口内无成交时返回 None
+    pub fn get_ticker(&self, symbol: &str) -> Option<SymbolStats> {
+        self.windows.read().get(symbol)?.snapshot(symbol)
+    }
+
+    /// 获取所有品种的行情
+    pub fn get_tickers(&self) -> Vec<SymbolStats> {
+        let windows = self.windows.read();
+        let mut tickers: Vec<_> = windows
+            .iter()
+            .filter_map(|(symbol, window)| window.snapshot(symbol))
+            .collect();
+        tickers.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        tickers
+    }
+
+    /// 清空所有状态
+    pub fn clear(&self) {
+        self.windows.write().clear();
+    }
+}
+
+impl Clone for TickerBuilder {
+    fn clone(&self) -> Self {
+        TickerBuilder {
+            window_ms: self.window_ms,
+            bucket_ms: self.bucket_ms,
+            windows: Arc::clone(&self.windows),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{Tick, KLine};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// K 线与 Tick 比较时的相对误差容忍度
+const EPSILON: f64 = 1e-9;
+
+/// 校验选项
+#[derive(Debug, Clone)]
+pub struct VerifyOptions {
+    /// 用 Tick 重新计算并覆盖不一致或损坏的 K 线
+    pub repair: bool,
+    /// 用 Tick 核对 K 线，关闭后只检查键和值
+    pub check_bars: bool,
+    /// 报告中保留的问题条数上限，超出部分只计数
+    pub max_issues: usize,
+}
+
+impl Default for VerifyOptions {
+    fn default() -> Self {
+        VerifyOptions {
+            repair: false,
+            check_bars: true,
+            max_issues: 1000,
+        }
+    }
+}
+
+impl VerifyOptions {
+    pub fn new() -> Self {
+        VerifyOptions::default()
+    }
+
+    pub fn repair(mut self, repair: bool) -> Self {
+        self.repair = repair;
+        self
+    }
+
+    pub fn check_bars(mut self, check_bars: bool) -> Self {
+        self.check_bars = check_bars;
+        self
+    }
+
+    pub fn max_issues(mut self, max_issues: usize) -> Self {
+        self.max_issues = max_issues;
+        self
+    }
+}
+
+/// 校验发现的问题
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Issue {
+    /// 键的类型或长度不可识别
+    MalformedKey { cf: String, key: Vec<u8> },
+    /// 键中的品种 id 未注册
+    UnknownSymbol { cf: String, key: Vec<u8> },
+    /// 值无法解码
+    CorruptValue { cf: String, key: Vec<u8>, error: String },
+    /// 值中的品种、时间戳、trade_id 或周期与键不一致
+    KeyMismatch { cf: String, key: Vec<u8>, detail: String },
+    /// Tick 没有指向自身的 trade_id 索引
+    MissingIndex { symbol: String, trade_id: u64 },
+    /// trade_id 索引指向不存在的 Tick
+    OrphanIndex { symbol: String, trade_id: u64, timestamp: u64 },
+    /// 缺失的 trade_id 区间 `[from, to]`
+    TradeIdGap { symbol: String, from: u64, to: u64 },
+    /// K 线与由 Tick 重新计算的结果不一致
+    BarMismatch { symbol: String, interval: u64, timestamp: u64, fields: Vec<String> },
+}
+
+impl Issue {
+    /// 问题类型，用于汇总
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Issue::MalformedKey { .. } => "malformed_key",
+            Issue::UnknownSymbol { .. } => "unknown_symbol",
+            Issue::CorruptValue { .. } => "corrupt_value",
+            Issue::KeyMismatch { .. } => "key_mismatch",
+            Issue::MissingIndex { .. } => "missing_index",
+            Issue::OrphanIndex { .. } => "orphan_index",
+            Issue::TradeIdGap { .. } => "trade_id_gap",
+            Issue::BarMismatch { .. } => "bar_mismatch",
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::MalformedKey { cf, key } => write!(f, "[{}] malformed key {:02x?}", cf, key),
+            Issue::UnknownSymbol { cf, key } => write!(f, "[{}] unknown symbol id in key {:02x?}", cf, key),
+            Issue::CorruptValue { cf, key, error } => write!(f, "[{}] corrupt value at {:02x?}: {}", cf, key, error),
+            Issue::KeyMismatch { cf, key, detail } => write!(f, "[{}] value does not match key {:02x?}: {}", cf, key, detail),
+            Issue::MissingIndex { symbol, trade_id } => write!(f, "{} trade {} has no index entry", symbol, trade_id),
+            Issue::OrphanIndex { symbol, trade_id, timestamp } => {
+                write!(f, "{} index for trade {} points to missing tick at {}", symbol, trade_id, timestamp)
+            }
+            Issue::TradeIdGap { symbol, from, to } => write!(f, "{} missing trade ids {}..={}", symbol, from, to),
+            Issue::BarMismatch { symbol, interval, timestamp, fields } => {
+                write!(f, "{} {}s bar at {} differs from ticks in {}", symbol, interval, timestamp, fields.join(", "))
+            }
+        }
+    }
+}
+
+/// 校验结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerifyReport {
+    /// 列族 -> 扫描的键数
+    pub keys: BTreeMap<String, u64>,
+    /// 问题类型 -> 数量（包括未保留在 `issues` 中的）
+    pub counts: BTreeMap<String, u64>,
+    pub issues: Vec<Issue>,
+    /// 缺失的 trade_id 总数
+    pub missing_trades: u64,
+    /// 用 Tick 核对过的 K 线数
+    pub bars_checked: u64,
+    /// 重新计算并覆盖的 K 线数
+    pub bars_repaired: u64,
+    #[serde(skip)]
+    max_issues: usize,
+}
+
+impl VerifyReport {
+    pub(crate) fn new(max_issues: usize) -> Self {
+        VerifyReport { max_issues, ..VerifyReport::default() }
+    }
+
+    pub(crate) fn push(&mut self, issue: Issue) {
+        if let Issue::TradeIdGap { from, to, .. } = &issue {
+            self.missing_trades += to - from + 1;
+        }
+        *self.counts.entry(issue.kind().to_string()).or_default() += 1;
+        if self.issues.len() < self.max_issues {
+            self.issues.push(issue);
+        }
+    }
+
+    /// 问题总数
+    pub fn total_issues(&self) -> u64 {
+        self.counts.values().sum()
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.total_issues() == 0
+    }
+}
+
+/// 用 `[timestamp, close_time)`（秒）内的 Tick 重新计算 K 线，没有成交时返回 None
+pub fn rebuild_bar(stored: &KLine, ticks: &[Tick]) -> Option<KLine> {
+    let first = ticks.first()?;
+    let mut kline = KLine::new(stored.symbol.clone(), stored.timestamp, stored.interval, first.price);
+    for tick in ticks {
+        kline.update(tick);
+    }
+    kline.close_time = stored.close_time;
+    Some(kline)
+}
+
+/// 比较 OHLCV 和成交笔数，返回不一致的字段
+pub fn bar_differences(stored: &KLine, rebuilt: Option<&KLine>) -> Vec<String> {
+    let rebuilt = match rebuilt {
+        Some(rebuilt) => rebuilt,
+        None if stored.number_of_trades == 0 => return Vec::new(),
+        None => return vec!["number_of_trades".to_string()],
+    };
+
+    let prices = [
+        ("open", stored.open, rebuilt.open),
+        ("high", stored.high, rebuilt.high),
+        ("low", stored.low, rebuilt.low),
+        ("close", stored.close, rebuilt.close),
+        ("volume", stored.volume, rebuilt.volume),
+    ];
+    let mut fields: Vec<String> = prices
+        .iter()
+        .filter(|(_, a, b)| (a - b).abs() > EPSILON * a.abs().max(b.abs()).max(1.0))
+        .map(|(name, _, _)| name.to_string())
+        .collect();
+    if stored.number_of_trades != rebuilt.number_of_trades {
+        fields.push("number_of_trades".to_string());
+    }
+    fields
+}
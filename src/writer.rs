@@ -0,0 +1,263 @@
+use crate::{Tick, KLine, VolumeProfile, TickStore, ThreadBuilder, MdiError, Result};
+use crossbeam::channel::{self, RecvTimeoutError, Sender, TrySendError};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// 待写入的记录
+#[derive(Debug, Clone)]
+pub enum WriteRecord {
+    Tick(Tick),
+    KLine(KLine),
+    Profile(VolumeProfile),
+}
+
+/// 队列满时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// 阻塞调用方，直到写入线程取走记录
+    #[default]
+    Block,
+    /// 丢弃记录并计数，调用方收到 `QueueError`
+    Drop,
+}
+
+/// 写入线程配置
+#[derive(Debug, Clone)]
+pub struct WriterConfig {
+    /// 队列容量（记录数）
+    pub capacity: usize,
+    /// 累计到该数量立即写入
+    pub batch_size: usize,
+    /// 距上次写入超过该时间即写入
+    pub flush_interval: Duration,
+    /// 绑定的 CPU 核心
+    pub cpu_id: Option<usize>,
+    pub overflow: OverflowPolicy,
+}
+
+impl Default for WriterConfig {
+    fn default() -> Self {
+        WriterConfig {
+            capacity: 100_000,
+            batch_size: 1000,
+            flush_interval: Duration::from_secs(1),
+            cpu_id: None,
+            overflow: OverflowPolicy::Block,
+        }
+    }
+}
+
+impl WriterConfig {
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = flush_interval;
+        self
+    }
+
+    pub fn cpu(mut self, cpu_id: usize) -> Self {
+        self.cpu_id = Some(cpu_id);
+        self
+    }
+
+    pub fn overflow(mut self, overflow: OverflowPolicy) -> Self {
+        self.overflow = overflow;
+        self
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    ticks: AtomicU64,
+    klines: AtomicU64,
+    profiles: AtomicU64,
+    flushes: AtomicU64,
+    dropped: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl Counters {
+    fn snapshot(&self, pending: usize) -> WriterStats {
+        WriterStats {
+            ticks: self.ticks.load(Ordering::Relaxed),
+            klines: self.klines.load(Ordering::Relaxed),
+            profiles: self.profiles.load(Ordering::Relaxed),
+            flushes: self.flushes.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            pending,
+        }
+    }
+}
+
+/// 写入统计信息
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WriterStats {
+    /// 已写入的 Tick 数
+    pub ticks: u64,
+    pub klines: u64,
+    pub profiles: u64,
+    /// 批量写入次数
+    pub flushes: u64,
+    /// 队列满时丢弃的记录数
+    pub dropped: u64,
+    /// 写入失败丢失的记录数
+    pub failed: u64,
+    /// 队列中等待写入的记录数
+    pub pending: usize,
+}
+
+/// 攒批缓冲
+#[derive(Default)]
+struct Batch {
+    ticks: Vec<Tick>,
+    klines: Vec<KLine>,
+    profiles: Vec<VolumeProfile>,
+}
+
+impl Batch {
+    fn push(&mut self, record: WriteRecord) {
+        match record {
+            WriteRecord::Tick(tick) => self.ticks.push(tick),
+            WriteRecord::KLine(kline) => self.klines.push(kline),
+            WriteRecord::Profile(profile) => self.profiles.push(profile),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.ticks.len() + self.klines.len() + self.profiles.len()
+    }
+
+    /// 写入存储并清空，失败的记录计入 `failed`
+    fn flush(&mut self, storage: &dyn TickStore, counters: &Counters) {
+        if self.len() == 0 {
+            return;
+        }
+
+        let results = [
+            (storage.write_ticks(&self.ticks), self.ticks.len(), &counters.ticks),
+            (storage.write_klines(&self.klines), self.klines.len(), &counters.klines),
+            (storage.write_profiles(&self.profiles), self.profiles.len(), &counters.profiles),
+        ];
+        for (result, len, written) in results {
+            if len == 0 {
+                continue;
+            }
+            match result {
+                Ok(()) => written.fetch_add(len as u64, Ordering::Relaxed),
+                Err(e) => {
+                    tracing::warn!("Storage writer failed to write {} records: {}", len, e);
+                    counters.failed.fetch_add(len as u64, Ordering::Relaxed)
+                }
+            };
+        }
+        counters.flushes.fetch_add(1, Ordering::Relaxed);
+
+        self.ticks.clear();
+        self.klines.clear();
+        self.profiles.clear();
+    }
+}
+
+/// 异步存储写入器 - 独立线程通过有界队列接收记录，按数量或时间阈值批量写入存储
+///
+/// 调用方只做一次入队，不在 tokio 工作线程上执行阻塞写入；`shutdown` 写完队列中的剩余记录后返回
+pub struct StorageWriter {
+    sender: Sender<WriteRecord>,
+    handle: JoinHandle<()>,
+    overflow: OverflowPolicy,
+    counters: Arc<Counters>,
+}
+
+impl StorageWriter {
+    /// 启动写入线程
+    pub fn spawn(storage: Arc<dyn TickStore>, config: WriterConfig) -> Self {
+        let (tx, rx) = channel::bounded::<WriteRecord>(config.capacity.max(1));
+        let counters = Arc::new(Counters::default());
+        let thread_counters = Arc::clone(&counters);
+
+        let mut builder = ThreadBuilder::new().name("storage-writer".to_string());
+        if let Some(cpu_id) = config.cpu_id {
+            builder = builder.cpu(cpu_id);
+        }
+
+        let handle = builder.spawn(move || {
+            let mut batch = Batch::default();
+            let mut deadline = Instant::now() + config.flush_interval;
+            loop {
+                match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(record) => {
+                        batch.push(record);
+                        if batch.len() < config.batch_size {
+                            continue;
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => {
+                        batch.flush(storage.as_ref(), &thread_counters);
+                        break;
+                    }
+                }
+                batch.flush(storage.as_ref(), &thread_counters);
+                deadline = Instant::now() + config.flush_interval;
+            }
+        });
+
+        StorageWriter {
+            sender: tx,
+            handle,
+            overflow: config.overflow,
+            counters,
+        }
+    }
+
+    pub fn write_tick(&self, tick: Tick) -> Result<()> {
+        self.send(WriteRecord::Tick(tick))
+    }
+
+    pub fn write_kline(&self, kline: KLine) -> Result<()> {
+        self.send(WriteRecord::KLine(kline))
+    }
+
+    pub fn write_profile(&self, profile: VolumeProfile) -> Result<()> {
+        self.send(WriteRecord::Profile(profile))
+    }
+
+    /// 入队，队列满时按 `OverflowPolicy` 阻塞或丢弃
+    pub fn send(&self, record: WriteRecord) -> Result<()> {
+        let stopped = || MdiError::QueueError("Storage writer stopped".to_string());
+        match self.overflow {
+            OverflowPolicy::Block => self.sender.send(record).map_err(|_| stopped()),
+            OverflowPolicy::Drop => match self.sender.try_send(record) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(_)) => {
+                    self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                    Err(MdiError::QueueError("Storage writer queue full".to_string()))
+                }
+                Err(TrySendError::Disconnected(_)) => Err(stopped()),
+            },
+        }
+    }
+
+    /// 获取写入统计
+    pub fn get_stats(&self) -> WriterStats {
+        self.counters.snapshot(self.sender.len())
+    }
+
+    /// 写完队列中的剩余记录后停止线程，返回最终统计
+    pub fn shutdown(self) -> WriterStats {
+        drop(self.sender);
+        let _ = self.handle.join();
+        self.counters.snapshot(0)
+    }
+}
@@ -0,0 +1,144 @@
+use mdi::archive::{decode_day, encode_day};
+use mdi::retention::DAY_MS;
+use mdi::storage::RangeOptions;
+use mdi::{codec, RetentionPolicy, Tick, TickArchive, TickStorage, TickStore, TieredStorage, ValueFormat};
+use tempfile::TempDir;
+
+fn tick(id: u64, ts: u64) -> Tick {
+    Tick::new("BTCUSDT".to_string(), ts, ts + 3, 42000.5 + (id % 3) as f64 * 0.1, 0.001 * id as f64, id.is_multiple_of(3), id)
+}
+
+#[test]
+fn test_columnar_roundtrip() {
+    let ticks: Vec<Tick> = (1..=1000).map(|id| tick(id, 1_700_000_000_000 + id * 37)).collect();
+    let bytes = encode_day("BTCUSDT", &ticks);
+    let binary: usize = ticks.iter().map(|t| codec::encode(t, ValueFormat::Binary).unwrap().len()).sum();
+    assert!(bytes.len() * 3 < binary, "{} vs {}", bytes.len(), binary);
+
+    let decoded = decode_day(&bytes).unwrap();
+    assert_eq!(decoded.len(), ticks.len());
+    for (a, b) in decoded.iter().zip(&ticks) {
+        assert_eq!(a.symbol, b.symbol);
+        assert_eq!((a.timestamp, a.event_time, a.trade_id), (b.timestamp, b.event_time, b.trade_id));
+        assert_eq!((a.price, a.quantity, a.is_buyer_maker), (b.price, b.quantity, b.is_buyer_maker));
+    }
+
+    assert!(decode_day(&bytes[..bytes.len() - 1]).is_err());
+    assert!(decode_day(b"nope").is_err());
+
+    // 损坏的行数不能导致按行数分配内存
+    let body = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f, 0x00];
+    let mut corrupt = b"MDIT\x01".to_vec();
+    corrupt.extend_from_slice(&snap::raw::Encoder::new().compress_vec(&body).unwrap());
+    assert!(decode_day(&corrupt).is_err());
+}
+
+#[test]
+fn test_compact_and_tiered_reads() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = TickStorage::open(temp_dir.path().join("test.db")).unwrap();
+    let tiered = TieredStorage::new(storage.clone(), TickArchive::open(temp_dir.path().join("archive")).unwrap());
+
+    // 第 10、11 天各 3 笔，第 12 天（当天）2 笔
+    let mut id = 0;
+    for day in [10, 11, 12] {
+        for i in 0..if day == 12 { 2 } else { 3 } {
+            id += 1;
+            tiered.write_tick(&tick(id, day * DAY_MS + i * 1000)).unwrap();
+        }
+    }
+    let now = 12 * DAY_MS + 3_600_000;
+    let report = tiered.compact(now).unwrap();
+    assert_eq!(report.days, 2);
+    assert_eq!(report.ticks, 6);
+    assert_eq!(storage.read_ticks_by_symbol("BTCUSDT", 100).unwrap().len(), 2);
+    assert_eq!(tiered.archive().entries("BTCUSDT").len(), 2);
+    assert!(tiered.compact(now).unwrap().is_empty());
+
+    // 两层合并分页
+    let mut ids = Vec::new();
+    let mut options = RangeOptions::new().limit(4);
+    loop {
+        let page = tiered.read_ticks_range("BTCUSDT", 0, u64::MAX, &options).unwrap();
+        ids.extend(page.items.iter().map(|t| t.trade_id));
+        match page.next_cursor {
+            Some(cursor) => options = options.after(cursor),
+            None => break,
+        }
+    }
+    assert_eq!(ids, (1..=8).collect::<Vec<_>>());
+    let page = tiered.read_ticks_range("BTCUSDT", 0, u64::MAX, &RangeOptions::new().reverse().limit(3)).unwrap();
+    assert_eq!(page.items.iter().map(|t| t.trade_id).collect::<Vec<_>>(), vec![8, 7, 6]);
+    assert_eq!(tiered.read_ticks_range("BTCUSDT", 11 * DAY_MS, 11 * DAY_MS + 1500, &RangeOptions::new()).unwrap().items.len(), 2);
+    assert_eq!(tiered.read_tick("BTCUSDT", 2).unwrap().unwrap().timestamp, 10 * DAY_MS + 1000);
+
+    // 迟到的成交再次归档时与已有文件合并
+    tiered.write_tick(&tick(100, 10 * DAY_MS + 500)).unwrap();
+    assert_eq!(tiered.compact(now).unwrap().ticks, 1);
+    // 已缓存的一天在重写文件后失效
+    assert_eq!(tiered.read_tick("BTCUSDT", 100).unwrap().unwrap().timestamp, 10 * DAY_MS + 500);
+    assert_eq!(tiered.archive().read_day("BTCUSDT", 10).unwrap().len(), 4);
+    let archive = TickArchive::open(temp_dir.path().join("archive")).unwrap().cache_days(0);
+    assert_eq!(archive.read_day("BTCUSDT", 10).unwrap().iter().map(|t| t.trade_id).collect::<Vec<_>>(), vec![1, 100, 2, 3]);
+
+    // 全天过期的文件整体删除
+    let report = tiered.purge(&RetentionPolicy::new().ticks(DAY_MS), now).unwrap();
+    assert_eq!(report.ticks, 4);
+    assert_eq!(tiered.archive().entries("BTCUSDT").iter().map(|e| e.day).collect::<Vec<_>>(), vec![11]);
+    assert_eq!(std::fs::read_dir(temp_dir.path().join("archive/BTCUSDT")).unwrap().count(), 1);
+}
+
+#[test]
+fn test_tiered_backup_and_restore() {
+    let temp_dir = TempDir::new().unwrap();
+    let backup_dir = temp_dir.path().join("backup");
+    let tiered = TieredStorage::new(
+        TickStorage::open(temp_dir.path().join("test.db")).unwrap(),
+        TickArchive::open(temp_dir.path().join("archive")).unwrap(),
+    );
+    for id in 1..=6 {
+        tiered.write_tick(&tick(id, (9 + id / 2) * DAY_MS + id * 1000)).unwrap();
+    }
+    let now = 12 * DAY_MS;
+    assert_eq!(tiered.compact(now).unwrap().ticks, 5);
+    let first = tiered.backup(&backup_dir, 2).unwrap();
+
+    // 备份之后归档继续变化，不影响已有快照
+    tiered.write_tick(&tick(7, 10 * DAY_MS + 500)).unwrap();
+    tiered.compact(now).unwrap();
+    tiered.purge(&RetentionPolicy::new().ticks(DAY_MS), now).unwrap();
+    assert_eq!(tiered.read_ticks_by_symbol("BTCUSDT", 100).unwrap().len(), 3);
+
+    let restored_db = temp_dir.path().join("restored.db");
+    let restored_archive = temp_dir.path().join("restored_archive");
+    TieredStorage::restore(&backup_dir, &restored_db, &restored_archive, Some(first.backup_id)).unwrap();
+    let restored = TieredStorage::new(
+        TickStorage::open(&restored_db).unwrap(),
+        TickArchive::open(&restored_archive).unwrap(),
+    );
+    let ids: Vec<u64> = restored.read_ticks_by_symbol("BTCUSDT", 100).unwrap().iter().map(|t| t.trade_id).collect();
+    assert_eq!(ids, (1..=6).collect::<Vec<_>>());
+    assert_eq!(restored.hot().read_ticks_by_symbol("BTCUSDT", 100).unwrap().len(), 1);
+    assert_eq!(restored.archive().entries("BTCUSDT").len(), 3);
+
+    // 不覆盖已有归档
+    let result = TieredStorage::restore(&backup_dir, temp_dir.path().join("other.db"), &restored_archive, None);
+    assert!(result.is_err());
+
+    // 只保留一份时，被清理的备份的快照一起删除
+    let latest = tiered.backup(&backup_dir, 1).unwrap();
+    let snapshots: Vec<String> = std::fs::read_dir(backup_dir.join("archive"))
+        .unwrap()
+        .map(|dir| dir.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(snapshots, vec![latest.backup_id.to_string()]);
+    let restored_db = temp_dir.path().join("latest.db");
+    let restored_archive = temp_dir.path().join("latest_archive");
+    TieredStorage::restore(&backup_dir, &restored_db, &restored_archive, None).unwrap();
+    let restored = TieredStorage::new(
+        TickStorage::open(&restored_db).unwrap(),
+        TickArchive::open(&restored_archive).unwrap(),
+    );
+    let ids: Vec<u64> = restored.read_ticks_by_symbol("BTCUSDT", 100).unwrap().iter().map(|t| t.trade_id).collect();
+    assert_eq!(ids, vec![4, 5, 6]);
+}
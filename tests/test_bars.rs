@@ -0,0 +1,246 @@
+use mdi::bars::{heikin_ashi_interval, BarBuilder, BarSpec, HeikinAshiBuilder};
+use mdi::storage::TickStorage;
+use mdi::{KLine, Tick};
+use tempfile::TempDir;
+
+fn tick(timestamp: u64, price: f64, quantity: f64, trade_id: u64) -> Tick {
+    Tick::new("BTCUSDT".to_string(), timestamp, timestamp, price, quantity, false, trade_id)
+}
+
+#[test]
+fn test_tick_bars() {
+    let builder = BarBuilder::new(vec![BarSpec::Ticks(3)]).unwrap();
+
+    builder.process_tick(&tick(1000, 100.0, 1.0, 1)).unwrap();
+    builder.process_tick(&tick(1001, 102.0, 1.0, 2)).unwrap();
+    let events = builder.process_tick(&tick(1002, 99.0, 1.0, 3)).unwrap();
+
+    assert_eq!(events.len(), 1);
+    assert!(events[0].is_closed);
+    let bar = &events[0].kline;
+    assert_eq!(bar.number_of_trades, 3);
+    assert_eq!(bar.high, 102.0);
+    assert_eq!(bar.low, 99.0);
+    assert_eq!(bar.open_time, 1000);
+    assert_eq!(bar.close_time, 1002);
+    assert!(builder.current_bar("BTCUSDT", &BarSpec::Ticks(3)).is_none());
+}
+
+#[test]
+fn test_invalid_thresholds_rejected() {
+    for spec in [
+        BarSpec::Ticks(0),
+        BarSpec::Volume(0.0),
+        BarSpec::Volume(-1.0),
+        BarSpec::Volume(f64::NAN),
+        BarSpec::Notional(0.0),
+        BarSpec::Notional(f64::INFINITY),
+    ] {
+        assert!(BarBuilder::new(vec![BarSpec::Ticks(3), spec]).is_err(), "{:?}", spec);
+    }
+}
+
+#[test]
+fn test_volume_bars_split_trade() {
+    let spec = BarSpec::Volume(10.0);
+    let builder = BarBuilder::new(vec![spec]).unwrap();
+
+    builder.process_tick(&tick(1000, 100.0, 4.0, 1)).unwrap();
+    builder.process_tick(&tick(1001, 101.0, 4.0, 2)).unwrap();
+    let events = builder.process_tick(&tick(1002, 102.0, 5.0, 3)).unwrap();
+
+    assert_eq!(events.len(), 2);
+    assert!(events[0].is_closed);
+    assert_eq!(events[0].kline.volume, 10.0);
+    assert_eq!(events[0].kline.close, 102.0);
+    assert!(!events[1].is_closed);
+    assert_eq!(events[1].kline.volume, 3.0);
+    assert_eq!(events[1].kline.open, 102.0);
+
+    // 单笔大单跨越多根 K 线，时间键仍唯一递增
+    let events = builder.process_tick(&tick(1003, 103.0, 25.0, 4)).unwrap();
+    let closed: Vec<_> = events.iter().filter(|e| e.is_closed).collect();
+    assert_eq!(closed.len(), 2);
+    assert!(closed.iter().all(|e| (e.kline.volume - 10.0).abs() < 1e-9));
+    assert!(closed[0].kline.timestamp < closed[1].kline.timestamp);
+    let current = builder.current_bar("BTCUSDT", &spec).unwrap();
+    assert!((current.volume - 8.0).abs() < 1e-9);
+    assert!(current.timestamp > closed[1].kline.timestamp);
+}
+
+#[test]
+fn test_notional_bars() {
+    let spec = BarSpec::Notional(1000.0);
+    let builder = BarBuilder::new(vec![spec]).unwrap();
+
+    let events = builder.process_tick(&tick(1000, 100.0, 15.0, 1)).unwrap();
+    assert!(events[0].is_closed);
+    assert_eq!(events[0].kline.quote_asset_volume, 1000.0);
+    assert_eq!(events[0].kline.volume, 10.0);
+
+    let current = builder.current_bar("BTCUSDT", &spec).unwrap();
+    assert_eq!(current.volume, 5.0);
+    assert_eq!(current.quote_asset_volume, 500.0);
+}
+
+#[test]
+fn test_bars_per_tick_capped() {
+    // 1 BTC 的成交按 1e-8 分线会生成 1e8 根，直接拒绝且不改变状态
+    let spec = BarSpec::Volume(1e-8);
+    let builder = BarBuilder::new(vec![spec, BarSpec::Ticks(10)]).unwrap();
+    assert!(builder.process_tick(&tick(1000, 100.0, 1.0, 1)).is_err());
+    assert!(builder.current_bar("BTCUSDT", &spec).is_none());
+    assert!(builder.current_bar("BTCUSDT", &BarSpec::Ticks(10)).is_none());
+
+    let events = builder.process_tick(&tick(1001, 100.0, 5e-6, 2)).unwrap();
+    assert_eq!(events.iter().filter(|e| e.is_closed).count(), 500);
+
+    let builder = BarBuilder::new(vec![BarSpec::Notional(0.01)]).unwrap();
+    assert!(builder.process_tick(&tick(1000, 100.0, 1.0, 1)).is_err());
+
+    // 价格跳空时 Renko 砖块数同样受限
+    let spec = BarSpec::Renko { box_size: 0.01, reversal: 2 };
+    let builder = BarBuilder::new(vec![spec]).unwrap();
+    builder.process_tick(&tick(1000, 100.0, 1.0, 1)).unwrap();
+    assert!(builder.process_tick(&tick(1001, 200.0, 1.0, 2)).is_err());
+    let events = builder.process_tick(&tick(1002, 105.0, 1.0, 3)).unwrap();
+    assert_eq!(events.iter().filter(|e| e.is_closed).count(), 500);
+}
+
+#[test]
+fn test_bar_spec_id() {
+    for spec in [
+        BarSpec::Ticks(100),
+        BarSpec::Volume(0.5),
+        BarSpec::Notional(1_000_000.0),
+        BarSpec::Range(25.5),
+        BarSpec::Renko { box_size: 100.0, reversal: 3 },
+    ] {
+        assert_eq!(BarSpec::from_id(spec.id()), Some(spec));
+        assert!(spec.id() > 86400 * 365);
+    }
+    assert_ne!(BarSpec::Volume(10.0).id(), BarSpec::Notional(10.0).id());
+    assert_eq!(BarSpec::from_id(60), None);
+}
+
+#[test]
+fn test_bar_spec_id_ranges() {
+    // 位宽边界上的类型仍能无损还原
+    for spec in [
+        BarSpec::Ticks((1 << 56) - 1),
+        BarSpec::Volume(1e-8),
+        BarSpec::Renko { box_size: 0.01, reversal: 255 },
+        BarSpec::Renko { box_size: 2_000_000.0, reversal: 2 },
+    ] {
+        spec.validate().unwrap();
+        assert_eq!(BarSpec::from_id(spec.id()), Some(spec));
+    }
+
+    // 超出位宽会被截断成其它类型，必须在创建时拒绝
+    for spec in [
+        BarSpec::Ticks(1 << 56),
+        BarSpec::Volume(1e-9),
+        BarSpec::Range(1e9),
+        BarSpec::Range(0.0),
+        BarSpec::Range(f64::NAN),
+        BarSpec::Renko { box_size: 0.0, reversal: 2 },
+        BarSpec::Renko { box_size: -100.0, reversal: 2 },
+        BarSpec::Renko { box_size: 100.0, reversal: 256 },
+        BarSpec::Renko { box_size: 100.0, reversal: 0 },
+        BarSpec::Renko { box_size: 100.0, reversal: 1 },
+        BarSpec::Renko { box_size: 3_000_000.0, reversal: 2 },
+    ] {
+        assert!(BarBuilder::new(vec![spec]).is_err(), "{:?}", spec);
+    }
+}
+
+#[test]
+fn test_bars_stored_alongside_time_bars() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = TickStorage::open(temp_dir.path().join("test.db")).unwrap();
+
+    let spec = BarSpec::Ticks(1);
+    let builder = BarBuilder::new(vec![spec]).unwrap();
+    let events = builder.process_tick(&tick(1000, 100.0, 1.0, 1)).unwrap();
+    storage.write_klines(&[events[0].kline.clone()]).unwrap();
+
+    let bars = storage.read_klines_by_symbol("BTCUSDT", spec.id()).unwrap();
+    assert_eq!(bars.len(), 1);
+    assert_eq!(bars[0].number_of_trades, 1);
+}
+
+#[test]
+fn test_range_bars() {
+    let spec = BarSpec::Range(10.0);
+    let builder = BarBuilder::new(vec![spec]).unwrap();
+
+    builder.process_tick(&tick(1000, 100.0, 1.0, 1)).unwrap();
+    builder.process_tick(&tick(1001, 105.0, 1.0, 2)).unwrap();
+    // 价差恰好达到 10，收线
+    let events = builder.process_tick(&tick(1002, 95.0, 1.0, 3)).unwrap();
+    assert!(events[0].is_closed);
+    assert_eq!(events[0].kline.high - events[0].kline.low, 10.0);
+
+    builder.process_tick(&tick(1003, 100.0, 1.0, 4)).unwrap();
+    // 超出价差的成交开启新 K 线
+    let events = builder.process_tick(&tick(1004, 112.0, 1.0, 5)).unwrap();
+    assert_eq!(events.len(), 2);
+    assert!(events[0].is_closed);
+    assert_eq!(events[0].kline.close, 100.0);
+    assert!(!events[1].is_closed);
+    assert_eq!(events[1].kline.open, 112.0);
+}
+
+#[test]
+fn test_renko_bricks_and_reversal() {
+    let spec = BarSpec::Renko { box_size: 10.0, reversal: 2 };
+    let builder = BarBuilder::new(vec![spec]).unwrap();
+
+    builder.process_tick(&tick(1000, 100.0, 1.0, 1)).unwrap();
+    // 上涨 25，生成两块上涨砖 100->110->120
+    let events = builder.process_tick(&tick(1001, 125.0, 2.0, 2)).unwrap();
+    let bricks: Vec<_> = events.iter().filter(|e| e.is_closed).map(|e| &e.kline).collect();
+    assert_eq!(bricks.len(), 2);
+    assert_eq!((bricks[0].open, bricks[0].close), (100.0, 110.0));
+    assert_eq!((bricks[1].open, bricks[1].close), (110.0, 120.0));
+    assert_eq!(bricks[0].volume, 3.0);
+    assert_eq!(bricks[1].volume, 0.0);
+
+    // 回撤 15 不足 2 格，不生成砖块
+    let events = builder.process_tick(&tick(1002, 105.0, 1.0, 3)).unwrap();
+    assert!(events.iter().all(|e| !e.is_closed));
+
+    // 回撤到 100 满足 2 格反转，反向砖从上一块的开盘价开始
+    let events = builder.process_tick(&tick(1003, 100.0, 1.0, 4)).unwrap();
+    let bricks: Vec<_> = events.iter().filter(|e| e.is_closed).collect();
+    assert_eq!(bricks.len(), 1);
+    assert_eq!((bricks[0].kline.open, bricks[0].kline.close), (110.0, 100.0));
+    assert_eq!(BarSpec::from_id(bricks[0].kline.interval), Some(spec));
+}
+
+#[test]
+fn test_heikin_ashi() {
+    let builder = HeikinAshiBuilder::new();
+
+    let mut first = KLine::new("BTCUSDT".to_string(), 0, 60, 100.0);
+    first.high = 110.0;
+    first.low = 90.0;
+    first.close = 104.0;
+    let events = builder.process_kline(&first, false);
+    assert_eq!(events.len(), 1);
+    let ha = &events[0].kline;
+    assert_eq!(ha.interval, heikin_ashi_interval(60));
+    assert_eq!(ha.open, 102.0);
+    assert_eq!(ha.close, 101.0);
+
+    // 新周期开始，上一根 HA 完成
+    let second = KLine::new("BTCUSDT".to_string(), 60, 60, 104.0);
+    let events = builder.process_kline(&second, false);
+    assert_eq!(events.len(), 2);
+    assert!(events[0].is_closed);
+    assert_eq!(events[0].kline.timestamp, 0);
+    assert!(!events[1].is_closed);
+    assert_eq!(events[1].kline.open, (102.0 + 101.0) / 2.0);
+    assert_eq!(events[1].kline.high, 104.0);
+    assert_eq!(events[1].kline.low, 101.5);
+}
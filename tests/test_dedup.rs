@@ -0,0 +1,52 @@
+use mdi::{Tick, TradeDedup};
+
+fn tick(symbol: &str, trade_id: u64) -> Tick {
+    Tick::new(symbol.to_string(), 1000000, 1000000, 100.0, 1.0, false, trade_id)
+}
+
+#[test]
+fn test_dedup_duplicates_and_out_of_order() {
+    let dedup = TradeDedup::new(10);
+
+    assert!(dedup.check(&tick("BTCUSDT", 100)));
+    assert!(dedup.check(&tick("BTCUSDT", 102)));
+    // 窗口内乱序到达
+    assert!(dedup.check(&tick("BTCUSDT", 101)));
+    assert!(!dedup.check(&tick("BTCUSDT", 101)));
+    assert!(!dedup.check(&tick("BTCUSDT", 102)));
+
+    // 早于窗口的 id 无法确认，丢弃但计为过期
+    assert!(dedup.check(&tick("BTCUSDT", 120)));
+    assert!(!dedup.check(&tick("BTCUSDT", 105)));
+    assert!(dedup.check(&tick("BTCUSDT", 111)));
+
+    // 品种之间互不影响
+    assert!(dedup.check(&tick("ETHUSDT", 101)));
+
+    assert_eq!(dedup.duplicates("BTCUSDT"), 2);
+    assert_eq!(dedup.stale("BTCUSDT"), 1);
+    assert_eq!(dedup.duplicates("ETHUSDT"), 0);
+    let stats = dedup.get_stats();
+    assert_eq!(stats.total_duplicates, 2);
+    assert_eq!(stats.total_stale, 1);
+    assert_eq!(stats.duplicates["BTCUSDT"], 2);
+
+    // 最大 trade_id 不溢出
+    assert!(dedup.check(&tick("SOLUSDT", u64::MAX)));
+    assert!(!dedup.check(&tick("SOLUSDT", u64::MAX)));
+    assert!(dedup.check(&tick("SOLUSDT", u64::MAX - 1)));
+}
+
+#[test]
+fn test_dedup_replayed_stream() {
+    let dedup = TradeDedup::default();
+    let mut accepted = 0;
+    // 重连后重放最后 50 笔
+    for id in (0..200).chain(150..250) {
+        if dedup.check(&tick("BTCUSDT", id)) {
+            accepted += 1;
+        }
+    }
+    assert_eq!(accepted, 250);
+    assert_eq!(dedup.duplicates("BTCUSDT"), 50);
+}
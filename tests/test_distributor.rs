@@ -1,5 +1,5 @@
 use mdi::KLine;
-use mdi::distributor::Distributor;
+use mdi::distributor::{Distributor, SubscriptionPattern};
 
 #[tokio::test]
 async fn test_distributor_broadcast() {
@@ -48,3 +48,43 @@ async fn test_distributor_multiple_symbols() {
     assert_eq!(msg1.kline.symbol, "BTCUSDT");
     assert_eq!(msg2.kline.symbol, "ETHUSDT");
 }
+
+#[tokio::test]
+async fn test_distributor_pattern_subscriptions() {
+    let distributor = Distributor::new(100);
+
+    // 发布过的品种周期已有缓存的分发表，新订阅需要并入
+    assert_eq!(distributor.broadcast_kline(KLine::new("BTCUSDT".to_string(), 1000, 60, 100.0), false), 0);
+
+    let mut btc_all = distributor.subscribe_pattern(&SubscriptionPattern::all().symbol("BTCUSDT"));
+    let mut minute = distributor.subscribe_pattern(&"*:1m".parse().unwrap());
+    let mut usdt = distributor.subscribe_pattern(&SubscriptionPattern::all().symbol("*USDT").interval(300));
+    let mut exact = distributor.subscribe_pattern(&"ETHUSDT:60".parse().unwrap());
+    assert_eq!(distributor.subscriber_count("BTCUSDT", 60), 2);
+    assert_eq!(distributor.subscriber_count("ETHUSDT", 60), 2);
+
+    assert_eq!(distributor.broadcast_kline(KLine::new("BTCUSDT".to_string(), 1000, 60, 100.0), false), 2);
+    assert_eq!(distributor.broadcast_kline(KLine::new("BTCUSDT".to_string(), 1000, 300, 100.0), false), 2);
+    assert_eq!(distributor.broadcast_kline(KLine::new("ETHUSDT".to_string(), 1000, 60, 50.0), true), 2);
+    assert_eq!(distributor.broadcast_kline(KLine::new("ETHBTC".to_string(), 1000, 300, 0.05), false), 0);
+
+    let intervals: Vec<u64> = [btc_all.recv().await.unwrap(), btc_all.recv().await.unwrap()]
+        .iter()
+        .map(|event| event.kline.interval)
+        .collect();
+    assert_eq!(intervals, vec![60, 300]);
+    assert_eq!(minute.recv().await.unwrap().kline.symbol, "BTCUSDT");
+    assert_eq!(minute.recv().await.unwrap().kline.symbol, "ETHUSDT");
+    assert_eq!(usdt.recv().await.unwrap().kline.symbol, "BTCUSDT");
+    assert!(usdt.try_recv().is_err());
+    assert!(exact.recv().await.unwrap().is_closed);
+
+    let mut channels = distributor.active_channels();
+    channels.sort();
+    assert_eq!(channels, vec!["*:60", "*USDT:300", "BTCUSDT:*", "ETHUSDT:60"]);
+
+    assert!(SubscriptionPattern::all().symbol("BTC?SDT").matches("BTCUSDT", 1));
+    assert!(!SubscriptionPattern::all().symbol("*USDT").matches("USDTBTC", 1));
+    assert!("BTCUSDT".parse::<SubscriptionPattern>().is_err());
+    assert!("BTCUSDT:xyz".parse::<SubscriptionPattern>().is_err());
+}
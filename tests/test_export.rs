@@ -0,0 +1,63 @@
+use mdi::export::{export_klines, export_ticks, ExportFormat, ExportOptions};
+use mdi::{KLine, MemoryStore, Tick, TickStore};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use tempfile::TempDir;
+
+fn storage_with_data() -> MemoryStore {
+    let storage = MemoryStore::new();
+    let mut ticks = Vec::new();
+    for symbol in ["BTCUSDT", "ETHUSDT"] {
+        for id in 0..100 {
+            ticks.push(Tick::new(symbol.to_string(), 1000 + id * 10, 1000 + id * 10, 100.0 + id as f64, 1.0, id % 2 == 0, id));
+        }
+    }
+    storage.write_ticks(&ticks).unwrap();
+    let klines: Vec<KLine> = (0..10).map(|i| KLine::new("BTCUSDT".to_string(), i * 60, 60, 100.0)).collect();
+    storage.write_klines(&klines).unwrap();
+    storage
+}
+
+#[test]
+fn test_export_ticks_csv() {
+    let storage = storage_with_data();
+
+    let mut out = Vec::new();
+    let options = ExportOptions::new(ExportFormat::Csv).page_size(7);
+    let symbols = vec!["ETHUSDT".to_string(), "BTCUSDT".to_string()];
+    // [1100, 1500) 每个品种 40 条
+    let stats = export_ticks(&storage, &symbols, 1100, 1500, &options, &mut out).unwrap();
+    assert_eq!(stats.rows, 80);
+
+    let text = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "symbol,timestamp,event_time,price,quantity,is_buyer_maker,trade_id");
+    assert_eq!(lines.len(), 81);
+    assert!(lines[1].starts_with("ETHUSDT,1100,1100,110.0,1.0,true,10"));
+    assert!(lines[80].starts_with("BTCUSDT,1490,"));
+}
+
+#[test]
+fn test_export_parquet_row_groups() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = storage_with_data();
+    let symbols = storage.symbols();
+
+    let path = temp_dir.path().join("ticks.parquet");
+    let options = ExportOptions::new(ExportFormat::Parquet).row_group_size(64).page_size(50);
+    let stats = export_ticks(&storage, &symbols, 0, u64::MAX, &options, std::fs::File::create(&path).unwrap()).unwrap();
+    assert_eq!(stats.rows, 200);
+    assert_eq!(stats.row_groups, 4);
+
+    let reader = SerializedFileReader::new(std::fs::File::open(&path).unwrap()).unwrap();
+    let metadata = reader.metadata();
+    assert_eq!(metadata.file_metadata().num_rows(), 200);
+    assert_eq!(metadata.num_row_groups(), 4);
+    let schema = metadata.file_metadata().schema_descr();
+    assert_eq!(schema.column(3).name(), "price");
+
+    let path = temp_dir.path().join("klines.parquet");
+    let options = ExportOptions::new(ExportFormat::Parquet);
+    let stats = export_klines(&storage, &symbols, 60, 120, 420, &options, std::fs::File::create(&path).unwrap()).unwrap();
+    assert_eq!(stats.rows, 5);
+    assert_eq!(stats.row_groups, 1);
+}
@@ -0,0 +1,104 @@
+use mdi::import::{ArchiveFile, ArchiveKind, Importer};
+use mdi::{Interval, MemoryStore, TickStore};
+use std::io::Write;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// 10 笔成交，每 20 秒一笔，时间为微秒
+fn trades_csv() -> String {
+    (0..10u64)
+        .map(|i| {
+            let time_us = (1_704_067_200_000 + i * 20_000) * 1000;
+            format!("{},{}.0,0.5,0,{},{},True\n", 100 + i, 42000 + i, time_us, i % 2 == 0)
+        })
+        .collect()
+}
+
+fn write_zip(path: &std::path::Path, name: &str, content: &str) {
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    zip.start_file(name, zip::write::SimpleFileOptions::default()).unwrap();
+    zip.write_all(content.as_bytes()).unwrap();
+    zip.finish().unwrap();
+}
+
+#[test]
+fn test_parse_archive_names() {
+    let file = ArchiveFile::parse("/data/BTCUSDT-trades-2024-01-01.zip").unwrap();
+    assert_eq!(file.symbol, "BTCUSDT");
+    assert_eq!(file.kind, ArchiveKind::Trades);
+
+    let file = ArchiveFile::parse("ETHUSDT-1h-2024-01.csv").unwrap();
+    assert_eq!(file.kind, ArchiveKind::KLines(Interval::Seconds(3600)));
+    assert!(ArchiveFile::parse("notes.txt").is_err());
+}
+
+#[test]
+fn test_import_trades_resume_and_rebuild() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = MemoryStore::new();
+    let archive = temp_dir.path().join("BTCUSDT-trades-2024-01-01.zip");
+    write_zip(&archive, "BTCUSDT-trades-2024-01-01.csv", &trades_csv());
+
+    // 模拟上次导入在第 4 行后中断
+    storage.write_meta("import:BTCUSDT-trades-2024-01-01.zip", br#"{"rows":4,"complete":false}"#).unwrap();
+
+    let importer = Importer::new(Arc::new(storage.clone())).batch_size(3);
+    let stats = importer.import_path(&archive).unwrap();
+    assert_eq!(stats.resumed_rows, 4);
+    assert_eq!(stats.ticks, 6);
+    assert!(storage.read_tick("BTCUSDT", 103).unwrap().is_none());
+    let tick = storage.read_tick("BTCUSDT", 109).unwrap().unwrap();
+    assert_eq!(tick.timestamp, 1_704_067_380_000);
+    assert_eq!(tick.price, 42009.0);
+
+    // 已完成的文件不再导入
+    let stats = importer.import_path(temp_dir.path()).unwrap();
+    assert_eq!((stats.files, stats.skipped_files, stats.ticks), (0, 1, 0));
+
+    // 重新导入全部成交后生成 1 分钟 K 线
+    storage.write_meta("import:BTCUSDT-trades-2024-01-01.zip", b"{\"rows\":0,\"complete\":false}").unwrap();
+    let stats = importer.import_path(&archive).unwrap();
+    assert_eq!(stats.ticks, 10);
+    assert_eq!(storage.read_ticks_by_symbol("BTCUSDT", 100).unwrap().len(), 10);
+
+    let (from_ms, to_ms) = stats.tick_ranges["BTCUSDT"];
+    let written = importer.rebuild_klines("BTCUSDT", &[Interval::Seconds(60)], from_ms, to_ms).unwrap();
+    assert_eq!(written, 4);
+    let kline = storage.read_kline("BTCUSDT", 60, 1_704_067_200).unwrap().unwrap();
+    assert_eq!((kline.open, kline.close, kline.number_of_trades), (42000.0, 42002.0, 3));
+    assert_eq!(kline.taker_sell_trades, 2);
+
+    // 续传只导入了后 6 笔，重建时边界 K 线仍包含之前导入的成交
+    storage.write_meta("import:BTCUSDT-trades-2024-01-01.zip", br#"{"rows":4,"complete":false}"#).unwrap();
+    let stats = importer.import_path(&archive).unwrap();
+    let (from_ms, to_ms) = stats.tick_ranges["BTCUSDT"];
+    assert_eq!(from_ms, 1_704_067_280_000);
+    let written = importer.rebuild_klines("BTCUSDT", &[Interval::Seconds(60)], from_ms, to_ms).unwrap();
+    assert_eq!(written, 3);
+    let kline = storage.read_kline("BTCUSDT", 60, 1_704_067_260).unwrap().unwrap();
+    assert_eq!((kline.open, kline.close, kline.number_of_trades), (42003.0, 42005.0, 3));
+    let kline = storage.read_kline("BTCUSDT", 60, 1_704_067_200).unwrap().unwrap();
+    assert_eq!(kline.number_of_trades, 3);
+}
+
+#[test]
+fn test_import_klines_csv_with_header() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = MemoryStore::new();
+    let path = temp_dir.path().join("ETHUSDT-1m-2024-01.csv");
+    std::fs::write(
+        &path,
+        "open_time,open,high,low,close,volume,close_time,quote_volume,count,taker_buy_volume,taker_buy_quote_volume,ignore\n\
+         1704067200000,2300.0,2310.0,2290.0,2305.0,10.0,1704067259999,23000.0,50,6.0,13800.0,0\n\
+         1704067260000,2305.0,2306.0,2301.0,2302.0,4.0,1704067319999,9210.0,20,1.0,2302.0,0\n",
+    )
+    .unwrap();
+
+    let stats = Importer::new(Arc::new(storage.clone())).import_path(&path).unwrap();
+    assert_eq!(stats.klines, 2);
+
+    let kline = storage.read_kline("ETHUSDT", 60, 1_704_067_200).unwrap().unwrap();
+    assert_eq!((kline.high, kline.low, kline.number_of_trades), (2310.0, 2290.0, 50));
+    assert_eq!(kline.taker_sell_volume, 4.0);
+    assert_eq!(kline.close_time, 1_704_067_260);
+}
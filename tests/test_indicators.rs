@@ -0,0 +1,96 @@
+use mdi::{Distributor, IndicatorEngine, IndicatorSpec, KLine};
+
+fn bar(timestamp: u64, close: f64) -> KLine {
+    let mut kline = KLine::new("BTCUSDT".to_string(), timestamp, 60, close);
+    kline.high = close + 1.0;
+    kline.low = close - 1.0;
+    kline
+}
+
+#[test]
+fn test_sma_ema_match_batch() {
+    let engine = IndicatorEngine::new(vec![IndicatorSpec::Sma(3), IndicatorSpec::Ema(3)]);
+    let closes = [10.0, 11.0, 12.0, 13.0, 14.0, 12.0];
+
+    let mut ema = (10.0 + 11.0 + 12.0) / 3.0;
+    for (i, close) in closes.iter().enumerate() {
+        let values = engine.process_kline(&bar(i as u64 * 60, *close), true);
+        if i < 2 {
+            assert!(values.is_empty());
+            continue;
+        }
+        let sma = closes[i - 2..=i].iter().sum::<f64>() / 3.0;
+        if i > 2 {
+            ema += 0.5 * (close - ema);
+        }
+        assert!((values[0].values[0] - sma).abs() < 1e-9);
+        assert!((values[1].values[0] - ema).abs() < 1e-9);
+        assert!(values.iter().all(|v| v.is_closed));
+    }
+}
+
+#[test]
+fn test_tentative_values_do_not_commit() {
+    let engine = IndicatorEngine::new(vec![IndicatorSpec::Sma(2)]);
+    engine.process_kline(&bar(0, 10.0), true);
+
+    // 同一根 K 线多次修正，只有最后一次被提交
+    let v = engine.process_kline(&bar(60, 20.0), false);
+    assert_eq!(v[0].values[0], 15.0);
+    assert!(!v[0].is_closed);
+    let v = engine.process_kline(&bar(60, 30.0), false);
+    assert_eq!(v[0].values[0], 20.0);
+
+    // 新的时间戳使上一根 K 线完成
+    let v = engine.process_kline(&bar(120, 40.0), false);
+    assert_eq!(v.len(), 2);
+    assert!(v[0].is_closed);
+    assert_eq!(v[0].timestamp, 60);
+    assert_eq!(v[0].values[0], 20.0);
+    assert_eq!(v[1].values[0], 35.0);
+
+    // 过期更新被忽略
+    assert!(engine.process_kline(&bar(60, 0.0), false).is_empty());
+    assert_eq!(engine.latest("BTCUSDT", 60, "SMA(2)").unwrap().values[0], 35.0);
+}
+
+#[test]
+fn test_rsi_macd_bollinger_atr() {
+    let engine = IndicatorEngine::new(vec![
+        IndicatorSpec::Rsi(3),
+        IndicatorSpec::Macd { fast: 2, slow: 3, signal: 2 },
+        IndicatorSpec::Bollinger { period: 3, k: 2.0 },
+        IndicatorSpec::Atr(3),
+    ]);
+    for i in 0..10u64 {
+        engine.process_kline(&bar(i * 60, 100.0 + i as f64), true);
+    }
+
+    // 单边上涨
+    assert_eq!(engine.latest("BTCUSDT", 60, "RSI(3)").unwrap().values[0], 100.0);
+    let macd = engine.latest("BTCUSDT", 60, "MACD(2,3,2)").unwrap().values;
+    assert!(macd[0] > 0.0);
+    assert!((macd[2] - (macd[0] - macd[1])).abs() < 1e-12);
+    let bb = engine.latest("BTCUSDT", 60, "BB(3,2)").unwrap().values;
+    assert_eq!(bb[0], 108.0);
+    assert!((bb[1] - bb[0] - 2.0 * (2.0f64 / 3.0).sqrt()).abs() < 1e-9);
+    assert!((bb[0] - bb[2] - (bb[1] - bb[0])).abs() < 1e-9);
+    // 每根真实波幅 = 2
+    assert!((engine.latest("BTCUSDT", 60, "ATR(3)").unwrap().values[0] - 2.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_indicator_distribution() {
+    let engine = IndicatorEngine::new(vec![IndicatorSpec::Ema(1)]);
+    let distributor = Distributor::new(16);
+    let mut rx = distributor.subscribe_indicators("BTCUSDT", 60);
+
+    for value in engine.process_kline(&bar(0, 10.0), false) {
+        assert_eq!(distributor.broadcast_indicator(value), 1);
+    }
+
+    let value = rx.recv().await.unwrap();
+    assert_eq!(value.name, "EMA(1)");
+    assert_eq!(value.values, vec![10.0]);
+    assert!(!value.is_closed);
+}
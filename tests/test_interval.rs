@@ -0,0 +1,127 @@
+use mdi::{Interval, Session, Tick};
+use mdi::kline::KLineBuilder;
+
+// 2024-01-01 00:00:00 UTC，周一
+const JAN_1_2024: u64 = 1704067200;
+
+#[test]
+fn test_fixed_interval_alignment() {
+    let interval = Interval::Seconds(300);
+    assert_eq!(interval.bucket_start(JAN_1_2024 + 299), JAN_1_2024);
+    assert_eq!(interval.bucket_end(JAN_1_2024), JAN_1_2024 + 300);
+    assert_eq!(interval.id(), 300);
+}
+
+#[test]
+fn test_calendar_interval_ids() {
+    let intervals = [
+        Interval::Seconds(86400),
+        Interval::Daily(Session::new(8 * 3600, 0).unwrap()),
+        Interval::Daily(Session::new(8 * 3600, 8 * 3600).unwrap()),
+        Interval::Daily(Session::new(-5 * 3600, 0).unwrap()),
+        Interval::Weekly(Session::utc()),
+        Interval::Monthly(Session::new(-5 * 3600, 9 * 3600 + 30 * 60).unwrap()),
+    ];
+    let mut ids: Vec<u64> = intervals.iter().map(Interval::id).collect();
+    for (interval, &id) in intervals.iter().zip(&ids) {
+        assert_eq!(Interval::from_id(id), Some(*interval));
+    }
+    ids.sort_unstable();
+    ids.dedup();
+    assert_eq!(ids.len(), intervals.len());
+
+    assert_eq!(Interval::from_id(mdi::BarSpec::Ticks(100).id()), None);
+    assert_eq!(Interval::from_id(0), None);
+
+    // UTC 日线与固定一天周期是同一周期
+    assert_eq!(Interval::Daily(Session::utc()).id(), 86400);
+    assert_eq!(Interval::Daily(Session::utc()).normalized(), Interval::Seconds(86400));
+
+    // 超出编码范围的时段
+    assert!(Session::new(24 * 3600, 0).is_err());
+    assert!(Session::new(-24 * 3600, 0).is_err());
+    assert!(Session::new(0, 86400).is_err());
+    assert_eq!(Session::new(-5 * 3600, 60).unwrap().utc_offset(), -5 * 3600);
+}
+
+#[test]
+fn test_daily_timezone_alignment() {
+    // UTC+8 零点 = 前一日 16:00 UTC
+    let daily = Interval::Daily(Session::new(8 * 3600, 0).unwrap());
+    assert_eq!(daily.bucket_start(JAN_1_2024), JAN_1_2024 - 8 * 3600);
+
+    // UTC+8 的 08:00 为界 = UTC 零点
+    let session = Interval::Daily(Session::new(8 * 3600, 8 * 3600).unwrap());
+    assert_eq!(session.bucket_start(JAN_1_2024 + 100), JAN_1_2024);
+    assert_eq!(session.bucket_start(JAN_1_2024 - 1), JAN_1_2024 - 86400);
+}
+
+#[test]
+fn test_weekly_starts_monday() {
+    let weekly = Interval::Weekly(Session::utc());
+    // 2024-01-03 12:00 UTC（周三）
+    let start = weekly.bucket_start(JAN_1_2024 + 2 * 86400 + 12 * 3600);
+    assert_eq!(start, JAN_1_2024);
+    assert_eq!(weekly.bucket_end(start), JAN_1_2024 + 7 * 86400);
+}
+
+#[test]
+fn test_monthly_boundaries() {
+    let monthly = Interval::Monthly(Session::utc());
+    // 2024-02-15 -> 2024-02-01 ~ 2024-03-01（闰年 29 天）
+    let start = monthly.bucket_start(JAN_1_2024 + 45 * 86400);
+    assert_eq!(start, JAN_1_2024 + 31 * 86400);
+    assert_eq!(monthly.bucket_end(start), start + 29 * 86400);
+}
+
+#[test]
+fn test_interval_parse_and_display() {
+    assert_eq!("4h".parse::<Interval>().unwrap(), Interval::Seconds(14400));
+    assert_eq!("1w".parse::<Interval>().unwrap(), Interval::Weekly(Session::utc()));
+    assert_eq!(
+        "1d@+08:00".parse::<Interval>().unwrap(),
+        Interval::Daily(Session::new(8 * 3600, 0).unwrap())
+    );
+    assert!("5m@+08:00".parse::<Interval>().is_err());
+
+    assert_eq!("1d".parse::<Interval>().unwrap(), Interval::Seconds(86400));
+    assert_eq!("1d@+00:00".parse::<Interval>().unwrap(), Interval::Seconds(86400));
+    assert_eq!(Interval::Daily(Session::utc()).to_string(), "1d");
+
+    for label in ["15m", "1d", "1M", "1d@+08:00+08:00", "1w@-05:00"] {
+        assert_eq!(label.parse::<Interval>().unwrap().to_string(), label);
+    }
+
+    // 偏移必须是 [+-]HH:MM
+    for label in ["1d@+8:000", "1d@+08:0a", "1d@08:00", "1d@+08-00", "1d@+08:00+8:00", "1d@+24:00"] {
+        assert!(label.parse::<Interval>().is_err(), "{}", label);
+    }
+}
+
+#[test]
+fn test_kline_builder_calendar_intervals() {
+    let builder = KLineBuilder::with_intervals(vec![
+        Interval::Monthly(Session::utc()),
+        Interval::Daily(Session::new(8 * 3600, 0).unwrap()),
+    ]);
+
+    let ts_ms = (JAN_1_2024 + 45 * 86400) * 1000;
+    let tick = Tick::new("BTCUSDT".to_string(), ts_ms, ts_ms, 100.0, 1.0, true, 1);
+    builder.process_tick(&tick);
+
+    let monthly = builder.get_latest_kline("BTCUSDT", Interval::Monthly(Session::utc()).id()).unwrap();
+    assert_eq!(monthly.open_time, JAN_1_2024 + 31 * 86400);
+    assert_eq!(monthly.close_time, JAN_1_2024 + 60 * 86400);
+
+    let daily = builder.get_latest_kline("BTCUSDT", Interval::Daily(Session::new(8 * 3600, 0).unwrap()).id()).unwrap();
+    assert_eq!(daily.timestamp, JAN_1_2024 + 45 * 86400 - 8 * 3600);
+}
+
+#[test]
+fn test_interval_alignment() {
+    assert!(Interval::Seconds(300).is_aligned_to(60));
+    assert!(!Interval::Seconds(90).is_aligned_to(60));
+    assert!(Interval::Daily(Session::new(8 * 3600, 0).unwrap()).is_aligned_to(3600));
+    assert!(!Interval::Daily(Session::new(5 * 3600 + 30 * 60, 0).unwrap()).is_aligned_to(3600));
+    assert!(Interval::Monthly(Session::utc()).is_aligned_to(60));
+}
@@ -1,6 +1,7 @@
-use mdi::KLine;
-use mdi::kline::KLineBuilder;
-use mdi::Tick;
+use mdi::kline::{rollup, KLineBuilder, KLineWorkers};
+use mdi::{Interval, MemoryStore, Tick, TickStore};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 #[test]
 fn test_kline_builder() {
@@ -14,10 +15,10 @@ fn test_kline_builder() {
     builder.process_tick(&tick1);
     builder.process_tick(&tick2);
 
-    let kLine = builder.get_latest_kline("BTCUSDT", 60).unwrap();
-    assert_eq!(kLine.close, 102.0);
-    assert_eq!(kLine.volume, 3.0);  // 1.0 + 2.0
-    assert_eq!(kLine.number_of_trades, 2);
+    let kline = builder.get_latest_kline("BTCUSDT", 60).unwrap();
+    assert_eq!(kline.close, 102.0);
+    assert_eq!(kline.volume, 3.0);  // 1.0 + 2.0
+    assert_eq!(kline.number_of_trades, 2);
 }
 
 #[test]
@@ -31,3 +32,187 @@ fn test_kline_stats() {
     assert_eq!(stats.total_symbols, 1);
     assert_eq!(stats.total_klines, 6); // 6 个周期
 }
+
+#[test]
+fn test_kline_rollup_from_base_interval() {
+    let builder = KLineBuilder::standard();
+
+    // 3 分钟内每 20 秒一笔，价格递增
+    let mut last = Vec::new();
+    for i in 0..9u64 {
+        let ts = 1_700_000_100_000 + i * 20_000;
+        let tick = Tick::new("BTCUSDT".to_string(), ts, ts, 100.0 + i as f64, 1.0, i % 2 == 0, i);
+        last = builder.process_tick(&tick);
+    }
+
+    // 返回值中的高周期 K 线包含未完成的 1m K 线
+    let five_min = &last[1].kline;
+    assert_eq!(five_min.interval, 300);
+    assert_eq!(five_min.close, 108.0);
+
+    let bars_5m = builder.get_klines("BTCUSDT", 300);
+    let total: f64 = bars_5m.iter().map(|k| k.volume).sum();
+    assert_eq!(total, 9.0);
+    assert_eq!(builder.get_latest_kline("BTCUSDT", 300).unwrap().close, 108.0);
+    assert_eq!(builder.get_latest_kline("BTCUSDT", 86400).unwrap().number_of_trades, 9);
+
+    // 由 1m K 线重建的 5m K 线与构建器一致
+    let rebuilt = rollup(&builder.get_klines("BTCUSDT", 60), &Interval::Seconds(300));
+    assert_eq!(rebuilt.len(), bars_5m.len());
+    for (a, b) in rebuilt.iter().zip(bars_5m.iter()) {
+        assert_eq!(a.timestamp, b.timestamp);
+        assert_eq!(a.open, b.open);
+        assert_eq!(a.high, b.high);
+        assert_eq!(a.low, b.low);
+        assert_eq!(a.close, b.close);
+        assert_eq!(a.volume, b.volume);
+        assert_eq!(a.taker_buy_volume, b.taker_buy_volume);
+    }
+}
+
+#[test]
+fn test_kline_rollup_late_tick() {
+    let builder = KLineBuilder::new(vec![60, 300]);
+
+    let tick = |ts: u64, price: f64, id: u64| {
+        Tick::new("BTCUSDT".to_string(), ts, ts, price, 1.0, true, id)
+    };
+    builder.process_tick(&tick(1_200_000, 100.0, 1));
+    builder.process_tick(&tick(1_260_000, 101.0, 2));
+    // 迟到的 Tick 落在已完成的 1m K 线中
+    builder.process_tick(&tick(1_230_000, 90.0, 3));
+
+    let five_min = builder.get_latest_kline("BTCUSDT", 300).unwrap();
+    assert_eq!(five_min.volume, 3.0);
+    assert_eq!(five_min.low, 90.0);
+    assert_eq!(five_min.close, 101.0);
+}
+
+#[test]
+fn test_kline_closed_events() {
+    let builder = KLineBuilder::new(vec![60, 300]);
+
+    let tick = |ts: u64, price: f64, id: u64| {
+        Tick::new("BTCUSDT".to_string(), ts, ts, price, 1.0, true, id)
+    };
+    let events = builder.process_tick(&tick(1_200_000, 100.0, 1));
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|event| !event.is_closed));
+    builder.process_tick(&tick(1_230_000, 102.0, 2));
+
+    // 进入下一分钟：1m K 线完成，5m K 线仍在进行
+    let events = builder.process_tick(&tick(1_260_000, 101.0, 3));
+    let closed: Vec<_> = events.iter().filter(|event| event.is_closed).collect();
+    assert_eq!(closed.len(), 1);
+    assert_eq!(closed[0].kline.interval, 60);
+    assert_eq!(closed[0].kline.timestamp, 1_200);
+    assert_eq!(closed[0].kline.close, 102.0);
+    assert_eq!(closed[0].kline.volume, 2.0);
+    assert_eq!(events.iter().filter(|event| !event.is_closed).count(), 2);
+
+    // 迟到的 Tick 不会再次完成 K 线
+    let events = builder.process_tick(&tick(1_250_000, 90.0, 4));
+    assert!(events.iter().all(|event| !event.is_closed));
+
+    // 进入下一个 5 分钟：两个周期都完成，5m 包含全部已完成的 1m K 线
+    let events = builder.process_tick(&tick(1_500_000, 105.0, 5));
+    let closed: Vec<_> = events.iter().filter(|event| event.is_closed).map(|event| &event.kline).collect();
+    assert_eq!(closed.len(), 2);
+    assert_eq!((closed[0].interval, closed[0].timestamp, closed[0].close), (60, 1_260, 101.0));
+    assert_eq!((closed[1].interval, closed[1].timestamp), (300, 1_200));
+    assert_eq!(closed[1].volume, 4.0);
+    assert_eq!(closed[1].low, 90.0);
+    assert_eq!(closed[1].close, 101.0);
+}
+
+#[test]
+fn test_kline_workers_sharded() {
+    let processed = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&processed);
+    let workers = KLineWorkers::spawn(KLineBuilder::new(vec![60, 300]), 4, move |_, events| {
+        assert_eq!(events.iter().filter(|event| !event.is_closed).count(), 2);
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    assert_eq!(workers.num_workers(), 4);
+    assert_eq!(workers.shard_of("BTCUSDT"), workers.shard_of("BTCUSDT"));
+
+    let symbols = ["BTCUSDT", "ETHUSDT", "SOLUSDT", "BNBUSDT"];
+    for i in 0..400u64 {
+        let symbol = symbols[(i % 4) as usize];
+        let ts = 1_200_000 + i * 1000;
+        workers
+            .dispatch(Tick::new(symbol.to_string(), ts, ts, 100.0, 1.0, true, i))
+            .unwrap();
+    }
+
+    let builder = workers.builder().clone();
+    workers.shutdown().unwrap();
+
+    assert_eq!(processed.load(Ordering::SeqCst), 400);
+    assert_eq!(builder.get_stats().total_symbols, 4);
+    for symbol in symbols {
+        let total: f64 = builder.get_klines(symbol, 300).iter().map(|k| k.volume).sum();
+        assert_eq!(total, 100.0);
+    }
+}
+
+#[test]
+fn test_kline_workers_report_panics() {
+    // 容量 1 的队列：dispatch 在工作线程追上之前阻塞
+    let workers = KLineWorkers::spawn_with_queue(KLineBuilder::new(vec![60]), 2, 1, |tick, _| {
+        assert!(tick.trade_id != 3, "bad tick");
+    });
+    for i in 0..10u64 {
+        // 出错的线程退出后，路由到它的 Tick 会返回错误
+        let _ = workers.dispatch(Tick::new("BTCUSDT".to_string(), i * 1000, i * 1000, 100.0, 1.0, true, i));
+    }
+    let error = workers.shutdown().unwrap_err();
+    assert!(error.to_string().contains("bad tick"), "{}", error);
+}
+
+#[test]
+fn test_kline_warm_start_from_storage() {
+    let storage = MemoryStore::new();
+
+    let day = 86_400_000u64 * 20_000;
+    let ticks: Vec<Tick> = (0..20u64)
+        .map(|i| {
+            let ts = day + i * 600_000; // 每 10 分钟一笔
+            Tick::new("BTCUSDT".to_string(), ts, ts, 100.0 + i as f64, 1.0, i % 2 == 0, 100 + i)
+        })
+        .collect();
+    // 前一天的 Tick 不参与回放
+    let old = Tick::new("BTCUSDT".to_string(), day - 1000, day - 1000, 50.0, 1.0, true, 99);
+    storage.write_tick(&old).unwrap();
+    storage.write_ticks(&ticks[..15]).unwrap();
+
+    let builder = KLineBuilder::standard();
+    let now = ticks[14].timestamp;
+    let stats = builder.warm_start(&storage, "BTCUSDT", now).unwrap();
+    assert_eq!(stats.ticks_replayed, 15);
+    assert_eq!(stats.last_trade_id, Some(114));
+    // 重复预热不会重复计数
+    assert_eq!(builder.warm_start(&storage, "BTCUSDT", now).unwrap().ticks_replayed, 0);
+
+    // 实时流与存储重叠的部分被忽略
+    assert!(builder.is_replayed(&ticks[14]));
+    assert!(!builder.is_replayed(&ticks[15]));
+    assert!(!builder.is_replayed(&Tick::new("ETHUSDT".to_string(), now, now, 1.0, 1.0, true, 1)));
+    for tick in &ticks[10..] {
+        builder.process_tick(tick);
+    }
+
+    let expected = KLineBuilder::standard();
+    for tick in &ticks {
+        expected.process_tick(tick);
+    }
+    for interval in [60, 3600, 86400] {
+        let a = builder.get_latest_kline("BTCUSDT", interval).unwrap();
+        let b = expected.get_latest_kline("BTCUSDT", interval).unwrap();
+        assert_eq!(a.volume, b.volume);
+        assert_eq!(a.open, b.open);
+        assert_eq!(a.close, b.close);
+        assert_eq!(a.number_of_trades, b.number_of_trades);
+    }
+    assert_eq!(builder.get_latest_kline("BTCUSDT", 86400).unwrap().volume, 20.0);
+}
@@ -22,3 +22,29 @@ fn test_kline_update() {
     assert_eq!(kline.volume, 15.0);
     assert_eq!(kline.number_of_trades, 2);
 }
+
+#[test]
+fn test_kline_taker_split() {
+    let mut kline = KLine::new("BTCUSDT".to_string(), 0, 60, 100.0);
+    // is_buyer_maker = false: 主动买入
+    kline.update(&Tick::new("BTCUSDT".to_string(), 0, 0, 100.0, 3.0, false, 1));
+    kline.update(&Tick::new("BTCUSDT".to_string(), 1, 1, 101.0, 1.0, true, 2));
+
+    assert_eq!(kline.taker_buy_volume, 3.0);
+    assert_eq!(kline.taker_buy_quote_volume, 300.0);
+    assert_eq!(kline.taker_buy_trades, 1);
+    assert_eq!(kline.taker_sell_volume, 1.0);
+    assert_eq!(kline.taker_sell_quote_volume, 101.0);
+    assert_eq!(kline.taker_sell_trades, 1);
+    assert_eq!(kline.delta(), 2.0);
+    assert_eq!(kline.order_flow_imbalance(), 0.5);
+}
+
+#[test]
+fn test_kline_deserialize_without_taker_fields() {
+    let json = r#"{"symbol":"BTCUSDT","timestamp":0,"interval":60,"open":1.0,"high":1.0,"low":1.0,
+        "close":1.0,"volume":0.0,"quote_asset_volume":0.0,"number_of_trades":0,"open_time":0,"close_time":60}"#;
+    let kline: KLine = serde_json::from_str(json).unwrap();
+    assert_eq!(kline.taker_buy_volume, 0.0);
+    assert_eq!(kline.order_flow_imbalance(), 0.0);
+}
@@ -0,0 +1,80 @@
+use mdi::storage::TickStorage;
+use mdi::{Distributor, KLineBuilder, ProfileConfig, Tick, VolumeProfile};
+use tempfile::TempDir;
+
+fn tick(ts: u64, price: f64, quantity: f64, is_buyer_maker: bool, id: u64) -> Tick {
+    Tick::new("BTCUSDT".to_string(), ts, ts, price, quantity, is_buyer_maker, id)
+}
+
+#[test]
+fn test_profile_levels_poc_value_area() {
+    let mut profile = VolumeProfile::new("BTCUSDT".to_string(), 60, 0, 10.0);
+    // 档位成交量: 100 -> 1, 110 -> 2, 120 -> 6, 130 -> 3, 140 -> 1
+    for (price, quantity) in [(101.0, 1.0), (115.0, 2.0), (120.0, 4.0), (129.9, 2.0), (130.0, 3.0), (149.0, 1.0)] {
+        profile.update(&tick(0, price, quantity, false, 0));
+    }
+    profile.update(&tick(0, 0.1 + 0.2 + 119.7, 0.0, true, 0));
+    profile.refresh(0.7);
+
+    let prices: Vec<f64> = profile.levels.iter().map(|l| l.price).collect();
+    assert_eq!(prices, vec![100.0, 110.0, 120.0, 130.0, 140.0]);
+    assert_eq!(profile.total_volume(), 13.0);
+    assert_eq!(profile.poc, 120.0);
+    // 6 -> +3(130) -> +2(110) = 11 >= 9.1
+    assert_eq!(profile.value_area_low, 110.0);
+    assert_eq!(profile.value_area_high, 130.0);
+}
+
+#[test]
+fn test_kline_builder_profiles() {
+    let builder = KLineBuilder::new(vec![60, 300])
+        .with_profiles(ProfileConfig::new(1.0).symbol_bucket("BTCUSDT", 5.0));
+
+    builder.process_tick(&tick(1_200_000, 100.0, 1.0, false, 1));
+    builder.process_tick(&tick(1_210_000, 103.0, 2.0, true, 2));
+    builder.process_tick(&tick(1_220_000, 106.0, 0.5, false, 3));
+
+    let profile = builder.get_latest_profile("BTCUSDT", 60).unwrap();
+    assert_eq!(profile.timestamp, 1200);
+    assert_eq!(profile.bucket_size, 5.0);
+    assert_eq!(profile.levels.len(), 2);
+    assert_eq!(profile.levels[0].buy_volume, 1.0);
+    assert_eq!(profile.levels[0].sell_volume, 2.0);
+    assert_eq!(profile.levels[0].delta(), -1.0);
+    assert_eq!(profile.poc, 100.0);
+    assert!(builder.drain_closed_profiles().is_empty());
+    assert_eq!(builder.drain_updated_profiles().len(), 2);
+    assert!(builder.drain_updated_profiles().is_empty());
+
+    // 进入下一分钟，上一分钟的分布完成
+    builder.process_tick(&tick(1_260_000, 104.0, 1.0, false, 4));
+    let closed = builder.drain_closed_profiles();
+    assert_eq!(closed.len(), 1);
+    assert_eq!(closed[0].interval, 60);
+    assert_eq!(closed[0].total_volume(), 3.5);
+    assert!(builder.drain_closed_profiles().is_empty());
+    assert_eq!(builder.get_latest_profile("BTCUSDT", 300).unwrap().total_volume(), 4.5);
+
+    // 已完成的分布不再留在内存中，迟到的成交不会重新创建
+    assert!(builder.get_profile("BTCUSDT", 60, 1200).is_none());
+    assert_eq!(builder.drain_updated_profiles().len(), 2);
+    builder.process_tick(&tick(1_230_000, 104.0, 1.0, false, 5));
+    assert!(builder.get_profile("BTCUSDT", 60, 1200).is_none());
+    let updated = builder.drain_updated_profiles();
+    assert_eq!(updated.iter().map(|p| p.interval).collect::<Vec<_>>(), vec![300]);
+
+    // 可存储
+    let temp_dir = TempDir::new().unwrap();
+    let storage = TickStorage::open(temp_dir.path().join("test.db")).unwrap();
+    storage.write_profiles(&closed).unwrap();
+    let read = storage.read_profile("BTCUSDT", 60, 1200).unwrap().unwrap();
+    assert_eq!(read, closed[0]);
+
+    // 可广播
+    let distributor = Distributor::new(16);
+    let mut rx = distributor.subscribe_profiles("BTCUSDT", 60);
+    assert_eq!(distributor.broadcast_profile(read, true), 1);
+    let event = rx.try_recv().unwrap();
+    assert!(event.is_closed);
+    assert_eq!(event.profile.poc, 100.0);
+}
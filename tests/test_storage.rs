@@ -1,5 +1,7 @@
-use mdi::{Tick, KLine};
-use mdi::storage::TickStorage;
+use mdi::{Tick, KLine, RetentionPolicy, ValueFormat};
+use mdi::codec;
+use mdi::storage::{RangeOptions, StorageConfig, TickStorage, CF_KLINES, CF_META, CF_TICKS};
+use rocksdb::{DBCompressionType, Options};
 use tempfile::TempDir;
 
 #[test]
@@ -21,6 +23,10 @@ fn test_write_and_read_tick() {
     let read_tick = storage.read_tick("BTCUSDT", 1).unwrap();
     assert!(read_tick.is_some());
     assert_eq!(read_tick.unwrap().price, 100.0);
+
+    // limit 为 0 时不返回任何记录
+    assert!(storage.read_ticks_by_symbol("BTCUSDT", 0).unwrap().is_empty());
+    assert_eq!(storage.read_ticks_by_symbol("BTCUSDT", 1).unwrap().len(), 1);
 }
 
 #[test]
@@ -28,10 +34,456 @@ fn test_write_and_read_kline() {
     let temp_dir = TempDir::new().unwrap();
     let storage = TickStorage::open(temp_dir.path().join("test.db")).unwrap();
 
-    let kline = KLine::new("BTCUSDT".to_string(), 1000, 60, 100.0);
+    let mut kline = KLine::new("BTCUSDT".to_string(), 1000, 60, 100.0);
+    kline.update(&Tick::new("BTCUSDT".to_string(), 1000000, 1000000, 100.0, 2.0, false, 1));
     storage.write_kline(&kline).unwrap();
 
     let read_kline = storage.read_kline("BTCUSDT", 60, 1000).unwrap();
     assert!(read_kline.is_some());
-    assert_eq!(read_kline.unwrap().open, 100.0);
+    let read_kline = read_kline.unwrap();
+    assert_eq!(read_kline.open, 100.0);
+    assert_eq!(read_kline.taker_buy_volume, 2.0);
+    assert_eq!(read_kline.taker_buy_trades, 1);
+}
+
+#[test]
+fn test_binary_codec_roundtrip_and_json_compat() {
+    let tick = Tick::new("BTCUSDT".to_string(), 1000000, 1000001, 100.5, 0.25, true, 42);
+    let binary = codec::encode(&tick, ValueFormat::Binary).unwrap();
+    let json = codec::encode(&tick, ValueFormat::Json).unwrap();
+    assert_eq!(binary[0], codec::TAG_TICK_V1);
+    assert_eq!(binary.len(), 43 + 7);
+    assert!(binary.len() * 2 < json.len());
+
+    for bytes in [&binary, &json] {
+        let decoded: Tick = codec::decode(bytes).unwrap();
+        assert_eq!(decoded.symbol, "BTCUSDT");
+        assert_eq!(decoded.event_time, 1000001);
+        assert_eq!(decoded.price, 100.5);
+        assert_eq!(decoded.quantity, 0.25);
+        assert!(decoded.is_buyer_maker);
+        assert_eq!(decoded.trade_id, 42);
+    }
+    assert!(codec::decode::<Tick>(&binary[..20]).is_err());
+    assert!(codec::decode::<KLine>(&binary).is_err());
+
+    let mut kline = KLine::new("ETHUSDT".to_string(), 60, 60, 10.0);
+    kline.update(&tick);
+    let decoded: KLine = codec::decode(&codec::encode(&kline, ValueFormat::Binary).unwrap()).unwrap();
+    assert_eq!(decoded.symbol, "ETHUSDT");
+    assert_eq!(decoded.high, kline.high);
+    assert_eq!(decoded.taker_sell_volume, 0.25);
+    assert_eq!(decoded.close_time, kline.close_time);
+
+    // 超过 255 字节的品种名不能被截断写入
+    let long = Tick::new("é".repeat(128), 1, 1, 1.0, 1.0, false, 1);
+    assert!(codec::encode(&long, ValueFormat::Binary).is_err());
+    assert!(codec::encode(&long, ValueFormat::Json).is_ok());
+    let max = Tick::new("A".repeat(255), 1, 1, 1.0, 1.0, false, 1);
+    let decoded: Tick = codec::decode(&codec::encode(&max, ValueFormat::Binary).unwrap()).unwrap();
+    assert_eq!(decoded.symbol.len(), 255);
+}
+
+#[test]
+fn test_migrate_json_records_to_binary() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("test.db");
+
+    {
+        let legacy = TickStorage::open_with_format(&path, ValueFormat::Json).unwrap();
+        let ticks: Vec<Tick> = (0..10)
+            .map(|i| Tick::new("BTCUSDT".to_string(), 1000 + i, 1000 + i, 100.0, 1.0, false, i))
+            .collect();
+        legacy.write_ticks(&ticks).unwrap();
+        legacy.write_kline(&KLine::new("BTCUSDT".to_string(), 0, 60, 100.0)).unwrap();
+    }
+
+    let storage = TickStorage::open(&path).unwrap();
+    // 旧记录仍可读取
+    assert_eq!(storage.read_tick("BTCUSDT", 3).unwrap().unwrap().timestamp, 1003);
+    storage.write_tick(&Tick::new("BTCUSDT".to_string(), 2000, 2000, 1.0, 1.0, true, 100)).unwrap();
+
+    let stats = storage.migrate_values().unwrap();
+    assert_eq!(stats.scanned, 12);
+    assert_eq!(stats.migrated, 11);
+    assert_eq!(storage.migrate_values().unwrap().migrated, 0);
+
+    assert_eq!(storage.read_ticks_by_symbol("BTCUSDT", 100).unwrap().len(), 11);
+    assert_eq!(storage.read_kline("BTCUSDT", 60, 0).unwrap().unwrap().open, 100.0);
+}
+
+#[test]
+fn test_klines_and_ticks_in_time_order() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = TickStorage::open(temp_dir.path().join("test.db")).unwrap();
+
+    // 十进制字符串键下 "100" < "99"
+    for ts in [99u64, 100, 1000, 5] {
+        storage.write_kline(&KLine::new("BTCUSDT".to_string(), ts, 60, ts as f64)).unwrap();
+    }
+    storage.write_kline(&KLine::new("BTCUSDT".to_string(), 7, 300, 1.0)).unwrap();
+    storage.write_kline(&KLine::new("ETHUSDT".to_string(), 1, 60, 1.0)).unwrap();
+
+    let timestamps: Vec<u64> = storage
+        .read_klines_by_symbol("BTCUSDT", 60)
+        .unwrap()
+        .iter()
+        .map(|k| k.timestamp)
+        .collect();
+    assert_eq!(timestamps, vec![5, 99, 100, 1000]);
+
+    for (ts, id) in [(3000u64, 9u64), (1000, 10), (2000, 100)] {
+        storage.write_tick(&Tick::new("BTCUSDT".to_string(), ts, ts, 1.0, 1.0, true, id)).unwrap();
+    }
+    let ids: Vec<u64> = storage.read_ticks_by_symbol("BTCUSDT", 10).unwrap().iter().map(|t| t.trade_id).collect();
+    assert_eq!(ids, vec![10, 100, 9]);
+    assert_eq!(storage.read_ticks_since("BTCUSDT", 1500).unwrap().len(), 2);
+    assert_eq!(storage.read_tick("BTCUSDT", 100).unwrap().unwrap().timestamp, 2000);
+    assert!(storage.read_tick("SOLUSDT", 100).unwrap().is_none());
+    assert_eq!(storage.symbols(), vec!["BTCUSDT", "ETHUSDT"]);
+}
+
+#[test]
+fn test_migrate_legacy_string_keys() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("test.db");
+
+    // 旧版数据库：字符串键 + JSON 值
+    {
+        let db = rocksdb::DB::open_default(&path).unwrap();
+        for id in [9u64, 10, 100] {
+            let tick = Tick::new("BTCUSDT".to_string(), 1000 + id, 1000 + id, 1.0, 1.0, true, id);
+            db.put(format!("tick:BTCUSDT:{}", id), serde_json::to_vec(&tick).unwrap()).unwrap();
+        }
+        let kline = KLine::new("BTCUSDT".to_string(), 120, 60, 5.0);
+        db.put("kline:BTCUSDT:60:120", serde_json::to_vec(&kline).unwrap()).unwrap();
+    }
+
+    // 未迁移时拒绝打开，提示执行 migrate
+    let err = TickStorage::open(&path).err().unwrap();
+    assert!(err.to_string().contains("mdi-cli migrate"));
+
+    let storage = TickStorage::open_for_migration(&path).unwrap();
+    assert!(storage.read_tick("BTCUSDT", 10).unwrap().is_none());
+
+    let stats = storage.migrate_keys().unwrap();
+    assert_eq!(stats.migrated, 4);
+    assert_eq!(storage.migrate_keys().unwrap().migrated, 0);
+
+    let ids: Vec<u64> = storage.read_ticks_by_symbol("BTCUSDT", 10).unwrap().iter().map(|t| t.trade_id).collect();
+    assert_eq!(ids, vec![9, 10, 100]);
+    assert_eq!(storage.read_kline("BTCUSDT", 60, 120).unwrap().unwrap().open, 5.0);
+    // 迁移时已按当前格式重新编码
+    assert_eq!(storage.migrate_values().unwrap().migrated, 0);
+
+    drop(storage);
+    let reopened = TickStorage::open(&path).unwrap();
+    assert_eq!(reopened.read_tick("BTCUSDT", 100).unwrap().unwrap().timestamp, 1100);
+}
+
+#[test]
+fn test_column_families_with_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("test.db");
+
+    let mut config = StorageConfig::default();
+    config.ticks.compression = DBCompressionType::None;
+    config.klines.block_cache_mb = 4;
+    config.meta.bloom_bits_per_key = Some(8.0);
+    {
+        let storage = TickStorage::open_with_config(&path, config).unwrap();
+        storage.write_tick(&Tick::new("BTCUSDT".to_string(), 1000, 1000, 1.0, 1.0, true, 1)).unwrap();
+        storage.write_kline(&KLine::new("BTCUSDT".to_string(), 60, 60, 5.0)).unwrap();
+    }
+
+    let cfs = rocksdb::DB::list_cf(&Options::default(), &path).unwrap();
+    for cf in [CF_TICKS, CF_KLINES, CF_META] {
+        assert!(cfs.iter().any(|name| name == cf));
+    }
+
+    // 默认列族中没有数据
+    let db = rocksdb::DB::open_cf(&Options::default(), &path, &cfs).unwrap();
+    assert_eq!(db.iterator(rocksdb::IteratorMode::Start).count(), 0);
+    assert_eq!(db.iterator_cf(db.cf_handle(CF_TICKS).unwrap(), rocksdb::IteratorMode::Start).count(), 2);
+    assert_eq!(db.iterator_cf(db.cf_handle(CF_KLINES).unwrap(), rocksdb::IteratorMode::Start).count(), 1);
+}
+
+#[test]
+fn test_migrate_default_column_family() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("test.db");
+
+    // 列族拆分前的数据库：二进制键全部位于默认列族
+    {
+        let db = rocksdb::DB::open_default(&path).unwrap();
+        let tick = Tick::new("ETHUSDT".to_string(), 1000, 1000, 2.0, 1.0, false, 7);
+        let mut key = vec![0x01];
+        key.extend_from_slice(&3u32.to_be_bytes());
+        key.extend_from_slice(&1000u64.to_be_bytes());
+        key.extend_from_slice(&7u64.to_be_bytes());
+        db.put(&key, codec::encode(&tick, ValueFormat::Binary).unwrap()).unwrap();
+
+        let mut index = vec![0x02];
+        index.extend_from_slice(&3u32.to_be_bytes());
+        index.extend_from_slice(&7u64.to_be_bytes());
+        db.put(&index, 1000u64.to_be_bytes()).unwrap();
+        db.put(b"\x00symbol:ETHUSDT", 3u32.to_be_bytes()).unwrap();
+    }
+
+    assert!(TickStorage::open(&path).is_err());
+    let storage = TickStorage::open_for_migration(&path).unwrap();
+    assert!(storage.read_tick("ETHUSDT", 7).unwrap().is_none());
+
+    assert_eq!(storage.migrate_keys().unwrap().migrated, 3);
+    assert_eq!(storage.migrate_keys().unwrap().migrated, 0);
+    assert_eq!(storage.read_tick("ETHUSDT", 7).unwrap().unwrap().price, 2.0);
+
+    // 迁移后的品种沿用原 id
+    storage.write_tick(&Tick::new("BTCUSDT".to_string(), 2000, 2000, 1.0, 1.0, true, 1)).unwrap();
+    drop(storage);
+    let reopened = TickStorage::open(&path).unwrap();
+    assert_eq!(reopened.symbols(), vec!["BTCUSDT", "ETHUSDT"]);
+    assert_eq!(reopened.read_ticks_by_symbol("ETHUSDT", 10).unwrap().len(), 1);
+    assert_eq!(reopened.read_ticks_by_symbol("BTCUSDT", 10).unwrap().len(), 1);
+}
+
+#[test]
+fn test_read_ticks_range_with_cursor() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = TickStorage::open(temp_dir.path().join("test.db")).unwrap();
+
+    // 每 100ms 一笔，同一毫秒两笔
+    let mut ticks: Vec<Tick> = (0..20u64)
+        .map(|i| Tick::new("BTCUSDT".to_string(), 1000 + i * 100, 0, 1.0, 1.0, true, i * 2))
+        .collect();
+    ticks.push(Tick::new("BTCUSDT".to_string(), 1500, 0, 1.0, 1.0, true, 11));
+    storage.write_ticks(&ticks).unwrap();
+    storage.write_tick(&Tick::new("ETHUSDT".to_string(), 1200, 0, 1.0, 1.0, true, 1)).unwrap();
+
+    // [1200, 1800)
+    let page = storage.read_ticks_range("BTCUSDT", 1200, 1800, &RangeOptions::new()).unwrap();
+    let ids: Vec<u64> = page.items.iter().map(|t| t.trade_id).collect();
+    assert_eq!(ids, vec![4, 6, 8, 10, 11, 12, 14]);
+    assert!(page.next_cursor.is_none());
+
+    // 正向分页
+    let mut collected = Vec::new();
+    let mut options = RangeOptions::new().limit(3);
+    loop {
+        let page = storage.read_ticks_range("BTCUSDT", 1200, 1800, &options).unwrap();
+        collected.extend(page.items.iter().map(|t| t.trade_id));
+        match page.next_cursor {
+            Some(cursor) => options = RangeOptions::new().limit(3).after(cursor),
+            None => break,
+        }
+    }
+    assert_eq!(collected, ids);
+
+    // 反向分页
+    let page = storage.read_ticks_range("BTCUSDT", 1200, 1800, &RangeOptions::new().reverse().limit(4)).unwrap();
+    let first: Vec<u64> = page.items.iter().map(|t| t.trade_id).collect();
+    assert_eq!(first, vec![14, 12, 11, 10]);
+    let options = RangeOptions::new().reverse().limit(4).after(page.next_cursor.unwrap());
+    let page = storage.read_ticks_range("BTCUSDT", 1200, 1800, &options).unwrap();
+    let rest: Vec<u64> = page.items.iter().map(|t| t.trade_id).collect();
+    assert_eq!(rest, vec![8, 6, 4]);
+    assert!(page.next_cursor.is_none());
+}
+
+#[test]
+fn test_read_klines_range() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = TickStorage::open(temp_dir.path().join("test.db")).unwrap();
+
+    let klines: Vec<KLine> = (0..10u64)
+        .map(|i| KLine::new("BTCUSDT".to_string(), i * 60, 60, i as f64))
+        .collect();
+    storage.write_klines(&klines).unwrap();
+    storage.write_kline(&KLine::new("BTCUSDT".to_string(), 120, 300, 0.0)).unwrap();
+
+    let page = storage.read_klines_range("BTCUSDT", 60, 120, 420, &RangeOptions::new()).unwrap();
+    let ts: Vec<u64> = page.items.iter().map(|k| k.timestamp).collect();
+    assert_eq!(ts, vec![120, 180, 240, 300, 360]);
+
+    let page = storage.read_klines_range("BTCUSDT", 60, 0, u64::MAX, &RangeOptions::new().reverse().limit(2)).unwrap();
+    let ts: Vec<u64> = page.items.iter().map(|k| k.timestamp).collect();
+    assert_eq!(ts, vec![540, 480]);
+    let options = RangeOptions::new().reverse().limit(2).after(page.next_cursor.unwrap());
+    let page = storage.read_klines_range("BTCUSDT", 60, 0, u64::MAX, &options).unwrap();
+    let ts: Vec<u64> = page.items.iter().map(|k| k.timestamp).collect();
+    assert_eq!(ts, vec![420, 360]);
+
+    assert!(storage.read_klines_range("SOLUSDT", 60, 0, 1000, &RangeOptions::new()).unwrap().items.is_empty());
+    assert!(storage.read_klines_range("BTCUSDT", 60, 300, 300, &RangeOptions::new()).unwrap().items.is_empty());
+}
+
+#[test]
+fn test_purge_by_retention_policy() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = TickStorage::open(temp_dir.path().join("test.db")).unwrap();
+
+    let day = mdi::retention::DAY_MS;
+    let now = 100 * day;
+    for (id, ts) in [(1u64, 10 * day), (2, 50 * day), (3, 80 * day), (4, 99 * day)] {
+        storage.write_tick(&Tick::new("BTCUSDT".to_string(), ts, ts, 1.0, 1.0, true, id)).unwrap();
+    }
+    let mut klines = Vec::new();
+    for ts in [10 * day, 80 * day] {
+        klines.push(KLine::new("BTCUSDT".to_string(), ts / 1000, 60, 1.0));
+        klines.push(KLine::new("BTCUSDT".to_string(), ts / 1000, 86400, 1.0));
+    }
+    storage.write_klines(&klines).unwrap();
+
+    let policy = RetentionPolicy::new().ticks(30 * day).klines(60, 30 * day);
+    let report = storage.purge(&policy, now).unwrap();
+    assert_eq!(report.ticks, 2);
+    assert_eq!(report.trade_index, 2);
+    assert_eq!(report.klines.get(&60), Some(&1));
+    assert_eq!(report.symbols.get("BTCUSDT"), Some(&5));
+
+    let ids: Vec<u64> = storage.read_ticks_by_symbol("BTCUSDT", 10).unwrap().iter().map(|t| t.trade_id).collect();
+    assert_eq!(ids, vec![3, 4]);
+    assert!(storage.read_tick("BTCUSDT", 1).unwrap().is_none());
+    assert_eq!(storage.read_klines_by_symbol("BTCUSDT", 60).unwrap().len(), 1);
+    // 未配置的周期永久保留
+    assert_eq!(storage.read_klines_by_symbol("BTCUSDT", 86400).unwrap().len(), 2);
+
+    assert!(storage.purge(&policy, now).unwrap().is_empty());
+
+    // 跨多个批次的删除：Tick 与索引保持一致
+    let ticks: Vec<Tick> = (0..25_000u64)
+        .map(|id| Tick::new("ETHUSDT".to_string(), id, id, 1.0, 1.0, false, id))
+        .collect();
+    storage.write_ticks(&ticks).unwrap();
+    assert_eq!(storage.delete_ticks_range("ETHUSDT", 1_000, 23_000).unwrap(), 22_000);
+    assert_eq!(storage.read_ticks_by_symbol("ETHUSDT", 30_000).unwrap().len(), 3_000);
+    assert!(storage.read_tick("ETHUSDT", 1_000).unwrap().is_none());
+    assert!(storage.read_tick("ETHUSDT", 22_999).unwrap().is_none());
+    assert_eq!(storage.read_tick("ETHUSDT", 999).unwrap().unwrap().timestamp, 999);
+    assert_eq!(storage.read_tick("ETHUSDT", 23_000).unwrap().unwrap().timestamp, 23_000);
+
+    // 只删除给定的 Tick，同一区间内的其他 Tick 保留；重写到其他时间戳的 trade_id 保留索引
+    let stale = storage.read_tick("ETHUSDT", 23_001).unwrap().unwrap();
+    storage.write_tick(&Tick::new("ETHUSDT".to_string(), 40_000, 40_000, 1.0, 1.0, false, 23_001)).unwrap();
+    let removed = [storage.read_tick("ETHUSDT", 23_000).unwrap().unwrap(), stale];
+    storage.remove_ticks(&removed).unwrap();
+    assert!(storage.read_tick("ETHUSDT", 23_000).unwrap().is_none());
+    assert_eq!(storage.read_tick("ETHUSDT", 23_001).unwrap().unwrap().timestamp, 40_000);
+    assert_eq!(storage.read_tick("ETHUSDT", 23_002).unwrap().unwrap().timestamp, 23_002);
+    storage.write_tick(&Tick::new("ETHUSDT".to_string(), 23_000, 23_000, 1.0, 1.0, false, 23_000)).unwrap();
+    storage.write_tick(&Tick::new("ETHUSDT".to_string(), 23_001, 23_001, 1.0, 1.0, false, 23_001)).unwrap();
+    let report = storage.purge(&RetentionPolicy::new().ticks(30 * day), now).unwrap();
+    assert_eq!(report.ticks, 3_000);
+    assert_eq!(report.trade_index, 3_000);
+
+    storage.clear().unwrap();
+    assert!(storage.symbols().is_empty());
+    assert!(storage.read_ticks_range("BTCUSDT", 0, u64::MAX, &RangeOptions::new()).unwrap().items.is_empty());
+}
+
+#[test]
+fn test_checkpoint_backup_and_restore() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = TickStorage::open(temp_dir.path().join("test.db")).unwrap();
+    let backup_dir = temp_dir.path().join("backup");
+
+    storage.write_tick(&Tick::new("BTCUSDT".to_string(), 1000, 1000, 1.0, 1.0, true, 1)).unwrap();
+    storage.checkpoint(temp_dir.path().join("checkpoint")).unwrap();
+    let first = storage.backup(&backup_dir, 2).unwrap();
+
+    // 备份后的写入只出现在后续备份中
+    storage.write_tick(&Tick::new("BTCUSDT".to_string(), 2000, 2000, 1.0, 1.0, true, 2)).unwrap();
+    storage.backup(&backup_dir, 2).unwrap();
+    let latest = storage.backup(&backup_dir, 2).unwrap();
+    let backups = TickStorage::list_backups(&backup_dir).unwrap();
+    assert_eq!(backups.len(), 2);
+    assert_eq!(backups.last().unwrap().backup_id, latest.backup_id);
+    assert!(backups.iter().all(|info| info.backup_id != first.backup_id));
+
+    let checkpoint = TickStorage::open(temp_dir.path().join("checkpoint")).unwrap();
+    assert_eq!(checkpoint.read_ticks_by_symbol("BTCUSDT", 10).unwrap().len(), 1);
+
+    let restored_path = temp_dir.path().join("restored");
+    TickStorage::restore(&backup_dir, &restored_path, None).unwrap();
+    let restored = TickStorage::open(&restored_path).unwrap();
+    assert_eq!(restored.read_ticks_by_symbol("BTCUSDT", 10).unwrap().len(), 2);
+    assert_eq!(restored.symbols(), vec!["BTCUSDT"]);
+
+    // 不覆盖已有数据库
+    assert!(TickStorage::restore(&backup_dir, temp_dir.path().join("test.db"), None).is_err());
+
+    // 写入进程持有数据库时，以从实例打开创建检查点和备份，未刷盘的写入也包含在内
+    storage.write_tick(&Tick::new("BTCUSDT".to_string(), 3000, 3000, 1.0, 1.0, true, 3)).unwrap();
+    assert!(TickStorage::open(temp_dir.path().join("test.db")).is_err());
+    let secondary = TickStorage::open_as_secondary(temp_dir.path().join("test.db"), temp_dir.path().join("secondary")).unwrap();
+    assert!(secondary.write_tick(&Tick::new("BTCUSDT".to_string(), 4000, 4000, 1.0, 1.0, true, 4)).is_err());
+    assert_eq!(secondary.read_ticks_by_symbol("BTCUSDT", 10).unwrap().len(), 3);
+
+    // 从实例打开后主实例继续写入、刷盘并压实，追上后可见
+    storage.write_tick(&Tick::new("ETHUSDT".to_string(), 4000, 4000, 1.0, 1.0, true, 4)).unwrap();
+    storage.compact_all().unwrap();
+    assert!(secondary.read_ticks_by_symbol("ETHUSDT", 10).unwrap().is_empty());
+    secondary.catch_up().unwrap();
+    assert_eq!(secondary.read_ticks_by_symbol("ETHUSDT", 10).unwrap().len(), 1);
+    secondary.checkpoint(temp_dir.path().join("live_checkpoint")).unwrap();
+    secondary.backup(&backup_dir, 2).unwrap();
+    drop(secondary);
+
+    let checkpoint = TickStorage::open(temp_dir.path().join("live_checkpoint")).unwrap();
+    assert_eq!(checkpoint.read_ticks_by_symbol("BTCUSDT", 10).unwrap().len(), 3);
+    let restored_path = temp_dir.path().join("live_restored");
+    TickStorage::restore(&backup_dir, &restored_path, None).unwrap();
+    let restored = TickStorage::open(&restored_path).unwrap();
+    assert_eq!(restored.read_ticks_by_symbol("BTCUSDT", 10).unwrap().len(), 3);
+}
+
+#[test]
+fn test_storage_stats() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = TickStorage::open(temp_dir.path().join("test.db")).unwrap();
+    for id in 1..=3 {
+        storage.write_tick(&Tick::new("BTCUSDT".to_string(), 1000 * id, 1000 * id, 1.0, 1.0, true, id)).unwrap();
+    }
+    storage.write_tick(&Tick::new("ETHUSDT".to_string(), 1000, 1000, 1.0, 1.0, true, 1)).unwrap();
+    storage.write_kline(&KLine::new("BTCUSDT".to_string(), 60, 60, 1.0)).unwrap();
+
+    let stats = storage.get_stats().unwrap();
+    assert_eq!(stats.column_families.len(), 4);
+    assert!(stats.column_families[CF_TICKS].memtable_bytes > 0);
+    assert_eq!(stats.column_families[CF_TICKS].sst_files.len(), 7);
+    assert!(stats.column_families[CF_TICKS].write_stalls.contains_key("total-stops"));
+    assert!(stats.column_families[CF_TICKS].block_cache.capacity >= 64 << 20);
+    assert!(!stats.write_stall.stopped);
+    assert!(stats.data.is_empty());
+
+    // 备份会刷盘，之后的读取经过块缓存
+    storage.backup(temp_dir.path().join("backup"), 1).unwrap();
+    storage.read_tick("BTCUSDT", 2).unwrap().unwrap();
+    let stats = storage.get_stats().unwrap();
+    assert!(stats.column_families[CF_TICKS].sst_bytes > 0);
+    assert!(stats.sst_bytes() >= stats.column_families[CF_TICKS].sst_bytes);
+    assert!(stats.block_cache.hits + stats.block_cache.misses > 0);
+    assert!((0.0..=1.0).contains(&stats.block_cache.hit_rate));
+
+    // 压实后 memtable 清空，覆盖写只保留一份
+    for _ in 0..3 {
+        storage.write_tick(&Tick::new("BTCUSDT".to_string(), 1000, 1000, 1.0, 1.0, true, 1)).unwrap();
+    }
+    storage.compact_all().unwrap();
+    let stats = storage.get_stats().unwrap();
+    assert_eq!(stats.column_families[CF_TICKS].immutable_memtables, 0);
+    assert!(stats.column_families[CF_TICKS].sst_bytes > 0);
+
+    let stats = storage.get_detailed_stats().unwrap();
+    let ticks = &stats.data["ticks"];
+    assert_eq!(ticks.total.keys, 4);
+    assert_eq!(ticks.symbols["BTCUSDT"].keys, 3);
+    assert_eq!(ticks.symbols["BTCUSDT"].key_bytes, 3 * 21);
+    assert_eq!(ticks.symbols["ETHUSDT"].keys, 1);
+    assert_eq!(stats.data["trade_index"].total.keys, 4);
+    assert_eq!(stats.data["klines"].symbols["BTCUSDT"].keys, 1);
+    assert_eq!(stats.data["meta"].total.keys, 2);
+    assert!(stats.data["meta"].symbols.is_empty());
+    assert_eq!(stats.total_keys(), 11);
+    assert!(serde_json::to_string(&stats).unwrap().contains("\"hit_rate\""));
 }
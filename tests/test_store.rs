@@ -0,0 +1,123 @@
+use mdi::storage::RangeOptions;
+use mdi::{KLine, MemoryStore, RetentionPolicy, SegmentStore, StoreConfig, Tick, TickStorage, TickStore, VolumeProfile};
+use std::io::Write;
+use tempfile::TempDir;
+
+fn tick(id: u64, ts: u64) -> Tick {
+    Tick::new("BTCUSDT".to_string(), ts, ts, 100.0 + id as f64, 1.0, true, id)
+}
+
+/// 各后端共用的行为检查
+fn check_store(store: &dyn TickStore) {
+    let ticks: Vec<Tick> = (1..=5).map(|id| tick(id, 1000 * id)).collect();
+    store.write_ticks(&ticks).unwrap();
+    store.write_tick(&Tick::new("ETHUSDT".to_string(), 2000, 2000, 10.0, 1.0, true, 1)).unwrap();
+    assert_eq!(store.symbols(), vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()]);
+    assert_eq!(store.read_tick("BTCUSDT", 3).unwrap().unwrap().price, 103.0);
+    assert!(store.read_tick("BTCUSDT", 9).unwrap().is_none());
+
+    // 同一 trade_id 以不同时间戳重写，只保留最新的一笔，批次内重写同样生效
+    let eth = |id: u64, ts: u64| Tick::new("ETHUSDT".to_string(), ts, ts, 10.0, 1.0, true, id);
+    store.write_tick(&eth(2, 9000)).unwrap();
+    store.write_tick(&eth(2, 8000)).unwrap();
+    store.write_ticks(&[eth(3, 9000), eth(3, 7000)]).unwrap();
+    assert_eq!(store.read_tick("ETHUSDT", 2).unwrap().unwrap().timestamp, 8000);
+    assert_eq!(store.read_tick("ETHUSDT", 3).unwrap().unwrap().timestamp, 7000);
+    assert!(store.read_ticks_range("ETHUSDT", 9000, 9000, &RangeOptions::new()).unwrap().items.is_empty());
+    assert_eq!(store.read_ticks_by_symbol("ETHUSDT", 10).unwrap().len(), 3);
+
+    // 分页
+    let page = store.read_ticks_range("BTCUSDT", 1000, 5000, &RangeOptions::new().limit(2)).unwrap();
+    assert_eq!(page.items.iter().map(|t| t.trade_id).collect::<Vec<_>>(), vec![1, 2]);
+    let page = store
+        .read_ticks_range("BTCUSDT", 1000, 5000, &RangeOptions::new().limit(2).after(page.next_cursor.unwrap()))
+        .unwrap();
+    assert_eq!(page.items.iter().map(|t| t.trade_id).collect::<Vec<_>>(), vec![3, 4]);
+    let page = store.read_ticks_range("BTCUSDT", 0, u64::MAX, &RangeOptions::new().reverse().limit(2)).unwrap();
+    assert_eq!(page.items.iter().map(|t| t.trade_id).collect::<Vec<_>>(), vec![5, 4]);
+    assert_eq!(store.read_ticks_since("BTCUSDT", 4000).unwrap().len(), 2);
+
+    let klines: Vec<KLine> = [1, 5, 9].iter().map(|&ts| KLine::new("BTCUSDT".to_string(), ts, 60, 1.0)).collect();
+    store.write_klines(&klines).unwrap();
+    assert!(store.read_kline("BTCUSDT", 60, 5).unwrap().is_some());
+    assert_eq!(store.read_klines_range("BTCUSDT", 60, 1, 9, &RangeOptions::new()).unwrap().items.len(), 2);
+    assert!(store.read_klines_by_symbol("BTCUSDT", 300).unwrap().is_empty());
+
+    store.write_profiles(&[VolumeProfile::new("BTCUSDT".to_string(), 60, 1, 10.0)]).unwrap();
+    assert!(store.read_profile("BTCUSDT", 60, 1).unwrap().is_some());
+
+    store.write_meta("import:test", b"done").unwrap();
+    assert_eq!(store.read_meta("import:test").unwrap(), Some(b"done".to_vec()));
+
+    // 全部只保留最近 3 秒
+    let policy = RetentionPolicy::new().ticks(3000).klines(60, 3000).profiles(3000);
+    let report = store.purge(&policy, 6000).unwrap();
+    assert_eq!(report.ticks, 3);
+    assert_eq!(report.trade_index, 3);
+    assert_eq!(report.klines.get(&60), Some(&1));
+    assert_eq!(report.profiles, 1);
+    assert_eq!(report.symbols.get("BTCUSDT"), Some(&6));
+    assert!(store.read_tick("BTCUSDT", 1).unwrap().is_none());
+    assert_eq!(store.read_ticks_by_symbol("BTCUSDT", 10).unwrap().len(), 3);
+}
+
+#[test]
+fn test_backends_behave_alike() {
+    let temp_dir = TempDir::new().unwrap();
+    check_store(&TickStorage::open(temp_dir.path().join("test.db")).unwrap());
+    check_store(&MemoryStore::new());
+    check_store(&SegmentStore::open(temp_dir.path().join("segments"), 1 << 20).unwrap());
+}
+
+#[test]
+fn test_segment_store_replay() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path().join("segments");
+    {
+        // 小段大小，强制切换段文件
+        let store = SegmentStore::open(&dir, 256).unwrap();
+        check_store(&store);
+        assert!(store.segment_count().unwrap() > 1);
+    }
+
+    // 模拟崩溃留下的不完整记录
+    let last = std::fs::read_dir(&dir).unwrap().map(|e| e.unwrap().path()).max().unwrap();
+    std::fs::OpenOptions::new().append(true).open(&last).unwrap().write_all(&[42, 0, 0, 0, 1, 7]).unwrap();
+
+    let store = SegmentStore::open(&dir, 256).unwrap();
+    let ids: Vec<u64> = store.read_ticks_by_symbol("BTCUSDT", 10).unwrap().iter().map(|t| t.trade_id).collect();
+    assert_eq!(ids, vec![3, 4, 5]);
+    assert!(store.read_kline("BTCUSDT", 60, 1).unwrap().is_none());
+    assert_eq!(store.read_klines_by_symbol("BTCUSDT", 60).unwrap().len(), 2);
+    assert_eq!(store.read_meta("import:test").unwrap(), Some(b"done".to_vec()));
+
+    store.write_tick(&tick(6, 6000)).unwrap();
+    let store = SegmentStore::open(&dir, 256).unwrap();
+    assert_eq!(store.read_ticks_by_symbol("BTCUSDT", 10).unwrap().len(), 4);
+
+    // 长度完整但内容损坏的尾部记录同样被截断
+    let last = std::fs::read_dir(&dir).unwrap().map(|e| e.unwrap().path()).max().unwrap();
+    let len = std::fs::metadata(&last).unwrap().len();
+    std::fs::OpenOptions::new().append(true).open(&last).unwrap().write_all(&[2, 0, 0, 0, 1, 0, 0, 0, 0, 0xff, 0xff]).unwrap();
+    let store = SegmentStore::open(&dir, 256).unwrap();
+    assert_eq!(store.read_ticks_by_symbol("BTCUSDT", 10).unwrap().len(), 4);
+    assert_eq!(std::fs::metadata(&last).unwrap().len(), len);
+
+    store.clear().unwrap();
+    assert_eq!(store.segment_count().unwrap(), 1);
+    assert!(SegmentStore::open(&dir, 256).unwrap().symbols().is_empty());
+}
+
+#[test]
+fn test_store_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("test.db");
+    let store = format!("rocksdb:{}", path.display()).parse::<StoreConfig>().unwrap().open().unwrap();
+    store.write_tick(&tick(1, 1000)).unwrap();
+    assert!(store.read_tick("BTCUSDT", 1).unwrap().is_some());
+
+    assert!(matches!("memory".parse::<StoreConfig>().unwrap(), StoreConfig::Memory));
+    assert!(matches!("segment:/tmp/mdi".parse::<StoreConfig>().unwrap(), StoreConfig::Segment { .. }));
+    assert!("redis:localhost".parse::<StoreConfig>().is_err());
+    assert!("rocksdb:".parse::<StoreConfig>().is_err());
+}
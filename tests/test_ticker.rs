@@ -0,0 +1,72 @@
+use mdi::{Distributor, Tick, TickerBuilder};
+
+fn tick(ts: u64, price: f64, quantity: f64, id: u64) -> Tick {
+    Tick::new("BTCUSDT".to_string(), ts, ts, price, quantity, false, id)
+}
+
+#[test]
+fn test_ticker_rolling_window() {
+    // 10 秒窗口，1 秒分桶
+    let ticker = TickerBuilder::new(10_000, 1000);
+
+    ticker.process_tick(&tick(0, 100.0, 1.0, 1));
+    ticker.process_tick(&tick(2_000, 120.0, 1.0, 2));
+    ticker.process_tick(&tick(4_000, 90.0, 2.0, 3));
+    let stats = ticker.process_tick(&tick(6_000, 110.0, 1.0, 4));
+    assert_eq!(stats.open, 100.0);
+    assert_eq!(stats.high, 120.0);
+    assert_eq!(stats.low, 90.0);
+    assert_eq!(stats.last_price, 110.0);
+    assert_eq!(stats.volume, 5.0);
+    assert_eq!(stats.quote_volume, 510.0);
+    assert_eq!(stats.tick_count, 4);
+    assert!((stats.price_change_percent() - 10.0).abs() < 1e-9);
+    assert!((stats.weighted_avg_price() - 102.0).abs() < 1e-9);
+
+    // 第一、二笔移出窗口
+    let stats = ticker.process_tick(&tick(12_500, 105.0, 1.0, 5));
+    assert_eq!(stats.open, 90.0);
+    assert_eq!(stats.open_time, 4_000);
+    assert_eq!(stats.high, 110.0);
+    assert_eq!(stats.low, 90.0);
+    assert_eq!(stats.volume, 4.0);
+    assert_eq!(stats.tick_count, 3);
+
+    // 无成交时按时间移出
+    ticker.expire(30_000);
+    assert!(ticker.get_ticker("BTCUSDT").is_none());
+    assert!(ticker.get_tickers().is_empty());
+}
+
+#[test]
+fn test_ticker_bucket_larger_than_window() {
+    // 分桶大于窗口时按窗口长度分桶，不能 panic
+    let ticker = TickerBuilder::new(1_000, 60_000);
+    let stats = ticker.process_tick(&tick(59_999, 100.0, 1.0, 1));
+    assert_eq!(stats.tick_count, 1);
+    assert_eq!(stats.last_price, 100.0);
+
+    let stats = ticker.process_tick(&tick(61_500, 101.0, 1.0, 2));
+    assert_eq!(stats.tick_count, 1);
+    assert_eq!(stats.open, 101.0);
+
+    // 零长度窗口同样只保留当前成交
+    let ticker = TickerBuilder::new(0, 0);
+    let stats = ticker.process_tick(&tick(5, 100.0, 1.0, 1));
+    assert_eq!(stats.tick_count, 1);
+}
+
+#[tokio::test]
+async fn test_ticker_distribution() {
+    let ticker = TickerBuilder::daily();
+    let distributor = Distributor::new(16);
+    let mut rx = distributor.subscribe_ticker("BTCUSDT");
+
+    let stats = ticker.process_tick(&tick(1_000_000, 100.0, 2.0, 1));
+    assert_eq!(distributor.broadcast_ticker(stats), 1);
+
+    let received = rx.recv().await.unwrap();
+    assert_eq!(received.symbol, "BTCUSDT");
+    assert_eq!(received.volume, 2.0);
+    assert_eq!(ticker.get_ticker("BTCUSDT").unwrap().last_update, 1_000_000);
+}
@@ -0,0 +1,96 @@
+use mdi::storage::{CF_KLINES, CF_META, CF_QUOTES, CF_TICKS};
+use mdi::verify::Issue;
+use mdi::{KLine, Tick, TickStorage, VerifyOptions};
+use rocksdb::{Options, DB};
+use tempfile::TempDir;
+
+fn tick(id: u64, ts: u64, price: f64) -> Tick {
+    Tick::new("BTCUSDT".to_string(), ts, ts, price, 1.0, false, id)
+}
+
+/// 绕过 TickStorage 直接修改数据库
+fn open_raw(path: &std::path::Path) -> DB {
+    DB::open_cf(&Options::default(), path, ["default", CF_TICKS, CF_KLINES, CF_QUOTES, CF_META]).unwrap()
+}
+
+fn tick_key(symbol_id: u32, timestamp: u64, trade_id: u64) -> Vec<u8> {
+    let mut key = vec![0x01];
+    key.extend_from_slice(&symbol_id.to_be_bytes());
+    key.extend_from_slice(&timestamp.to_be_bytes());
+    key.extend_from_slice(&trade_id.to_be_bytes());
+    key
+}
+
+#[test]
+fn test_verify_clean_storage() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = TickStorage::open(temp_dir.path().join("test.db")).unwrap();
+    let ticks: Vec<Tick> = (1..=20).map(|id| tick(id, 50_000 + id * 1000, 100.0 + id as f64)).collect();
+    storage.write_ticks(&ticks).unwrap();
+    let mut kline = KLine::new("BTCUSDT".to_string(), 60, 60, 110.0);
+    for tick in &ticks[9..] {
+        kline.update(tick);
+    }
+    storage.write_kline(&kline).unwrap();
+    storage.write_tick(&tick(21, 120_500, 1.0)).unwrap();
+
+    let report = storage.verify(&VerifyOptions::new()).unwrap();
+    assert!(report.is_ok(), "{:?}", report.issues);
+    assert_eq!(report.bars_checked, 1);
+    assert_eq!(report.keys.get(CF_TICKS), Some(&42));
+}
+
+#[test]
+fn test_verify_detects_and_repairs() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("test.db");
+    {
+        let storage = TickStorage::open(&path).unwrap();
+        // trade_id 6、7 缺失
+        let ticks: Vec<Tick> = (1..=21)
+            .filter(|id| !(6..=7).contains(id))
+            .map(|id| tick(id, 50_000 + id * 1000, 100.0 + id as f64))
+            .collect();
+        storage.write_ticks(&ticks).unwrap();
+        storage.write_tick(&tick(22, 120_500, 1.0)).unwrap();
+        // 与 Tick 不一致的 K 线
+        let mut kline = KLine::new("BTCUSDT".to_string(), 60, 60, 110.0);
+        kline.high = 500.0;
+        kline.number_of_trades = 8;
+        storage.write_kline(&kline).unwrap();
+    }
+    {
+        let db = open_raw(&path);
+        let ticks = db.cf_handle(CF_TICKS).unwrap();
+        db.put_cf(ticks, tick_key(0, 52_000, 2), b"\xffgarbage").unwrap();
+        // 删除 trade 3 的索引
+        let mut index_key = vec![0x02, 0, 0, 0, 0];
+        index_key.extend_from_slice(&3u64.to_be_bytes());
+        db.delete_cf(ticks, index_key).unwrap();
+    }
+
+    let storage = TickStorage::open(&path).unwrap();
+    // 批量读取跳过损坏的记录
+    assert_eq!(storage.read_ticks_by_symbol("BTCUSDT", 100).unwrap().len(), 19);
+
+    let report = storage.verify(&VerifyOptions::new()).unwrap();
+    let kinds: Vec<&str> = report.issues.iter().map(Issue::kind).collect();
+    assert_eq!(kinds, vec!["corrupt_value", "missing_index", "trade_id_gap", "bar_mismatch"], "{:?}", report.issues);
+    assert_eq!(report.missing_trades, 2);
+    assert!(matches!(&report.issues[2], Issue::TradeIdGap { from: 6, to: 7, .. }));
+    match &report.issues[3] {
+        Issue::BarMismatch { fields, .. } => assert_eq!(fields, &vec!["high", "close", "volume", "number_of_trades"]),
+        issue => panic!("unexpected {:?}", issue),
+    }
+    assert_eq!(report.bars_repaired, 0);
+
+    let report = storage.verify(&VerifyOptions::new().repair(true)).unwrap();
+    assert_eq!(report.bars_repaired, 1);
+    let repaired = storage.read_kline("BTCUSDT", 60, 60).unwrap().unwrap();
+    assert_eq!((repaired.high, repaired.close, repaired.number_of_trades), (121.0, 121.0, 12));
+
+    let report = storage.verify(&VerifyOptions::new().max_issues(1)).unwrap();
+    assert_eq!(report.issues.len(), 1);
+    assert_eq!(report.total_issues(), 3);
+    assert_eq!(report.counts.get("bar_mismatch"), None);
+}
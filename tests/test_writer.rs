@@ -0,0 +1,58 @@
+use mdi::writer::{OverflowPolicy, StorageWriter, WriterConfig};
+use mdi::{KLine, MemoryStore, Tick, TickStore};
+use std::sync::Arc;
+use std::time::Duration;
+
+fn tick(id: u64) -> Tick {
+    Tick::new("BTCUSDT".to_string(), 1000 + id, 1000 + id, 50000.0, 0.1, false, id)
+}
+
+#[test]
+fn test_writer_flushes_on_size_time_and_shutdown() {
+    let storage = MemoryStore::new();
+
+    let config = WriterConfig::default()
+        .batch_size(10)
+        .flush_interval(Duration::from_millis(50));
+    let writer = StorageWriter::spawn(Arc::new(storage.clone()), config);
+
+    // 达到批量大小立即写入
+    for id in 0..10 {
+        writer.write_tick(tick(id)).unwrap();
+    }
+    // 不足一批时按时间阈值写入
+    writer.write_tick(tick(10)).unwrap();
+    writer.write_kline(KLine::new("BTCUSDT".to_string(), 60, 60, 50000.0)).unwrap();
+    std::thread::sleep(Duration::from_millis(300));
+    assert_eq!(storage.read_ticks_by_symbol("BTCUSDT", 100).unwrap().len(), 11);
+    assert!(storage.read_kline("BTCUSDT", 60, 60).unwrap().is_some());
+
+    // 关闭时写完剩余记录
+    for id in 11..15 {
+        writer.write_tick(tick(id)).unwrap();
+    }
+    let stats = writer.shutdown();
+    assert_eq!(stats.ticks, 15);
+    assert_eq!(stats.klines, 1);
+    assert_eq!(stats.dropped, 0);
+    assert_eq!(storage.read_ticks_by_symbol("BTCUSDT", 100).unwrap().len(), 15);
+}
+
+#[test]
+fn test_writer_drop_policy_reports_drops() {
+    let storage = MemoryStore::new();
+
+    let config = WriterConfig::default()
+        .capacity(4)
+        .batch_size(1000)
+        .flush_interval(Duration::from_secs(60))
+        .overflow(OverflowPolicy::Drop);
+    let writer = StorageWriter::spawn(Arc::new(storage.clone()), config);
+
+    let accepted = (0..10_000).filter(|&id| writer.write_tick(tick(id)).is_ok()).count() as u64;
+    let stats = writer.shutdown();
+    assert!(stats.dropped > 0);
+    assert_eq!(stats.ticks + stats.dropped, 10_000);
+    assert_eq!(stats.ticks, accepted);
+    assert_eq!(storage.read_ticks_by_symbol("BTCUSDT", 20_000).unwrap().len() as u64, accepted);
+}